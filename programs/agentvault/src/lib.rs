@@ -1,65 +1,654 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::solana_program::{ed25519_program, sysvar};
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{
+    self, CloseAccount, FreezeAccount, GetAccountDataSize, InitializeAccount3, Mint, MintTo, TokenAccount,
+    TokenInterface, TransferChecked,
+};
 
 declare_id!("AgntVLT1111111111111111111111111111111111111");
 
+/// Seconds in an hour and in a day
+pub const SECONDS_PER_HOUR: i64 = 3_600;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+/// Length of one inactivity decay step
+pub const DECAY_INTERVAL_DAYS: i64 = 30;
+/// Default reputation inactivity decay never pushes below, see
+/// `set_decay_floor`
+pub const DEFAULT_DECAY_FLOOR: u8 = 10;
+/// Default share of the reputation above the floor that each decay
+/// interval takes, in basis points
+pub const DEFAULT_DECAY_FRACTION_BPS: u16 = 1_000;
+/// Default size of an agent's endorsement credit pool, and how many
+/// credits it regains per hour
+pub const DEFAULT_MAX_ENDORSEMENT_CREDITS: u16 = 10;
+pub const DEFAULT_CREDITS_PER_HOUR: u16 = 1;
+/// Default minimum gap between two jobs that credit the same worker from
+/// the same payer
+pub const DEFAULT_JOB_CREDIT_COOLDOWN: i64 = SECONDS_PER_DAY;
+/// Minimum gap between an agent's heartbeats
+pub const HEARTBEAT_INTERVAL: i64 = 3_600;
+/// Default minimum age of an endorsement before it can be revoked
+pub const DEFAULT_REVOKE_COOLDOWN: i64 = 3_600;
+/// Default age a profile must reach before its endorsements are accepted
+pub const DEFAULT_MIN_ENDORSER_AGE: i64 = SECONDS_PER_DAY;
+/// Default lifetime of a registration before it needs `renew_agent`
+pub const DEFAULT_AGENT_TTL: i64 = 10 * 365 * SECONDS_PER_DAY;
+/// Default lifetime of an endorsement before anyone may expire it
+pub const DEFAULT_ENDORSEMENT_TTL: i64 = 365 * SECONDS_PER_DAY;
+/// Longest `valid_for` an endorser may give an endorsement
+pub const MAX_ENDORSEMENT_VALIDITY: i64 = 2 * 365 * SECONDS_PER_DAY;
+/// Default reputation economics, adjustable via `set_reputation_params`
+pub const DEFAULT_BASE_REPUTATION: u8 = 50;
+pub const DEFAULT_ENDORSEMENT_BOOST: u8 = 2;
+pub const DEFAULT_MAX_REPUTATION: u8 = 100;
+/// Default blend used by `recompute_reputation`, in basis points
+pub const DEFAULT_ENDORSEMENT_WEIGHT_BPS: u16 = 6_000;
+pub const DEFAULT_REVIEW_WEIGHT_BPS: u16 = 4_000;
+/// Default age at which `recompute_time_weighted_reputation` counts an
+/// endorsement at half its weight
+pub const DEFAULT_REPUTATION_HALF_LIFE: i64 = 180 * SECONDS_PER_DAY;
+/// Most milestones a job can be split into
+pub const MAX_MILESTONES: usize = 12;
+/// Most skills `endorse_skills` and `revoke_all_for_target` accept in one
+/// call, keeping their per-skill account work within the compute budget
+pub const MAX_BATCH_ENDORSEMENTS: usize = 5;
+/// Most endorsements `revoke_many` revokes per call; each touches six
+/// accounts, so this stays well within the compute budget
+pub const MAX_BATCH_REVOKES: usize = 5;
+/// Most profiles `recompute_batch` rewrites per call
+pub const MAX_BATCH_RECOMPUTE: usize = 10;
+/// Most agents `register_agents_batch` registers in one call, keeping it
+/// within compute and transaction-size limits
+pub const MAX_BATCH_REGISTRATIONS: usize = 5;
+/// Live endorsements an `EndorserIndex` can list; endorsers at the cap must
+/// revoke one before endorsing again
+pub const ENDORSER_INDEX_CAPACITY: usize = 64;
+/// Skills a profile has room for at registration, and the most
+/// `grow_skill_capacity` can raise that to
+pub const DEFAULT_SKILL_CAPACITY: u8 = 10;
+pub const MAX_SKILL_CAPACITY: u8 = 20;
+/// Endorsement counts that earn an agent an `EndorsementMilestone` event
+pub const ENDORSEMENT_MILESTONES: [u32; 4] = [10, 50, 100, 500];
+/// Default endorsement count at which a skill is flagged verified
+pub const DEFAULT_SKILL_VERIFICATION_THRESHOLD: u32 = 50;
+/// Default share of outstanding decay restored per recovery, in basis points
+pub const DEFAULT_RECOVERY_RATE_BPS: u16 = 2_500;
+/// Ceiling of a per-skill reputation score
+pub const MAX_SKILL_REPUTATION: u8 = 100;
+/// Most coarse categories ("defi", "gaming", ...) a profile may list
+pub const MAX_CATEGORIES: usize = 3;
+/// Most key-value attributes a profile may hold
+pub const MAX_ATTRIBUTES: usize = 8;
+/// Most verified domains a profile may show
+pub const MAX_VERIFIED_DOMAINS: usize = 2;
+/// Most protocols a profile may list
+pub const MAX_PROTOCOLS: usize = 4;
+/// Skills a profile must declare to count as complete, see
+/// `AgentProfile::is_complete`
+pub const MIN_COMPLETE_PROFILE_SKILLS: usize = 2;
+/// Most agents a team may hold
+pub const MAX_TEAM_MEMBERS: usize = 10;
+/// Most guardians a profile may name for social recovery
+pub const MAX_GUARDIANS: usize = 5;
+/// Time between a guardian opening a recovery and it taking effect, during
+/// which the owner can still cancel it
+pub const RECOVERY_TIMELOCK: i64 = 7 * SECONDS_PER_DAY;
+/// Time an agent has to undo `request_deactivation` before anyone can
+/// retire it
+pub const DEACTIVATION_GRACE_PERIOD: i64 = 7 * SECONDS_PER_DAY;
+/// Longest away window `set_away` accepts
+pub const MAX_AWAY_DURATION: i64 = 90 * SECONDS_PER_DAY;
+/// Layout versions written by the init paths. Accounts created before the
+/// `version` field existed read it back as 0. A new profile version also
+/// needs its fields added to `profile_layout`.
+pub const PROFILE_VERSION: u8 = 28;
+pub const REGISTRY_VERSION: u8 = 27;
+/// Basis-point denominator for dispute splits
+pub const BPS_DENOMINATOR: u16 = 10_000;
+/// Skills `SkillFrequency` counts; skills beyond it are treated as unseen
+pub const MAX_TRACKED_SKILLS: usize = 64;
+/// Agents declaring a skill at which its endorsements earn the plain boost.
+/// Rarer skills earn up to `MAX_RARITY_BPS` of it, commoner ones down to
+/// `MIN_RARITY_BPS`; see `rarity_weight`.
+pub const RARITY_NEUTRAL_AGENTS: u64 = 10;
+pub const MIN_RARITY_BPS: u64 = 5_000;
+pub const MAX_RARITY_BPS: u64 = 20_000;
+
+// Text limits count UTF-8 bytes, not characters: that is what `max_len`
+// reserves in the account, and skills double as PDA seeds, which are capped
+// at 32 bytes. A name of 8 four-byte emoji is therefore the longest possible.
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+pub const MAX_BIO_LEN: usize = 160;
+pub const MAX_SKILL_LEN: usize = 32;
+pub const MAX_CATEGORY_LEN: usize = 24;
+pub const MAX_ATTRIBUTE_KEY_LEN: usize = 24;
+pub const MAX_ATTRIBUTE_VALUE_LEN: usize = 64;
+pub const MAX_DOMAIN_LEN: usize = 64;
+pub const MAX_ENDPOINT_LEN: usize = 128;
+pub const MAX_PROTOCOL_LEN: usize = 16;
+pub const MAX_REGION_LEN: usize = 16;
+pub const MAX_EXTERNAL_SOURCE_LEN: usize = 32;
+pub const MAX_REVIEW_COMMENT_LEN: usize = 140;
+pub const MAX_REPORT_NOTE_LEN: usize = 100;
+/// `AgentProfile.capabilities` bits. Append new bits; never reuse one.
+/// The agent streams partial results
+pub const CAPABILITY_STREAMING: u64 = 1 << 0;
+/// The agent can call external tools
+pub const CAPABILITY_TOOLS: u64 = 1 << 1;
+/// The agent is staffed for on-call response
+pub const CAPABILITY_ON_CALL: u64 = 1 << 2;
+/// Every defined capability bit
+pub const KNOWN_CAPABILITIES: u64 = CAPABILITY_STREAMING | CAPABILITY_TOOLS | CAPABILITY_ON_CALL;
+
+/// Instruction data prefix of the registration hook call: Anchor's
+/// discriminator for an `on_agent_registered` instruction, i.e. the first 8
+/// bytes of `sha256("global:on_agent_registered")`. See
+/// `invoke_registration_hook` for the full ABI.
+pub const REGISTRATION_HOOK_DISCRIMINATOR: [u8; 8] = [216, 204, 109, 241, 246, 26, 236, 64];
+/// Instruction data prefix of the personhood check: the first 8 bytes of
+/// `sha256("global:verify_personhood")`. See `check_personhood`.
+pub const PERSONHOOD_CHECK_DISCRIMINATOR: [u8; 8] = [164, 119, 199, 126, 141, 110, 235, 179];
+
+/// Prefixes a `metadata_uri` may start with, so frontends can fetch it
+pub const METADATA_URI_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
+/// Prefixes an `endpoint` may start with
+pub const ENDPOINT_SCHEMES: [&str; 2] = ["https://", "wss://"];
+/// Codes `AgentProfile.region` may take. Append new codes (at most
+/// `MAX_REGION_LEN` bytes each); never rename one, as clients filter on them.
+pub const REGIONS: [&str; 10] = [
+    "us-east",
+    "us-west",
+    "ca-central",
+    "sa-east",
+    "eu-west",
+    "eu-central",
+    "me-central",
+    "af-south",
+    "ap-south",
+    "ap-east",
+];
+
 #[program]
 pub mod agentvault {
     use super::*;
 
-    /// Register a new agent in the registry
-    pub fn register_agent(
-        ctx: Context<RegisterAgent>,
+    /// Register a new agent in the registry, optionally crediting the
+    /// registered agent `referrer` (its `agent_id`) with the referral.
+    ///
+    /// With a `hook_program`, that program is called in the same transaction
+    /// (see `invoke_registration_hook`) and the registration fails if it
+    /// does. `remaining_accounts` then holds the hook program followed by
+    /// any accounts it needs. `region`, when given, must be one of `REGIONS`.
+    ///
+    /// While the registry requires proof of personhood, the owner must also
+    /// pass the `personhood_program` and a credential it accepts (see
+    /// `check_personhood`).
+    pub fn register_agent<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterAgent<'info>>,
         name: String,
         metadata_uri: String,
         skills: Vec<String>,
+        categories: Vec<String>,
+        referrer: Option<Pubkey>,
+        bio: Option<String>,
+        capabilities: u64,
+        hook_program: Option<Pubkey>,
+        region: Option<String>,
     ) -> Result<()> {
-        require!(name.len() <= 32, AgentVaultError::NameTooLong);
-        require!(metadata_uri.len() <= 200, AgentVaultError::MetadataUriTooLong);
-        require!(skills.len() <= 10, AgentVaultError::TooManySkills);
+        require!(
+            ctx.accounts.registry_stats.registration_stake == 0,
+            AgentVaultError::InsufficientStake
+        );
 
-        let agent = &mut ctx.accounts.agent_profile;
+        initialize_agent(
+            ctx.accounts,
+            &ctx.bumps,
+            name,
+            metadata_uri,
+            skills,
+            categories,
+            referrer,
+            bio,
+            capabilities,
+            region,
+        )?;
+
+        if let Some(hook_program) = hook_program {
+            ctx.accounts.agent_profile.hook_program = Some(hook_program);
+            // The hook reads the profile, which is otherwise only written
+            // once this handler returns
+            ctx.accounts.agent_profile.exit(&crate::ID)?;
+            invoke_registration_hook(
+                hook_program,
+                &ctx.accounts.agent_profile.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+                ctx.remaining_accounts,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Register a new agent, locking `amount` of the registry's stake token
+    /// in the program vault until the agent deregisters
+    pub fn register_agent_with_stake(
+        ctx: Context<RegisterAgentWithStake>,
+        name: String,
+        metadata_uri: String,
+        skills: Vec<String>,
+        categories: Vec<String>,
+        referrer: Option<Pubkey>,
+        bio: Option<String>,
+        capabilities: u64,
+        region: Option<String>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            amount >= ctx.accounts.register.registry_stats.registration_stake,
+            AgentVaultError::InsufficientStake
+        );
+
+        initialize_agent(
+            &mut ctx.accounts.register,
+            &ctx.bumps.register,
+            name,
+            metadata_uri,
+            skills,
+            categories,
+            referrer,
+            bio,
+            capabilities,
+            region,
+        )?;
+
+        let vault_before = token_amount(&ctx.accounts.stake_vault)?;
+        token_transfer_checked(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.owner_token_account.to_account_info(),
+            &ctx.accounts.stake_mint.to_account_info(),
+            &ctx.accounts.stake_vault.to_account_info(),
+            &ctx.accounts.register.owner.to_account_info(),
+            amount,
+            ctx.accounts.stake_mint.decimals,
+            &[],
+        )?;
+        // A Token-2022 transfer fee would lock up less than `amount`
+        require!(
+            token_amount(&ctx.accounts.stake_vault)? - vault_before == amount,
+            AgentVaultError::InsufficientStake
+        );
+
+        let agent = &mut ctx.accounts.register.agent_profile;
         let clock = Clock::get()?;
 
-        agent.wallet = ctx.accounts.owner.key();
-        agent.name = name;
-        agent.metadata_uri = metadata_uri;
-        agent.skills = skills;
-        agent.reputation = 50; // Base reputation
-        agent.endorsements_received = 0;
-        agent.registered_at = clock.unix_timestamp;
-        agent.last_active = clock.unix_timestamp;
-        agent.bump = ctx.bumps.agent_profile;
+        agent.stake_mint = ctx.accounts.stake_mint.key();
+        agent.staked_amount = amount;
+
+        emit!(StakeDeposited {
+            wallet: agent.wallet,
+            mint: agent.stake_mint,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register up to `MAX_BATCH_REGISTRATIONS` agents in one transaction,
+    /// with `payer` covering rent and fees for all of them. Entry `i` takes
+    /// `names[i]`, `metadata_uris[i]`, `skills[i]` and `categories[i]`, and
+    /// eight `remaining_accounts`: the agent's wallet (which must sign, so
+    /// nobody registers a wallet they don't hold), then its `AgentProfile`,
+    /// `OwnerIndex`, `ReputationHistory`, `BlacklistEntry`,
+    /// `DeregisteredAgent`, `NameRegistry` and `AgentIndex` addresses,
+    /// entries taking consecutive indexes from `next_agent_index`. Any failing entry rolls back the batch. Not
+    /// available while the registry requires proof of personhood.
+    pub fn register_agents_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterAgentsBatch<'info>>,
+        names: Vec<String>,
+        metadata_uris: Vec<String>,
+        skills: Vec<Vec<String>>,
+        categories: Vec<Vec<String>>,
+    ) -> Result<()> {
+        let stats = &ctx.accounts.registry_stats;
+        require!(!stats.paused, AgentVaultError::RegistryPaused);
+        require!(stats.registration_stake == 0, AgentVaultError::InsufficientStake);
+        require!(!stats.require_personhood, AgentVaultError::PersonhoodRequired);
+        require!(
+            !names.is_empty()
+                && names.len() <= MAX_BATCH_REGISTRATIONS
+                && metadata_uris.len() == names.len()
+                && skills.len() == names.len()
+                && categories.len() == names.len(),
+            AgentVaultError::InvalidBatchSize
+        );
+        require!(
+            ctx.remaining_accounts.len() == names.len() * 8,
+            AgentVaultError::BatchAccountsMismatch
+        );
+
+        let name_limit = stats.name_limit();
+        let uri_limit = stats.uri_limit();
+        let skill_limit = stats.skill_limit().min(DEFAULT_SKILL_CAPACITY as usize);
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let treasury = ctx.accounts.treasury.as_ref().map(|t| t.to_account_info());
+        let clock = Clock::get()?;
+        let mut next_index = stats.next_agent_index;
+
+        let entries = names.into_iter().zip(metadata_uris).zip(skills).zip(categories);
+        for ((((name, metadata_uri), skills), categories), accounts) in
+            entries.zip(ctx.remaining_accounts.chunks(8))
+        {
+            let [wallet, profile_info, owner_index_info, history_info, blacklist_info, deregistered_info, name_registry_info, agent_index_info] =
+                accounts
+            else {
+                return err!(AgentVaultError::BatchAccountsMismatch);
+            };
+            require!(wallet.is_signer, AgentVaultError::Unauthorized);
+            require!(name.len() <= name_limit, AgentVaultError::NameTooLong);
+            validate_metadata_uri(&metadata_uri, uri_limit)?;
+            let skills = normalize_skills(skills, skill_limit)?;
+            let categories = normalize_categories(categories)?;
+
+            // As in `initialize_agent`, the blacklist entry exists iff the
+            // program owns its address
+            pda_bump(blacklist_info, &[b"blacklist", wallet.key.as_ref()])?;
+            require!(blacklist_info.owner != &crate::ID, AgentVaultError::WalletBlacklisted);
+            pda_bump(deregistered_info, &[b"deregistered", wallet.key.as_ref()])?;
+            require!(deregistered_info.owner != &crate::ID, AgentVaultError::AgentIdDeregistered);
+            // Tell a repeat registration apart from other creation failures
+            // (see `RegistrationGuard`); both addresses are checked against
+            // their seeds below
+            require!(owner_index_info.data_is_empty(), AgentVaultError::AgentAlreadyRegistered);
+            require!(profile_info.data_is_empty(), AgentVaultError::ProfileAddressTransferred);
+
+            claim_name(&name, wallet.key(), name_registry_info, &payer, &system_program)?;
+            charge_registration_fee(stats, treasury.clone(), None, &payer, &system_program)?;
+
+            let profile_bump = pda_bump(profile_info, &[b"agent", wallet.key.as_ref()])?;
+            let agent = AgentProfile::new(
+                wallet.key(),
+                name,
+                metadata_uri,
+                skills,
+                categories,
+                stats.base_reputation,
+                profile_bump,
+                clock.unix_timestamp,
+                agent_expiry(stats, clock.unix_timestamp),
+                next_index,
+            );
+            record_skill_frequency(&ctx.accounts.skill_frequency, &[], &agent.skills)?;
+            create_pda(
+                &payer,
+                profile_info,
+                &system_program,
+                &[b"agent", wallet.key.as_ref(), &[profile_bump]],
+                &agent,
+            )?;
+
+            let owner_index_bump = pda_bump(owner_index_info, &[b"owner", wallet.key.as_ref()])?;
+            create_pda(
+                &payer,
+                owner_index_info,
+                &system_program,
+                &[b"owner", wallet.key.as_ref(), &[owner_index_bump]],
+                &OwnerIndex {
+                    profile: profile_info.key(),
+                    bump: owner_index_bump,
+                },
+            )?;
+
+            let history_bump = pda_bump(history_info, &[b"reputation_history", wallet.key.as_ref()])?;
+            create_pda(
+                &payer,
+                history_info,
+                &system_program,
+                &[b"reputation_history", wallet.key.as_ref(), &[history_bump]],
+                &ReputationHistory::new(profile_info.key(), history_bump),
+            )?;
+
+            let index_seed = next_index.to_le_bytes();
+            let agent_index_bump = pda_bump(agent_index_info, &[b"agent_idx", index_seed.as_ref()])?;
+            create_pda(
+                &payer,
+                agent_index_info,
+                &system_program,
+                &[b"agent_idx", index_seed.as_ref(), &[agent_index_bump]],
+                &AgentIndex {
+                    index: next_index,
+                    agent: wallet.key(),
+                    deregistered_at: 0,
+                    bump: agent_index_bump,
+                },
+            )?;
+            next_index += 1;
+
+            emit!(AgentRegistered {
+                wallet: agent.wallet,
+                name: agent.name,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        let stats = &mut ctx.accounts.registry_stats;
+        stats.total_agents += (ctx.remaining_accounts.len() / 8) as u64;
+        stats.next_agent_index = next_index;
+
+        Ok(())
+    }
+
+    /// Remove your agent from the registry, reclaiming rent and any stake.
+    /// A small `DeregisteredAgent` record stays behind, and the `agent_id`
+    /// can't register again.
+    pub fn deregister_agent(ctx: Context<DeregisterAgent>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let stake_returned = refund_stake(
+            agent,
+            &ctx.accounts.stake_mint,
+            &ctx.accounts.stake_vault,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.stake_vault,
+        )?;
+
+        release_name(
+            &agent.name,
+            agent.agent_id,
+            &ctx.accounts.name_registry.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+        )?;
+        tombstone_agent_index(agent, &ctx.accounts.agent_index, clock.unix_timestamp)?;
+        record_skill_frequency(&ctx.accounts.skill_frequency, &agent.skills, &[])?;
+        remove_from_leaderboard(&ctx.accounts.leaderboard, agent.agent_id)?;
+
+        let deregistered = &mut ctx.accounts.deregistered_agent;
+        deregistered.agent_id = agent.agent_id;
+        deregistered.slash_count = agent.slash_count;
+        deregistered.deregistered_at = clock.unix_timestamp;
+        deregistered.bump = ctx.bumps.deregistered_agent;
+
+        let stats = &mut ctx.accounts.registry_stats;
+        stats.total_agents = stats.total_agents.saturating_sub(1);
+
+        emit!(AgentDeregistered {
+            wallet: agent.wallet,
+            stake_returned,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close an expired agent's accounts (permissionless), refunding rent
+    /// and any stake to its owner. The caller pays for the
+    /// `DeregisteredAgent` record left behind, as in `deregister_agent`.
+    pub fn deregister_expired(ctx: Context<DeregisterExpired>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(agent.is_expired(clock.unix_timestamp), AgentVaultError::AgentNotExpired);
+
+        let stake_returned = refund_stake(
+            agent,
+            &ctx.accounts.stake_mint,
+            &ctx.accounts.stake_vault,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.stake_vault,
+        )?;
+
+        release_name(
+            &agent.name,
+            agent.agent_id,
+            &ctx.accounts.name_registry.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+        )?;
+        tombstone_agent_index(agent, &ctx.accounts.agent_index, clock.unix_timestamp)?;
+        record_skill_frequency(&ctx.accounts.skill_frequency, &agent.skills, &[])?;
+        remove_from_leaderboard(&ctx.accounts.leaderboard, agent.agent_id)?;
+
+        let deregistered = &mut ctx.accounts.deregistered_agent;
+        deregistered.agent_id = agent.agent_id;
+        deregistered.slash_count = agent.slash_count;
+        deregistered.deregistered_at = clock.unix_timestamp;
+        deregistered.bump = ctx.bumps.deregistered_agent;
 
-        // Update registry stats
         let stats = &mut ctx.accounts.registry_stats;
-        stats.total_agents += 1;
+        stats.total_agents = stats.total_agents.saturating_sub(1);
+
+        emit!(AgentDeregistered {
+            wallet: agent.wallet,
+            stake_returned,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Push your agent's expiry out by the registry's `agent_ttl`, counted
+    /// from now or from the current expiry if that is later. Costs the
+    /// registration fee, if any.
+    pub fn renew_agent(ctx: Context<RenewAgent>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        charge_registration_fee(
+            &ctx.accounts.registry_stats,
+            ctx.accounts.treasury.as_ref().map(|t| t.to_account_info()),
+            None,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let from = std::cmp::max(clock.unix_timestamp, agent.expires_at);
+        agent.expires_at = agent_expiry(&ctx.accounts.registry_stats, from);
+        agent.last_active = clock.unix_timestamp;
 
-        emit!(AgentRegistered {
+        emit!(AgentRenewed {
             wallet: agent.wallet,
-            name: agent.name.clone(),
+            expires_at: agent.expires_at,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Update an existing agent profile
+    /// Update an existing agent profile (owner or delegate). `avatar_hash`
+    /// is the SHA-256 of the avatar image, all zeros for none.
+    /// `private_reputation` can only be changed by the owner. An empty
+    /// `region` clears it. Replacing `skills` needs the `skill_frequency`
+    /// account.
     pub fn update_profile(
-        ctx: Context<UpdateProfile>,
+        ctx: Context<OperateAgent>,
         metadata_uri: Option<String>,
         skills: Option<Vec<String>>,
+        bio: Option<String>,
+        avatar_hash: Option<[u8; 32]>,
+        endpoint: Option<String>,
+        protocols: Option<Vec<String>>,
+        private_reputation: Option<bool>,
+        region: Option<String>,
     ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        // Privacy is the owner's call, not an operational key's
+        require!(
+            private_reputation.is_none() || ctx.accounts.delegate.is_none(),
+            AgentVaultError::Unauthorized
+        );
+
+        let stats = &ctx.accounts.registry_stats;
         let agent = &mut ctx.accounts.agent_profile;
         let clock = Clock::get()?;
 
         if let Some(uri) = metadata_uri {
-            require!(uri.len() <= 200, AgentVaultError::MetadataUriTooLong);
+            validate_metadata_uri(&uri, stats.uri_limit())?;
             agent.metadata_uri = uri;
         }
 
         if let Some(new_skills) = skills {
-            require!(new_skills.len() <= 10, AgentVaultError::TooManySkills);
+            let new_skills = normalize_skills(new_skills, agent.max_skills().min(stats.skill_limit()))?;
+            let Some(skill_frequency) = &ctx.accounts.skill_frequency else {
+                return err!(AgentVaultError::MissingSkillFrequency);
+            };
+            let removed: Vec<String> = agent.skills.iter().filter(|s| !new_skills.contains(s)).cloned().collect();
+            let added: Vec<String> = new_skills.iter().filter(|s| !agent.skills.contains(s)).cloned().collect();
+            record_skill_frequency(skill_frequency, &removed, &added)?;
+            // Skills that stay keep their verified flag
+            let verified_skills = new_skills
+                .iter()
+                .enumerate()
+                .filter(|(_, skill)| agent.is_skill_verified(skill))
+                .fold(0, |mask, (i, _)| mask | 1 << i);
             agent.skills = new_skills;
+            agent.verified_skills = verified_skills;
+        }
+
+        if let Some(bio) = bio {
+            require!(bio.len() <= MAX_BIO_LEN, AgentVaultError::BioTooLong);
+            agent.bio = bio;
+        }
+
+        if let Some(endpoint) = endpoint {
+            validate_endpoint(&endpoint)?;
+            agent.endpoint = endpoint;
+        }
+
+        if let Some(protocols) = protocols {
+            agent.protocols = normalize_protocols(protocols)?;
+        }
+
+        if let Some(region) = region {
+            validate_region(&region)?;
+            agent.region = region;
+        }
+
+        if let Some(private_reputation) = private_reputation {
+            agent.private_reputation = private_reputation;
+        }
+
+        if let Some(avatar_hash) = avatar_hash {
+            agent.avatar_hash = avatar_hash;
+            emit!(AvatarUpdated {
+                wallet: agent.wallet,
+                avatar_hash,
+                timestamp: clock.unix_timestamp,
+            });
         }
 
         agent.last_active = clock.unix_timestamp;
@@ -72,49 +661,223 @@ pub mod agentvault {
         Ok(())
     }
 
-    /// Endorse another agent's skill
-    pub fn endorse_skill(
-        ctx: Context<EndorseSkill>,
-        skill: String,
-    ) -> Result<()> {
-        require!(skill.len() <= 32, AgentVaultError::SkillNameTooLong);
-        
-        // Can't endorse yourself
+    /// Call the profile's registration hook again (owner only), e.g. after
+    /// the hook program was upgraded or a first call's side effect was
+    /// undone. `remaining_accounts` is laid out as for `register_agent`.
+    pub fn rerun_registration_hook<'info>(ctx: Context<'_, '_, 'info, 'info, UpdateProfile<'info>>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let hook_program = ctx
+            .accounts
+            .agent_profile
+            .hook_program
+            .ok_or(AgentVaultError::InvalidHookProgram)?;
+        invoke_registration_hook(
+            hook_program,
+            &ctx.accounts.agent_profile.to_account_info(),
+            &ctx.accounts.owner.to_account_info(),
+            ctx.remaining_accounts,
+        )
+    }
+
+    /// Publish where and how to reach the agent (owner or delegate). An
+    /// empty `endpoint` clears it.
+    pub fn set_endpoint(ctx: Context<OperateAgent>, endpoint: String, protocols: Vec<String>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        validate_endpoint(&endpoint)?;
+        let protocols = normalize_protocols(protocols)?;
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        agent.endpoint = endpoint;
+        agent.protocols = protocols;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(ProfileUpdated {
+            wallet: agent.wallet,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the profile's categories (owner or delegate)
+    pub fn set_categories(ctx: Context<OperateAgent>, categories: Vec<String>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        agent.categories = normalize_categories(categories)?;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(ProfileUpdated {
+            wallet: agent.wallet,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the agent's capability bits (owner or delegate); see the
+    /// `CAPABILITY_*` constants
+    pub fn set_capabilities(ctx: Context<OperateAgent>, capabilities: u64) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        validate_capabilities(capabilities)?;
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        agent.capabilities = capabilities;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(CapabilitiesUpdated {
+            wallet: agent.wallet,
+            capabilities,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Change your agent's name, freeing the old name and claiming the new
+    /// one in the same transaction
+    pub fn rename_agent(ctx: Context<RenameAgent>, new_name: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
         require!(
-            ctx.accounts.endorser.key() != ctx.accounts.target_agent.wallet,
-            AgentVaultError::CannotEndorseSelf
+            new_name.len() <= ctx.accounts.registry_stats.name_limit(),
+            AgentVaultError::NameTooLong
         );
 
-        // Target must have this skill declared
+        let agent = &mut ctx.accounts.agent_profile;
+        let owner = ctx.accounts.owner.to_account_info();
+        let clock = Clock::get()?;
+
+        // Release first, so a change of case alone reclaims the same PDA
+        release_name(
+            &agent.name,
+            agent.agent_id,
+            &ctx.accounts.old_name_registry.to_account_info(),
+            &owner,
+        )?;
+        claim_name(
+            &new_name,
+            agent.agent_id,
+            &ctx.accounts.new_name_registry.to_account_info(),
+            &owner,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let old_name = std::mem::replace(&mut agent.name, new_name);
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(AgentRenamed {
+            wallet: agent.wallet,
+            old_name,
+            new_name: agent.name.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Make room for up to `capacity` skills (at most `MAX_SKILL_CAPACITY`),
+    /// growing the profile account; the owner pays the extra rent
+    pub fn grow_skill_capacity(ctx: Context<GrowSkillCapacity>, capacity: u8) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_profile;
+
         require!(
-            ctx.accounts.target_agent.skills.contains(&skill),
-            AgentVaultError::SkillNotDeclared
+            capacity as usize > agent.max_skills() && capacity <= MAX_SKILL_CAPACITY,
+            AgentVaultError::InvalidSkillCapacity
         );
+        agent.skill_capacity = capacity;
 
-        let endorsement = &mut ctx.accounts.endorsement;
+        Ok(())
+    }
+
+    /// Mint the agent's soulbound reputation token: one unit of a
+    /// per-agent mint, into a token account owned by the agent's wallet and
+    /// frozen straight away so it can never move. Both addresses are PDAs
+    /// of the `agent_id`, so anyone holding the token can find the on-chain
+    /// profile (and its live reputation) it stands for. Once per agent.
+    pub fn mint_reputation_token(ctx: Context<MintReputationToken>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            !ctx.accounts.agent_profile.reputation_token_minted,
+            AgentVaultError::ReputationTokenAlreadyMinted
+        );
+
+        let mint = ctx.accounts.reputation_mint.to_account_info();
+        let token_account = ctx.accounts.reputation_token_account.to_account_info();
+        let authority = ctx.accounts.mint_authority.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let authority_seeds: &[&[u8]] = &[b"reputation_authority", &[ctx.bumps.mint_authority]];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                MintTo {
+                    mint: mint.clone(),
+                    to: token_account.clone(),
+                    authority: authority.clone(),
+                },
+                &[authority_seeds],
+            ),
+            1,
+        )?;
+        token_interface::freeze_account(CpiContext::new_with_signer(
+            token_program,
+            FreezeAccount {
+                account: token_account,
+                mint: mint.clone(),
+                authority,
+            },
+            &[authority_seeds],
+        ))?;
+
+        let agent = &mut ctx.accounts.agent_profile;
         let clock = Clock::get()?;
+        agent.reputation_token_minted = true;
 
-        endorsement.endorser = ctx.accounts.endorser.key();
-        endorsement.target = ctx.accounts.target_agent.wallet;
-        endorsement.skill = skill.clone();
-        endorsement.timestamp = clock.unix_timestamp;
-        endorsement.bump = ctx.bumps.endorsement;
+        emit!(ReputationTokenMinted {
+            wallet: agent.wallet,
+            mint: mint.key(),
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Update target's endorsement count and reputation
-        let target = &mut ctx.accounts.target_agent;
-        target.endorsements_received += 1;
-        
-        // Reputation boost: min(100, current + 2)
-        target.reputation = std::cmp::min(100, target.reputation + 2);
-        target.last_active = clock.unix_timestamp;
+        Ok(())
+    }
 
-        // Update endorser's last active
-        let endorser_profile = &mut ctx.accounts.endorser_profile;
-        endorser_profile.last_active = clock.unix_timestamp;
+    /// Declare one additional skill on your profile. Needs the
+    /// `skill_frequency` account.
+    pub fn add_skill(ctx: Context<UpdateProfile>, skill: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        let skill = normalize_skill(&skill)?;
 
-        emit!(SkillEndorsed {
-            endorser: endorsement.endorser,
-            target: endorsement.target,
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        // Profiles from before normalization may hold mixed-case skills
+        require!(
+            !agent.skills.iter().any(|s| s.trim().to_lowercase() == skill),
+            AgentVaultError::SkillAlreadyDeclared
+        );
+        require!(
+            agent.skills.len() < agent.max_skills().min(ctx.accounts.registry_stats.skill_limit()),
+            AgentVaultError::TooManySkills
+        );
+
+        let Some(skill_frequency) = &ctx.accounts.skill_frequency else {
+            return err!(AgentVaultError::MissingSkillFrequency);
+        };
+        record_skill_frequency(skill_frequency, &[], &[skill.clone()])?;
+
+        agent.skills.push(skill.clone());
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(SkillAdded {
+            wallet: agent.wallet,
             skill,
             timestamp: clock.unix_timestamp,
         });
@@ -122,234 +885,9836 @@ pub mod agentvault {
         Ok(())
     }
 
-    /// Revoke a previously given endorsement
-    pub fn revoke_endorsement(ctx: Context<RevokeEndorsement>) -> Result<()> {
-        let endorsement = &ctx.accounts.endorsement;
-        let target = &mut ctx.accounts.target_agent;
+    /// Remove a declared skill. Its endorsement counter and skill reputation
+    /// are kept, so re-adding the skill later restores its history. Needs
+    /// the `skill_frequency` account.
+    pub fn remove_skill(ctx: Context<UpdateProfile>, skill: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
         let clock = Clock::get()?;
 
-        // Decrease endorsement count
-        target.endorsements_received = target.endorsements_received.saturating_sub(1);
-        
-        // Reputation penalty: max(0, current - 2)
-        target.reputation = target.reputation.saturating_sub(2);
+        let index = agent
+            .skills
+            .iter()
+            .position(|s| *s == skill)
+            .ok_or(AgentVaultError::SkillNotFound)?;
+        let Some(skill_frequency) = &ctx.accounts.skill_frequency else {
+            return err!(AgentVaultError::MissingSkillFrequency);
+        };
+        record_skill_frequency(skill_frequency, &[skill.clone()], &[])?;
+        agent.skills.remove(index);
+        // Shift the verified flags of the later skills down with them
+        let below = (1u32 << index) - 1;
+        agent.verified_skills = (agent.verified_skills & below) | ((agent.verified_skills >> 1) & !below);
+        agent.last_active = clock.unix_timestamp;
 
-        emit!(EndorsementRevoked {
-            endorser: endorsement.endorser,
-            target: endorsement.target,
-            skill: endorsement.skill.clone(),
+        emit!(SkillRemoved {
+            wallet: agent.wallet,
+            skill,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Initialize the registry (one-time setup)
-    pub fn initialize_registry(ctx: Context<InitializeRegistry>) -> Result<()> {
-        let stats = &mut ctx.accounts.registry_stats;
-        stats.total_agents = 0;
-        stats.total_endorsements = 0;
-        stats.authority = ctx.accounts.authority.key();
-        stats.bump = ctx.bumps.registry_stats;
+    /// Set a profile attribute (owner or delegate), replacing the value if
+    /// `key` is already present
+    pub fn set_attribute(ctx: Context<OperateAgent>, key: String, value: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            !key.is_empty() && key.len() <= MAX_ATTRIBUTE_KEY_LEN,
+            AgentVaultError::InvalidAttributeKey
+        );
+        require!(value.len() <= MAX_ATTRIBUTE_VALUE_LEN, AgentVaultError::AttributeValueTooLong);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        match agent.attributes.iter_mut().find(|a| a.key == key) {
+            Some(attribute) => attribute.value = value.clone(),
+            None => {
+                require!(agent.attributes.len() < MAX_ATTRIBUTES, AgentVaultError::TooManyAttributes);
+                agent.attributes.push(Attribute {
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(AttributeSet {
+            wallet: agent.wallet,
+            key,
+            value,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
-}
 
-// ============================================================================
-// Accounts
-// ============================================================================
+    /// Remove a profile attribute (owner or delegate)
+    pub fn remove_attribute(ctx: Context<OperateAgent>, key: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
 
-#[derive(Accounts)]
-pub struct InitializeRegistry<'info> {
-    #[account(
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let index = agent
+            .attributes
+            .iter()
+            .position(|a| a.key == key)
+            .ok_or(AgentVaultError::AttributeNotFound)?;
+        agent.attributes.remove(index);
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(AttributeRemoved {
+            wallet: agent.wallet,
+            key,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record that the owner controls `domain` (a web domain, or a handle
+    /// such as "github.com/alice"). The registry's `domain_verifier` checks
+    /// control off-chain and signs `domain_challenge(domain, wallet)`; the
+    /// transaction must carry that signature in an ed25519 program
+    /// instruction immediately before this one.
+    pub fn verify_domain(ctx: Context<VerifyDomain>, domain: String, signature: [u8; 64]) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        let verifier = ctx.accounts.registry_stats.domain_verifier;
+        require!(verifier != Pubkey::default(), AgentVaultError::DomainVerifierNotSet);
+        let domain = normalize_domain(&domain)?;
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let instructions = ctx.accounts.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)? as usize;
+        require!(current > 0, AgentVaultError::DomainSignatureInvalid);
+        let ed25519_ix = load_instruction_at_checked(current - 1, &instructions)?;
+        verify_ed25519_signature(
+            &ed25519_ix,
+            &verifier,
+            &domain_challenge(&domain, &agent.wallet),
+            &signature,
+        )?;
+
+        if !agent.verified_domains.contains(&domain) {
+            require!(
+                agent.verified_domains.len() < MAX_VERIFIED_DOMAINS,
+                AgentVaultError::TooManyDomains
+            );
+            agent.verified_domains.push(domain.clone());
+        }
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(DomainVerified {
+            wallet: agent.wallet,
+            domain,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Mark your agent as alive (owner or delegate), refreshing
+    /// `last_active` so a quiet agent isn't decayed. At most once per
+    /// `HEARTBEAT_INTERVAL`.
+    ///
+    /// Also extends the agent's streak of heartbeat days. Days are UTC
+    /// calendar days (`timestamp / SECONDS_PER_DAY`): a heartbeat the
+    /// day after the previous one extends the streak, one on the same day
+    /// leaves it as is and a skipped day starts it over at 1. Other activity
+    /// doesn't count, since it needn't come from the agent itself.
+    pub fn heartbeat(ctx: Context<OperateAgent>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= agent.last_heartbeat_at + HEARTBEAT_INTERVAL,
+            AgentVaultError::HeartbeatTooSoon
+        );
+
+        let today = clock.unix_timestamp / SECONDS_PER_DAY;
+        let last_day = agent.last_heartbeat_at / SECONDS_PER_DAY;
+        agent.current_streak = if agent.last_heartbeat_at == 0 || today > last_day + 1 {
+            1
+        } else if today == last_day + 1 {
+            agent.current_streak.saturating_add(1)
+        } else {
+            // Profiles from before streaks were tracked start at 0
+            agent.current_streak.max(1)
+        };
+        if agent.current_streak > agent.longest_streak {
+            agent.longest_streak = agent.current_streak;
+
+            emit!(StreakUpdated {
+                wallet: agent.wallet,
+                current_streak: agent.current_streak,
+                longest_streak: agent.longest_streak,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        agent.last_heartbeat_at = clock.unix_timestamp;
+        agent.last_active = clock.unix_timestamp;
+
+        if let Some(history) = ctx.accounts.reputation_history.as_mut() {
+            recover_reputation(agent, &ctx.accounts.registry_stats, history, clock.unix_timestamp);
+        }
+
+        Ok(())
+    }
+
+    /// Authorize an operational key to update the profile and send
+    /// heartbeats on the owner's behalf, replacing any current delegate
+    pub fn set_delegate(ctx: Context<SetDelegate>, delegate: Pubkey) -> Result<()> {
+        let record = &mut ctx.accounts.delegate;
+        let clock = Clock::get()?;
+
+        record.agent = ctx.accounts.agent_profile.agent_id;
+        record.delegate = delegate;
+        record.set_at = clock.unix_timestamp;
+        record.bump = ctx.bumps.delegate;
+
+        emit!(DelegateSet {
+            wallet: ctx.accounts.owner.key(),
+            delegate,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw the delegate's authority
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        emit!(DelegateRevoked {
+            wallet: ctx.accounts.owner.key(),
+            delegate: ctx.accounts.delegate.delegate,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Suspend, reactivate or retire your own agent
+    pub fn set_status(ctx: Context<UpdateProfile>, status: AgentStatus) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let old_status = agent.status;
+        agent.status = status;
+        agent.last_active = clock.unix_timestamp;
+        // An explicit status supersedes a pending deactivation
+        agent.reactivate_before = 0;
+
+        emit!(StatusChanged {
+            wallet: agent.wallet,
+            old_status,
+            new_status: status,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Suspend your own agent at once, e.g. after a key compromise, and
+    /// retire it for good unless `reactivate` is called within
+    /// `DEACTIVATION_GRACE_PERIOD`. Works while paused.
+    pub fn request_deactivation(ctx: Context<UpdateProfile>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(agent.reactivate_before == 0, AgentVaultError::DeactivationPending);
+        require!(agent.status != AgentStatus::Retired, AgentVaultError::AgentNotActive);
+
+        let old_status = agent.status;
+        agent.status = AgentStatus::Suspended;
+        agent.reactivate_before = clock.unix_timestamp + DEACTIVATION_GRACE_PERIOD;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(StatusChanged {
+            wallet: agent.wallet,
+            old_status,
+            new_status: agent.status,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(DeactivationRequested {
+            wallet: agent.wallet,
+            reactivate_before: agent.reactivate_before,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Call off a pending deactivation within its grace period (owner or
+    /// guardian), making the agent active again. Works while paused.
+    pub fn reactivate(ctx: Context<Reactivate>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(agent.reactivate_before != 0, AgentVaultError::NoPendingDeactivation);
+        require!(
+            clock.unix_timestamp < agent.reactivate_before,
+            AgentVaultError::DeactivationGraceElapsed
+        );
+
+        let old_status = agent.status;
+        agent.status = AgentStatus::Active;
+        agent.reactivate_before = 0;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(StatusChanged {
+            wallet: agent.wallet,
+            old_status,
+            new_status: agent.status,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Retire an agent whose deactivation grace period has run out
+    /// (permissionless)
+    pub fn finalize_deactivation(ctx: Context<FinalizeDeactivation>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(agent.reactivate_before != 0, AgentVaultError::NoPendingDeactivation);
+        require!(
+            clock.unix_timestamp >= agent.reactivate_before,
+            AgentVaultError::DeactivationGraceActive
+        );
+
+        let old_status = agent.status;
+        agent.status = AgentStatus::Retired;
+        agent.reactivate_before = 0;
+        remove_from_leaderboard(&ctx.accounts.leaderboard, agent.agent_id)?;
+
+        emit!(StatusChanged {
+            wallet: agent.wallet,
+            old_status,
+            new_status: agent.status,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Endorse another agent's skill. `skill` must already be in canonical
+    /// form (see `normalize_skill`): the PDA seeds are derived from it before
+    /// the handler runs, so it can't be normalized here.
+    ///
+    /// `valid_for` limits the endorsement to that many seconds, for trust
+    /// that is only meant to hold for a while; the registry's
+    /// `endorsement_ttl` still applies if it is shorter. `None` uses the TTL
+    /// alone. Either way `expire_endorsement` cleans up afterwards.
+    pub fn endorse_skill(
+        ctx: Context<EndorseSkill>,
+        skill: String,
+        rating: u8,
+        valid_for: Option<i64>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            !matches!(valid_for, Some(seconds) if seconds <= 0 || seconds > MAX_ENDORSEMENT_VALIDITY),
+            AgentVaultError::InvalidExpiry
+        );
+        require!(normalize_skill(&skill)? == skill, AgentVaultError::InvalidSkillFormat);
+        require!((1..=5).contains(&rating), AgentVaultError::InvalidRating);
+        
+        // Can't endorse yourself
+        require!(
+            ctx.accounts.endorser.key() != ctx.accounts.target_agent.wallet,
+            AgentVaultError::CannotEndorseSelf
+        );
+        check_endorser(
+            &ctx.accounts.endorser_profile,
+            &ctx.accounts.registry_stats,
+            Clock::get()?.unix_timestamp,
+        )?;
+        require!(
+            !ctx.accounts.registry_stats.require_endorser_has_skill
+                || ctx.accounts.endorser_profile.skills.contains(&skill),
+            AgentVaultError::EndorserLacksSkill
+        );
+
+        require!(
+            ctx.accounts.target_agent.status == AgentStatus::Active,
+            AgentVaultError::AgentNotActive
+        );
+        require!(
+            !ctx.accounts.target_agent.is_expired(Clock::get()?.unix_timestamp),
+            AgentVaultError::AgentExpired
+        );
+        require!(
+            !ctx.accounts.registry_stats.require_complete_profile || ctx.accounts.target_agent.is_complete(),
+            AgentVaultError::ProfileIncomplete
+        );
+
+        // Target must have this skill declared
+        require!(
+            ctx.accounts.target_agent.skills.contains(&skill),
+            AgentVaultError::SkillNotDeclared
+        );
+
+        let deposit = lock_endorsement_deposit(
+            &ctx.accounts.registry_stats,
+            &ctx.accounts.endorser.to_account_info(),
+            &ctx.accounts.endorsement.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let endorsement = &mut ctx.accounts.endorsement;
+        let clock = Clock::get()?;
+
+        endorsement.endorser = ctx.accounts.endorser.key();
+        endorsement.target = ctx.accounts.target_agent.agent_id;
+        endorsement.skill = skill.clone();
+        endorsement.timestamp = clock.unix_timestamp;
+        endorsement.bump = ctx.bumps.endorsement;
+        endorsement.rating = rating;
+        let lifetime = valid_for.map_or(ctx.accounts.registry_stats.endorsement_ttl, |seconds| {
+            std::cmp::min(seconds, ctx.accounts.registry_stats.endorsement_ttl)
+        });
+        endorsement.expires_at = clock.unix_timestamp.saturating_add(lifetime);
+        endorsement.deposit = deposit;
+
+        // Update target's endorsement count and reputation
+        let target = &mut ctx.accounts.target_agent;
+        target.endorsements_received += 1;
+        emit_endorsement_milestone(target, clock.unix_timestamp);
+        
+        // Rarer skills earn a larger boost than common ones. Mutual
+        // endorsements of the same skill are a reputation-washing pattern,
+        // so they only earn half the usual boost.
+        let stats = &ctx.accounts.registry_stats;
+        let skill_frequency = load_skill_frequency(&ctx.accounts.skill_frequency)?;
+        let mut weight = rarity_weight(
+            algorithm_weight(
+                stats,
+                ctx.accounts.endorser_profile.reputation,
+                target.endorsements_received - 1,
+                rating,
+            ),
+            skill_frequency.as_ref(),
+            &skill,
+        );
+        let reciprocal = ctx.accounts.reverse_endorsement.is_some();
+        if let Some(reverse) = ctx.accounts.reverse_endorsement.as_mut() {
+            reverse.reciprocal = true;
+            weight /= 2;
+        }
+        endorsement.reciprocal = reciprocal;
+
+        // Each further skill from the same endorser is worth less
+        let pair_count = &mut ctx.accounts.pair_count;
+        weight = diminished_weight(weight, pair_count.count);
+        pair_count.endorser = ctx.accounts.endorser.key();
+        pair_count.target = target.agent_id;
+        add_pair_endorsement(pair_count, target);
+        pair_count.bump = ctx.bumps.pair_count;
+
+        let endorser_index = &mut ctx.accounts.endorser_index;
+        endorser_index.endorser = ctx.accounts.endorser.key();
+        endorser_index.bump = ctx.bumps.endorser_index;
+        endorser_index.add(target.agent_id, &skill)?;
+
+        // Reputation boost, clamped to max_reputation
+        let old_reputation = target.reputation;
+        apply_reputation_delta(target, weight as i16, stats);
+        target.last_active = clock.unix_timestamp;
+
+        // The same boost feeds this skill's own score, clamped separately
+        let skill_reputation = &mut ctx.accounts.skill_reputation;
+        let old_skill_score = skill_reputation.score;
+        skill_reputation.agent = target.key();
+        skill_reputation.skill = skill.clone();
+        skill_reputation.score = std::cmp::min(
+            MAX_SKILL_REPUTATION,
+            skill_reputation.score.saturating_add(weight),
+        );
+        skill_reputation.bump = ctx.bumps.skill_reputation;
+        endorsement.skill_weight = skill_reputation.score - old_skill_score;
+        update_skill_leaderboard(&ctx.accounts.skill_leaderboard, target.agent_id, skill_reputation.score)?;
+
+        // Record the boost actually applied (after clamping) so a later
+        // revoke removes exactly this amount
+        let weight = target.reputation.saturating_sub(old_reputation);
+        endorsement.weight = weight;
+        target.endorsement_points = target.endorsement_points.saturating_add(weight as u32);
+
+        log_reputation_change(
+            &mut ctx.accounts.reputation_history,
+            target,
+            old_reputation,
+            ReputationReason::Endorsed,
+            clock.unix_timestamp,
+        );
+        // Recovered points aren't part of the endorsement's weight, so a
+        // revoke leaves them in place
+        recover_reputation(target, stats, &mut ctx.accounts.reputation_history, clock.unix_timestamp);
+        update_leaderboard(&ctx.accounts.leaderboard, target.agent_id, target.reputation)?;
+
+        // Update the per-skill counter (created on the skill's first endorsement)
+        let skill_count = &mut ctx.accounts.skill_count;
+        skill_count.agent = target.key();
+        skill_count.skill = skill.clone();
+        skill_count.count += 1;
+        skill_count.bump = ctx.bumps.skill_count;
+        sync_skill_verification(
+            target,
+            &skill,
+            skill_count.count,
+            ctx.accounts.registry_stats.skill_verification_threshold,
+            clock.unix_timestamp,
+        );
+
+        // Update endorser's last active and spend one of its credits
+        let endorser_profile = &mut ctx.accounts.endorser_profile;
+        spend_endorsement_credits(endorser_profile, &ctx.accounts.registry_stats, 1, clock.unix_timestamp)?;
+        endorser_profile.last_endorsement_at = clock.unix_timestamp;
+        endorser_profile.last_active = clock.unix_timestamp;
+
+        // Update registry stats
+        let stats = &mut ctx.accounts.registry_stats;
+        stats.total_endorsements += 1;
+
+        emit!(SkillEndorsed {
+            endorser: endorsement.endorser,
+            target: endorsement.target,
+            skill,
+            rating,
+            weight: target.public_score(weight),
+            reciprocal,
+            skill_endorsements: skill_count.count,
+            skill_reputation: target.public_score(skill_reputation.score),
+            total_endorsements: stats.total_endorsements,
+            endorsements_received: target.endorsements_received,
+            distinct_endorsers: target.distinct_endorsers,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Endorse up to `MAX_BATCH_ENDORSEMENTS` of another agent's skills at
+    /// one rating. For each skill, in order, `remaining_accounts` holds its
+    /// `Endorsement`, `SkillEndorsementCount`, `SkillReputation` and reverse
+    /// endorsement addresses (writable; the reverse one need not exist).
+    /// Callers may add each skill's `SkillLeaderboard` address as a fifth
+    /// account, for every skill or none; boards that exist are updated as in
+    /// `endorse_skill`.
+    ///
+    /// The batch fails as a whole if any skill is undeclared or already
+    /// endorsed, logging the offending skill. The summed boost is applied to
+    /// the target's reputation as a single change, and inactivity decay is
+    /// recovered once for the batch.
+    pub fn endorse_skills<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EndorseSkills<'info>>,
+        skills: Vec<String>,
+        rating: u8,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            !skills.is_empty() && skills.len() <= MAX_BATCH_ENDORSEMENTS,
+            AgentVaultError::TooManyBatchedSkills
+        );
+        require!((1..=5).contains(&rating), AgentVaultError::InvalidRating);
+        let per_skill = ctx.remaining_accounts.len() / skills.len();
+        require!(
+            matches!(per_skill, 4 | 5) && ctx.remaining_accounts.len() == skills.len() * per_skill,
+            AgentVaultError::BatchAccountsMismatch
+        );
+        require!(
+            ctx.accounts.endorser.key() != ctx.accounts.target_agent.wallet,
+            AgentVaultError::CannotEndorseSelf
+        );
+        check_endorser(
+            &ctx.accounts.endorser_profile,
+            &ctx.accounts.registry_stats,
+            Clock::get()?.unix_timestamp,
+        )?;
+        require!(
+            ctx.accounts.target_agent.status == AgentStatus::Active,
+            AgentVaultError::AgentNotActive
+        );
+        require!(
+            !ctx.accounts.target_agent.is_expired(Clock::get()?.unix_timestamp),
+            AgentVaultError::AgentExpired
+        );
+        require!(
+            !ctx.accounts.registry_stats.require_complete_profile || ctx.accounts.target_agent.is_complete(),
+            AgentVaultError::ProfileIncomplete
+        );
+        for skill in &skills {
+            require!(normalize_skill(skill)? == *skill, AgentVaultError::InvalidSkillFormat);
+            if !ctx.accounts.target_agent.skills.contains(skill) {
+                msg!("Skill not declared: {}", skill);
+                return err!(AgentVaultError::SkillNotDeclared);
+            }
+            require!(
+                !ctx.accounts.registry_stats.require_endorser_has_skill
+                    || ctx.accounts.endorser_profile.skills.contains(skill),
+                AgentVaultError::EndorserLacksSkill
+            );
+        }
+
+        let clock = Clock::get()?;
+
+        // The whole batch is paid for up front, a credit per skill
+        let endorser_profile = &mut ctx.accounts.endorser_profile;
+        spend_endorsement_credits(
+            endorser_profile,
+            &ctx.accounts.registry_stats,
+            skills.len() as u16,
+            clock.unix_timestamp,
+        )?;
+        endorser_profile.last_endorsement_at = clock.unix_timestamp;
+        endorser_profile.last_active = clock.unix_timestamp;
+        let endorser_id = endorser_profile.agent_id;
+        let endorser_reputation = endorser_profile.reputation;
+        let skill_frequency = load_skill_frequency(&ctx.accounts.skill_frequency)?;
+
+        let payer = ctx.accounts.endorser.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let endorser = payer.key();
+        let target = &mut ctx.accounts.target_agent;
+        let target_key = target.key();
+        let target_id = target.agent_id;
+        let target_wallet = target.wallet;
+        let stats = &mut ctx.accounts.registry_stats;
+        let pair_count = &mut ctx.accounts.pair_count;
+        pair_count.endorser = endorser;
+        pair_count.target = target_id;
+        pair_count.bump = ctx.bumps.pair_count;
+        let endorser_index = &mut ctx.accounts.endorser_index;
+        endorser_index.endorser = endorser;
+        endorser_index.bump = ctx.bumps.endorser_index;
+
+        let old_reputation = target.reputation;
+        for (skill, accounts) in skills.iter().zip(ctx.remaining_accounts.chunks(per_skill)) {
+            let [endorsement_info, skill_count_info, skill_reputation_info, reverse_info, rest @ ..] = accounts else {
+                return err!(AgentVaultError::BatchAccountsMismatch);
+            };
+
+            let endorsement_bump = pda_bump(
+                endorsement_info,
+                &[b"endorsement", endorser.as_ref(), target_id.as_ref(), skill.as_bytes()],
+            )?;
+            if !endorsement_info.data_is_empty() {
+                msg!("Skill already endorsed: {}", skill);
+                return err!(AgentVaultError::AlreadyEndorsed);
+            }
+
+            pda_bump(
+                reverse_info,
+                &[b"endorsement", target_wallet.as_ref(), endorser_id.as_ref(), skill.as_bytes()],
+            )?;
+
+            // Weighted by rarity, and mutual endorsements only earn half the
+            // boost, as in `endorse_skill`; the reverse endorsement exists
+            // iff the program owns its address
+            let mut weight = rarity_weight(
+                algorithm_weight(stats, endorser_reputation, target.endorsements_received, rating),
+                skill_frequency.as_ref(),
+                skill,
+            );
+            let reciprocal = reverse_info.owner == &crate::ID;
+            if reciprocal {
+                let mut reverse = Account::<Endorsement>::try_from(reverse_info)?;
+                reverse.reciprocal = true;
+                reverse.exit(&crate::ID)?;
+                weight /= 2;
+            }
+            weight = diminished_weight(weight, pair_count.count);
+            add_pair_endorsement(pair_count, target);
+            endorser_index.add(target_id, skill)?;
+
+            let skill_count_bump = pda_bump(
+                skill_count_info,
+                &[b"skill_count", target_key.as_ref(), skill.as_bytes()],
+            )?;
+            if skill_count_info.data_is_empty() {
+                create_pda(
+                    &payer,
+                    skill_count_info,
+                    &system_program,
+                    &[b"skill_count", target_key.as_ref(), skill.as_bytes(), &[skill_count_bump]],
+                    &SkillEndorsementCount {
+                        agent: target_key,
+                        skill: skill.clone(),
+                        count: 0,
+                        bump: skill_count_bump,
+                    },
+                )?;
+            }
+            let mut skill_count = Account::<SkillEndorsementCount>::try_from(skill_count_info)?;
+            skill_count.count += 1;
+            skill_count.exit(&crate::ID)?;
+            sync_skill_verification(
+                target,
+                skill,
+                skill_count.count,
+                stats.skill_verification_threshold,
+                clock.unix_timestamp,
+            );
+
+            let skill_reputation_bump = pda_bump(
+                skill_reputation_info,
+                &[b"skill_reputation", target_key.as_ref(), skill.as_bytes()],
+            )?;
+            if skill_reputation_info.data_is_empty() {
+                create_pda(
+                    &payer,
+                    skill_reputation_info,
+                    &system_program,
+                    &[b"skill_reputation", target_key.as_ref(), skill.as_bytes(), &[skill_reputation_bump]],
+                    &SkillReputation {
+                        agent: target_key,
+                        skill: skill.clone(),
+                        score: 0,
+                        bump: skill_reputation_bump,
+                    },
+                )?;
+            }
+            let mut skill_reputation = Account::<SkillReputation>::try_from(skill_reputation_info)?;
+            let old_skill_score = skill_reputation.score;
+            skill_reputation.score = std::cmp::min(
+                MAX_SKILL_REPUTATION,
+                skill_reputation.score.saturating_add(weight),
+            );
+            skill_reputation.exit(&crate::ID)?;
+
+            if let Some(board_info) = rest.first() {
+                update_skill_leaderboard_info(board_info, skill, target_id, skill_reputation.score)?;
+            }
+
+            // Each endorsement records the share of the clamped boost it
+            // applied, so revoking any one removes exactly that share
+            let weight = apply_reputation_delta(target, weight as i16, stats).max(0) as u8;
+            target.endorsement_points = target.endorsement_points.saturating_add(weight as u32);
+
+            create_pda(
+                &payer,
+                endorsement_info,
+                &system_program,
+                &[b"endorsement", endorser.as_ref(), target_id.as_ref(), skill.as_bytes(), &[endorsement_bump]],
+                &Endorsement {
+                    endorser,
+                    target: target_id,
+                    skill: skill.clone(),
+                    timestamp: clock.unix_timestamp,
+                    bump: endorsement_bump,
+                    weight,
+                    rating,
+                    reciprocal,
+                    expires_at: clock.unix_timestamp.saturating_add(stats.endorsement_ttl),
+                    skill_weight: skill_reputation.score - old_skill_score,
+                    deposit: stats.endorsement_deposit,
+                },
+            )?;
+            lock_endorsement_deposit(stats, &payer, endorsement_info, &system_program)?;
+
+            target.endorsements_received += 1;
+            emit_endorsement_milestone(target, clock.unix_timestamp);
+            stats.total_endorsements += 1;
+
+            emit!(SkillEndorsed {
+                endorser,
+                target: target_id,
+                skill: skill.clone(),
+                rating,
+                weight: target.public_score(weight),
+                reciprocal,
+                skill_endorsements: skill_count.count,
+                skill_reputation: target.public_score(skill_reputation.score),
+                total_endorsements: stats.total_endorsements,
+                endorsements_received: target.endorsements_received,
+                distinct_endorsers: target.distinct_endorsers,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+        target.last_active = clock.unix_timestamp;
+
+        log_reputation_change(
+            &mut ctx.accounts.reputation_history,
+            target,
+            old_reputation,
+            ReputationReason::Endorsed,
+            clock.unix_timestamp,
+        );
+        // Once per batch, not per skill; as in `endorse_skill`, a revoke
+        // leaves the recovered points in place
+        recover_reputation(target, stats, &mut ctx.accounts.reputation_history, clock.unix_timestamp);
+        update_leaderboard(&ctx.accounts.leaderboard, target.agent_id, target.reputation)?;
+
+        Ok(())
+    }
+
+    /// Revoke a previously given endorsement. `reason` is a `RevokeReason`
+    /// code, reported in the event only; 0 is `Other`.
+    pub fn revoke_endorsement(ctx: Context<RevokeEndorsement>, reason: u8) -> Result<()> {
+        let reason = RevokeReason::from_code(reason)?;
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let endorsement = &ctx.accounts.endorsement;
+        let target = &mut ctx.accounts.target_agent;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= endorsement.timestamp.saturating_add(ctx.accounts.registry_stats.revoke_cooldown),
+            AgentVaultError::RevokeTooSoon
+        );
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let endorser_profile = ctx.accounts.endorser_profile.as_deref().map(|profile| &**profile);
+        let penalty = revoke_penalty(endorsement, endorser_profile, target, stats);
+        unwind_endorsement(
+            endorsement.weight,
+            penalty,
+            target,
+            &mut ctx.accounts.reputation_history,
+            stats,
+            ReputationReason::Revoked,
+            clock.unix_timestamp,
+        );
+        update_leaderboard(&ctx.accounts.leaderboard, target.agent_id, target.reputation)?;
+
+        // Endorsements that predate per-skill counters start from an empty one
+        let skill_count = &mut ctx.accounts.skill_count;
+        skill_count.agent = target.key();
+        skill_count.skill = endorsement.skill.clone();
+        skill_count.count = skill_count.count.saturating_sub(1);
+        skill_count.bump = ctx.bumps.skill_count;
+        sync_skill_verification(
+            target,
+            &endorsement.skill,
+            skill_count.count,
+            stats.skill_verification_threshold,
+            clock.unix_timestamp,
+        );
+
+        let skill_reputation = &mut ctx.accounts.skill_reputation;
+        skill_reputation.agent = target.key();
+        skill_reputation.skill = endorsement.skill.clone();
+        skill_reputation.score = skill_reputation.score.saturating_sub(endorsement.skill_weight);
+        skill_reputation.bump = ctx.bumps.skill_reputation;
+        update_skill_leaderboard(&ctx.accounts.skill_leaderboard, target.agent_id, skill_reputation.score)?;
+
+        // Endorsements that predate pair counters start from an empty one
+        let pair_count = &mut ctx.accounts.pair_count;
+        pair_count.endorser = endorsement.endorser;
+        pair_count.target = endorsement.target;
+        remove_pair_endorsement(pair_count, target);
+        pair_count.bump = ctx.bumps.pair_count;
+
+        let endorser_index = &mut ctx.accounts.endorser_index;
+        endorser_index.endorser = endorsement.endorser;
+        endorser_index.bump = ctx.bumps.endorser_index;
+        endorser_index.remove(endorsement.target, &endorsement.skill);
+
+        emit!(EndorsementRevoked {
+            endorser: endorsement.endorser,
+            target: endorsement.target,
+            skill: endorsement.skill.clone(),
+            skill_endorsements: skill_count.count,
+            skill_reputation: target.public_score(skill_reputation.score),
+            total_endorsements: stats.total_endorsements,
+            reason,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke every endorsement the signer has given the target for the
+    /// listed skills (at most `MAX_BATCH_ENDORSEMENTS`). For each skill, in
+    /// order, `remaining_accounts` holds its `Endorsement`,
+    /// `SkillEndorsementCount` and `SkillReputation` addresses (writable),
+    /// optionally followed, for every skill or none, by its
+    /// `SkillLeaderboard`, re-ranked when it exists. Skills with no
+    /// endorsement are skipped, so stale client state is harmless; the
+    /// summed weight comes off the target's reputation as a single change.
+    pub fn revoke_all_for_target<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevokeAllForTarget<'info>>,
+        skills: Vec<String>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            !skills.is_empty() && skills.len() <= MAX_BATCH_ENDORSEMENTS,
+            AgentVaultError::TooManyBatchedSkills
+        );
+        let per_skill = ctx.remaining_accounts.len() / skills.len();
+        require!(
+            matches!(per_skill, 3 | 4) && ctx.remaining_accounts.len() == skills.len() * per_skill,
+            AgentVaultError::BatchAccountsMismatch
+        );
+
+        let clock = Clock::get()?;
+        let endorser_info = ctx.accounts.endorser.to_account_info();
+        let endorser = endorser_info.key();
+        let target = &mut ctx.accounts.target_agent;
+        let target_key = target.key();
+        let target_id = target.agent_id;
+        let stats = &mut ctx.accounts.registry_stats;
+        let pair_count = &mut ctx.accounts.pair_count;
+        pair_count.endorser = endorser;
+        pair_count.target = target_id;
+        pair_count.bump = ctx.bumps.pair_count;
+        let endorser_index = &mut ctx.accounts.endorser_index;
+        endorser_index.endorser = endorser;
+        endorser_index.bump = ctx.bumps.endorser_index;
+        let endorser_profile = ctx.accounts.endorser_profile.as_deref().map(|profile| &**profile);
+
+        let old_reputation = target.reputation;
+        for (skill, accounts) in skills.iter().zip(ctx.remaining_accounts.chunks(per_skill)) {
+            let [endorsement_info, skill_count_info, skill_reputation_info, rest @ ..] = accounts else {
+                return err!(AgentVaultError::BatchAccountsMismatch);
+            };
+
+            pda_bump(
+                endorsement_info,
+                &[b"endorsement", endorser.as_ref(), target_id.as_ref(), skill.as_bytes()],
+            )?;
+            if endorsement_info.owner != &crate::ID {
+                continue;
+            }
+            let endorsement = Account::<Endorsement>::try_from(endorsement_info)?;
+            require!(
+                clock.unix_timestamp >= endorsement.timestamp.saturating_add(stats.revoke_cooldown),
+                AgentVaultError::RevokeTooSoon
+            );
+
+            let penalty = revoke_penalty(&endorsement, endorser_profile, target, stats);
+            target.endorsements_received = target.endorsements_received.saturating_sub(1);
+            take_endorsement_weight(target, endorsement.weight, penalty, stats);
+            stats.total_endorsements = stats.total_endorsements.saturating_sub(1);
+            remove_pair_endorsement(pair_count, target);
+            endorser_index.remove(target_id, skill);
+
+            // Endorsements that predate per-skill counters have none to update
+            pda_bump(
+                skill_count_info,
+                &[b"skill_count", target_key.as_ref(), skill.as_bytes()],
+            )?;
+            let mut skill_endorsements = 0;
+            if skill_count_info.owner == &crate::ID {
+                let mut skill_count = Account::<SkillEndorsementCount>::try_from(skill_count_info)?;
+                skill_count.count = skill_count.count.saturating_sub(1);
+                skill_count.exit(&crate::ID)?;
+                skill_endorsements = skill_count.count;
+            }
+            sync_skill_verification(
+                target,
+                skill,
+                skill_endorsements,
+                stats.skill_verification_threshold,
+                clock.unix_timestamp,
+            );
+
+            pda_bump(
+                skill_reputation_info,
+                &[b"skill_reputation", target_key.as_ref(), skill.as_bytes()],
+            )?;
+            let mut skill_score = 0;
+            if skill_reputation_info.owner == &crate::ID {
+                let mut skill_reputation = Account::<SkillReputation>::try_from(skill_reputation_info)?;
+                skill_reputation.score = skill_reputation.score.saturating_sub(endorsement.skill_weight);
+                skill_reputation.exit(&crate::ID)?;
+                skill_score = skill_reputation.score;
+            }
+            if let Some(board_info) = rest.first() {
+                update_skill_leaderboard_info(board_info, skill, target_id, skill_score)?;
+            }
+
+            close_pda(endorsement_info, &endorser_info)?;
+
+            emit!(EndorsementRevoked {
+                endorser,
+                target: target_id,
+                skill: skill.clone(),
+                skill_endorsements,
+                skill_reputation: target.public_score(skill_score),
+                total_endorsements: stats.total_endorsements,
+                reason: RevokeReason::Other,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        if target.reputation != old_reputation {
+            log_reputation_change(
+                &mut ctx.accounts.reputation_history,
+                target,
+                old_reputation,
+                ReputationReason::Revoked,
+                clock.unix_timestamp,
+            );
+            update_leaderboard(&ctx.accounts.leaderboard, target.agent_id, target.reputation)?;
+        }
+
+        Ok(())
+    }
+
+    /// Revoke up to `MAX_BATCH_REVOKES` of the signer's endorsements across
+    /// any targets, e.g. before leaving the registry. For each endorsement,
+    /// in order, `remaining_accounts` holds six writable accounts: the
+    /// `Endorsement`, its target's `AgentProfile` and `ReputationHistory`,
+    /// then the target's `SkillEndorsementCount`, `SkillReputation` and
+    /// `PairEndorsementCount` addresses, optionally followed, for every
+    /// endorsement or none, by the skill's `SkillLeaderboard`. A profile
+    /// that isn't the endorsement's target fails the whole call; counters
+    /// and boards that don't exist are skipped, as in
+    /// `revoke_all_for_target`.
+    pub fn revoke_many<'info>(ctx: Context<'_, '_, 'info, 'info, RevokeMany<'info>>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        // Six accounts per endorsement, or seven with skill leaderboards. No
+        // batch of one size has as many accounts as a batch of the other
+        // while `MAX_BATCH_REVOKES` is below 7.
+        let per_entry = if ctx.remaining_accounts.len() % 6 == 0 { 6 } else { 7 };
+        require!(
+            ctx.remaining_accounts.len() % per_entry == 0,
+            AgentVaultError::BatchAccountsMismatch
+        );
+        let count = ctx.remaining_accounts.len() / per_entry;
+        require!(
+            count > 0 && count <= MAX_BATCH_REVOKES,
+            AgentVaultError::InvalidBatchSize
+        );
+
+        let clock = Clock::get()?;
+        let endorser_info = ctx.accounts.endorser.to_account_info();
+        let endorser = endorser_info.key();
+        let endorser_profile = ctx.accounts.endorser_profile.as_deref().map(|profile| &**profile);
+        let stats = &mut ctx.accounts.registry_stats;
+        let endorser_index = &mut ctx.accounts.endorser_index;
+        endorser_index.endorser = endorser;
+        endorser_index.bump = ctx.bumps.endorser_index;
+
+        // A target listed more than once is reloaded each time, so every
+        // entry sees the previous one's changes
+        for accounts in ctx.remaining_accounts.chunks(per_entry) {
+            let [endorsement_info, target_info, history_info, skill_count_info, skill_reputation_info, pair_count_info, rest @ ..] =
+                accounts
+            else {
+                return err!(AgentVaultError::BatchAccountsMismatch);
+            };
+
+            let endorsement = Account::<Endorsement>::try_from(endorsement_info)?;
+            require!(endorsement.endorser == endorser, AgentVaultError::Unauthorized);
+            require!(
+                clock.unix_timestamp >= endorsement.timestamp.saturating_add(stats.revoke_cooldown),
+                AgentVaultError::RevokeTooSoon
+            );
+
+            // Only this program creates `AgentProfile` accounts, so a
+            // matching `agent_id` is enough
+            let mut target = Account::<AgentProfile>::try_from(target_info)?;
+            require!(
+                target.agent_id == endorsement.target,
+                AgentVaultError::EndorsementTargetMismatch
+            );
+            let target_key = target.key();
+            pda_bump(history_info, &[b"reputation_history", endorsement.target.as_ref()])?;
+            let mut history = Account::<ReputationHistory>::try_from(history_info)?;
+
+            let penalty = revoke_penalty(&endorsement, endorser_profile, &target, stats);
+            unwind_endorsement(
+                endorsement.weight,
+                penalty,
+                &mut target,
+                &mut history,
+                stats,
+                ReputationReason::Revoked,
+                clock.unix_timestamp,
+            );
+            endorser_index.remove(endorsement.target, &endorsement.skill);
+
+            pda_bump(
+                skill_count_info,
+                &[b"skill_count", target_key.as_ref(), endorsement.skill.as_bytes()],
+            )?;
+            let mut skill_endorsements = 0;
+            if skill_count_info.owner == &crate::ID {
+                let mut skill_count = Account::<SkillEndorsementCount>::try_from(skill_count_info)?;
+                skill_count.count = skill_count.count.saturating_sub(1);
+                skill_count.exit(&crate::ID)?;
+                skill_endorsements = skill_count.count;
+            }
+            sync_skill_verification(
+                &mut target,
+                &endorsement.skill,
+                skill_endorsements,
+                stats.skill_verification_threshold,
+                clock.unix_timestamp,
+            );
+
+            pda_bump(
+                skill_reputation_info,
+                &[b"skill_reputation", target_key.as_ref(), endorsement.skill.as_bytes()],
+            )?;
+            let mut skill_score = 0;
+            if skill_reputation_info.owner == &crate::ID {
+                let mut skill_reputation = Account::<SkillReputation>::try_from(skill_reputation_info)?;
+                skill_reputation.score = skill_reputation.score.saturating_sub(endorsement.skill_weight);
+                skill_reputation.exit(&crate::ID)?;
+                skill_score = skill_reputation.score;
+            }
+            if let Some(board_info) = rest.first() {
+                update_skill_leaderboard_info(board_info, &endorsement.skill, endorsement.target, skill_score)?;
+            }
+
+            pda_bump(
+                pair_count_info,
+                &[b"pair_endorsements", endorser.as_ref(), endorsement.target.as_ref()],
+            )?;
+            if pair_count_info.owner == &crate::ID {
+                let mut pair_count = Account::<PairEndorsementCount>::try_from(pair_count_info)?;
+                remove_pair_endorsement(&mut pair_count, &mut target);
+                pair_count.exit(&crate::ID)?;
+            }
+
+            update_leaderboard(&ctx.accounts.leaderboard, target.agent_id, target.reputation)?;
+            target.exit(&crate::ID)?;
+            history.exit(&crate::ID)?;
+            close_pda(endorsement_info, &endorser_info)?;
+
+            emit!(EndorsementRevoked {
+                endorser,
+                target: endorsement.target,
+                skill: endorsement.skill.clone(),
+                skill_endorsements,
+                skill_reputation: target.public_score(skill_score),
+                total_endorsements: stats.total_endorsements,
+                reason: RevokeReason::Other,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Close an endorsement past its `expires_at` (permissionless). Rent goes
+    /// back to the endorser and the boost is removed from the target.
+    pub fn expire_endorsement(ctx: Context<ExpireEndorsement>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp > ctx.accounts.endorsement.expires_at,
+            AgentVaultError::EndorsementNotExpired
+        );
+
+        remove_endorsement(ctx.accounts, ReputationReason::Expired, clock.unix_timestamp)?;
+
+        let endorsement = &ctx.accounts.endorsement;
+        let skill_count = &ctx.accounts.skill_count;
+        let skill_reputation = &ctx.accounts.skill_reputation;
+        let stats = &ctx.accounts.registry_stats;
+
+        emit!(EndorsementExpired {
+            endorser: endorsement.endorser,
+            target: endorsement.target,
+            skill: endorsement.skill.clone(),
+            skill_endorsements: skill_count.count,
+            skill_reputation: ctx.accounts.target_agent.public_score(skill_reputation.score),
+            total_endorsements: stats.total_endorsements,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close an endorsement whose target profile has been deregistered,
+    /// returning its rent to the endorser. There's no target left to
+    /// adjust, so no reputation changes.
+    pub fn close_orphaned_endorsement(ctx: Context<CloseOrphanedEndorsement>) -> Result<()> {
+        require!(
+            ctx.accounts.target_agent.owner != &crate::ID,
+            AgentVaultError::EndorsementNotOrphaned
+        );
+
+        let endorsement = &ctx.accounts.endorsement;
+        let clock = Clock::get()?;
+
+        let stats = &mut ctx.accounts.registry_stats;
+        stats.total_endorsements = stats.total_endorsements.saturating_sub(1);
+
+        let index_info = ctx.accounts.endorser_index.to_account_info();
+        if index_info.owner == &crate::ID {
+            let mut endorser_index = EndorserIndex::try_deserialize(&mut &index_info.try_borrow_data()?[..])?;
+            endorser_index.remove(endorsement.target, &endorsement.skill);
+            endorser_index.try_serialize(&mut &mut index_info.try_borrow_mut_data()?[..])?;
+        }
+
+        emit!(OrphanedEndorsementClosed {
+            endorser: endorsement.endorser,
+            target: endorsement.target,
+            skill: endorsement.skill.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Remove an endorsement shown to be fraudulent (authority or
+    /// moderator). Its boost is unwound as on expiry and its deposit goes to
+    /// the treasury; the endorser only gets the rent back.
+    pub fn slash_endorsement(ctx: Context<SlashEndorsement>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let deposit = ctx.accounts.expire.endorsement.deposit;
+        if deposit > 0 {
+            let endorsement_info = ctx.accounts.expire.endorsement.to_account_info();
+            **endorsement_info.try_borrow_mut_lamports()? -= deposit;
+            **ctx.accounts.treasury.try_borrow_mut_lamports()? += deposit;
+            ctx.accounts.expire.endorsement.deposit = 0;
+        }
+
+        remove_endorsement(&mut ctx.accounts.expire, ReputationReason::Slashed, clock.unix_timestamp)?;
+
+        let endorsement = &ctx.accounts.expire.endorsement;
+        emit!(EndorsementSlashed {
+            endorser: endorsement.endorser,
+            target: endorsement.target,
+            skill: endorsement.skill.clone(),
+            deposit_forfeited: deposit,
+            slashed_by: ctx.accounts.signer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Emit a live endorsement as an `EndorsementAttestation` (permissionless,
+    /// read-only), so attestation registries can snapshot it in a canonical
+    /// form instead of decoding the account themselves. The issuer is this
+    /// program, vouching for the account's contents at `timestamp`.
+    pub fn attest_endorsement(ctx: Context<AttestEndorsement>) -> Result<()> {
+        let endorsement = &ctx.accounts.endorsement;
+        let clock = Clock::get()?;
+
+        emit!(EndorsementAttestation {
+            issuer: crate::ID,
+            endorsement: endorsement.key(),
+            endorser: endorsement.endorser,
+            target: endorsement.target,
+            skill: endorsement.skill.clone(),
+            rating: endorsement.rating,
+            endorsed_at: endorsement.timestamp,
+            expires_at: endorsement.expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a job for a registered agent, escrowing `amount` of `mint` from
+    /// the payer in a vault owned by the job. `milestones` optionally splits
+    /// the payout into separately releasable amounts.
+    pub fn create_job(
+        ctx: Context<CreateJob>,
+        job_id: u64,
+        amount: u64,
+        milestones: Vec<u64>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(amount > 0, AgentVaultError::InvalidJobAmount);
+        require!(milestones.len() <= MAX_MILESTONES, AgentVaultError::TooManyMilestones);
+        let mut milestone_total: u64 = 0;
+        for milestone_amount in &milestones {
+            require!(*milestone_amount > 0, AgentVaultError::InvalidJobAmount);
+            milestone_total = milestone_total
+                .checked_add(*milestone_amount)
+                .ok_or(AgentVaultError::InvalidJobAmount)?;
+        }
+        require!(milestone_total <= amount, AgentVaultError::InvalidJobAmount);
+        require!(
+            ctx.accounts.worker_profile.status == AgentStatus::Active,
+            AgentVaultError::AgentNotActive
+        );
+        require!(
+            ctx.accounts.worker_profile.wallet != ctx.accounts.payer.key(),
+            AgentVaultError::NotJobParty
+        );
+
+        let job_key = ctx.accounts.job.key();
+        create_token_account(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[b"job_vault", job_key.as_ref(), &[ctx.bumps.job_vault]],
+        )?;
+        token_transfer_checked(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.payer_token_account.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+        // A Token-2022 transfer fee would leave the escrow short of `amount`
+        require!(
+            token_amount(&ctx.accounts.job_vault)? == amount,
+            AgentVaultError::InvalidJobAmount
+        );
+
+        let job = &mut ctx.accounts.job;
+        let clock = Clock::get()?;
+
+        job.payer = ctx.accounts.payer.key();
+        job.worker = ctx.accounts.worker_profile.agent_id;
+        job.mint = ctx.accounts.mint.key();
+        job.amount = amount;
+        job.job_id = job_id;
+        job.status = JobStatus::Open;
+        job.created_at = clock.unix_timestamp;
+        job.bump = ctx.bumps.job;
+        job.vault_bump = ctx.bumps.job_vault;
+        job.milestones = milestones
+            .into_iter()
+            .map(|amount| Milestone { amount, released: false })
+            .collect();
+        job.released_amount = 0;
+        job.disputed = false;
+
+        emit!(JobCreated {
+            job: job.key(),
+            payer: job.payer,
+            worker: job.worker,
+            mint: job.mint,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Mark an open job's work as delivered (worker only)
+    pub fn submit_work(ctx: Context<SubmitWork>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let job = &mut ctx.accounts.job;
+        let clock = Clock::get()?;
+
+        require!(job.status == JobStatus::Open, AgentVaultError::InvalidJobStatus);
+        job.status = JobStatus::Submitted;
+
+        emit!(WorkSubmitted {
+            job: job.key(),
+            worker: job.worker,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pay the escrowed funds to the worker (payer only) and close the job.
+    /// The worker's reputation is credited only as `job_earns_credit`
+    /// allows.
+    pub fn release_payment(ctx: Context<ReleasePayment>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            matches!(ctx.accounts.job.status, JobStatus::Open | JobStatus::Submitted),
+            AgentVaultError::InvalidJobStatus
+        );
+
+        require!(!ctx.accounts.job.disputed, AgentVaultError::JobDisputed);
+
+        // Everything not already paid out through milestones
+        let amount = ctx.accounts.job.amount - ctx.accounts.job.released_amount;
+
+        let job_key = ctx.accounts.job.key();
+        let vault_seeds: &[&[u8]] = &[b"job_vault", job_key.as_ref(), &[ctx.accounts.job.vault_bump]];
+        token_transfer_checked(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.worker_token_account.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            amount,
+            ctx.accounts.mint.decimals,
+            &[vault_seeds],
+        )?;
+        token_close_account(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &[vault_seeds],
+        )?;
+
+        let job = &mut ctx.accounts.job;
+        let clock = Clock::get()?;
+
+        let credit = &mut ctx.accounts.job_credit;
+        credit.payer = job.payer;
+        credit.worker = job.worker;
+        credit.bump = ctx.bumps.job_credit;
+        let credited = job_earns_credit(
+            job,
+            job.status == JobStatus::Submitted,
+            ctx.accounts.payer_profile.as_deref().map(|profile| &**profile),
+            credit,
+            &ctx.accounts.registry_stats,
+            clock.unix_timestamp,
+        );
+        if credited {
+            credit.last_credited_at = clock.unix_timestamp;
+            credit.last_credited_job = job.key();
+        }
+
+        job.released_amount = job.amount;
+        for milestone in job.milestones.iter_mut() {
+            milestone.released = true;
+        }
+        complete_job(
+            job,
+            &mut ctx.accounts.worker_profile,
+            &mut ctx.accounts.reputation_history,
+            &ctx.accounts.registry_stats,
+            credited,
+            clock.unix_timestamp,
+        );
+        let worker = &ctx.accounts.worker_profile;
+        update_leaderboard(&ctx.accounts.leaderboard, worker.agent_id, worker.reputation)?;
+
+        emit!(PaymentReleased {
+            job: job.key(),
+            payer: job.payer,
+            worker: job.worker,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        ctx.accounts.job.close(ctx.accounts.payer.to_account_info())?;
+
+        Ok(())
+    }
+
+    /// Pay out a single milestone to the worker (payer only). Releasing the
+    /// last outstanding milestone completes the job, returns any leftover
+    /// escrow to the payer and closes the vault and the job.
+    pub fn release_milestone(ctx: Context<ReleaseMilestone>, index: u8) -> Result<()> {
+        let accounts = &mut ctx.accounts.release;
+        require!(!accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            matches!(accounts.job.status, JobStatus::Open | JobStatus::Submitted),
+            AgentVaultError::InvalidJobStatus
+        );
+        require!(!accounts.job.disputed, AgentVaultError::JobDisputed);
+
+        let milestone = *accounts
+            .job
+            .milestones
+            .get(index as usize)
+            .ok_or(AgentVaultError::InvalidMilestoneIndex)?;
+        require!(!milestone.released, AgentVaultError::MilestoneAlreadyReleased);
+
+        let job_key = accounts.job.key();
+        let vault_seeds: &[&[u8]] = &[b"job_vault", job_key.as_ref(), &[accounts.job.vault_bump]];
+        token_transfer_checked(
+            &accounts.token_program.to_account_info(),
+            &accounts.job_vault.to_account_info(),
+            &accounts.mint.to_account_info(),
+            &accounts.worker_token_account.to_account_info(),
+            &accounts.job_vault.to_account_info(),
+            milestone.amount,
+            accounts.mint.decimals,
+            &[vault_seeds],
+        )?;
+
+        let clock = Clock::get()?;
+        let submitted = accounts.job.status == JobStatus::Submitted;
+        let job = &mut accounts.job;
+        job.milestones[index as usize].released = true;
+        job.released_amount += milestone.amount;
+
+        let job_completed = job.milestones.iter().all(|m| m.released);
+        let mut dust_returned = 0;
+        if job_completed {
+            dust_returned = token_amount(&accounts.job_vault)?;
+            if dust_returned > 0 {
+                token_transfer_checked(
+                    &accounts.token_program.to_account_info(),
+                    &accounts.job_vault.to_account_info(),
+                    &accounts.mint.to_account_info(),
+                    &ctx.accounts.payer_token_account.to_account_info(),
+                    &accounts.job_vault.to_account_info(),
+                    dust_returned,
+                    accounts.mint.decimals,
+                    &[vault_seeds],
+                )?;
+            }
+            token_close_account(
+                &accounts.token_program.to_account_info(),
+                &accounts.job_vault.to_account_info(),
+                &accounts.payer.to_account_info(),
+                &accounts.job_vault.to_account_info(),
+                &[vault_seeds],
+            )?;
+
+            let credit = &mut accounts.job_credit;
+            credit.payer = job.payer;
+            credit.worker = job.worker;
+            credit.bump = ctx.bumps.release.job_credit;
+            let credited = job_earns_credit(
+                job,
+                submitted,
+                accounts.payer_profile.as_deref().map(|profile| &**profile),
+                credit,
+                &accounts.registry_stats,
+                clock.unix_timestamp,
+            );
+            if credited {
+                credit.last_credited_at = clock.unix_timestamp;
+                credit.last_credited_job = job.key();
+            }
+
+            job.released_amount = job.amount;
+            complete_job(
+                job,
+                &mut accounts.worker_profile,
+                &mut accounts.reputation_history,
+                &accounts.registry_stats,
+                credited,
+                clock.unix_timestamp,
+            );
+            let worker = &accounts.worker_profile;
+            update_leaderboard(&accounts.leaderboard, worker.agent_id, worker.reputation)?;
+        }
+
+        emit!(MilestoneReleased {
+            job: job.key(),
+            index,
+            amount: milestone.amount,
+            job_completed,
+            dust_returned,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if job_completed {
+            accounts.job.close(accounts.payer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Refund an open job before the worker submits (payer only)
+    pub fn cancel_job(ctx: Context<CancelJob>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(ctx.accounts.job.status == JobStatus::Open, AgentVaultError::InvalidJobStatus);
+        require!(!ctx.accounts.job.disputed, AgentVaultError::JobDisputed);
+
+        let job_key = ctx.accounts.job.key();
+        let vault_seeds: &[&[u8]] = &[b"job_vault", job_key.as_ref(), &[ctx.accounts.job.vault_bump]];
+        token_transfer_checked(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.payer_token_account.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            ctx.accounts.job.amount - ctx.accounts.job.released_amount,
+            ctx.accounts.mint.decimals,
+            &[vault_seeds],
+        )?;
+        token_close_account(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &[vault_seeds],
+        )?;
+
+        let job = &ctx.accounts.job;
+        let clock = Clock::get()?;
+
+        emit!(JobCancelled {
+            job: job.key(),
+            payer: job.payer,
+            amount: job.amount - job.released_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Leave a 1-5 star review of the worker on a completed job (payer only,
+    /// once per job). Only the last job between the two that credited the
+    /// worker, see `job_earns_credit`, can be reviewed, and the reviewer
+    /// must still be an active agent.
+    pub fn submit_review(ctx: Context<SubmitReview>, stars: u8, comment: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!((1..=5).contains(&stars), AgentVaultError::InvalidRating);
+        require!(comment.len() <= MAX_REVIEW_COMMENT_LEN, AgentVaultError::ReviewTooLong);
+
+        let review = &mut ctx.accounts.review;
+        let target = &mut ctx.accounts.target_agent;
+        let clock = Clock::get()?;
+
+        review.reviewer = ctx.accounts.reviewer.key();
+        review.target = target.agent_id;
+        review.job = ctx.accounts.job.key();
+        review.stars = stars;
+        review.comment = comment;
+        review.timestamp = clock.unix_timestamp;
+        review.bump = ctx.bumps.review;
+
+        target.rating_sum = target.rating_sum.saturating_add(stars as u64);
+        target.rating_count = target.rating_count.saturating_add(1);
+        ctx.accounts.job_credit.last_credited_job = Pubkey::default();
+
+        emit!(ReviewSubmitted {
+            reviewer: review.reviewer,
+            target: review.target,
+            job: review.job,
+            stars,
+            rating_sum: target.rating_sum,
+            rating_count: target.rating_count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze a job's escrow pending arbitration (payer or worker)
+    pub fn open_dispute(ctx: Context<OpenDispute>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let job = &mut ctx.accounts.job;
+        let clock = Clock::get()?;
+
+        require!(
+            matches!(job.status, JobStatus::Open | JobStatus::Submitted),
+            AgentVaultError::InvalidJobStatus
+        );
+        require!(!job.disputed, AgentVaultError::JobDisputed);
+        job.disputed = true;
+
+        emit!(DisputeOpened {
+            job: job.key(),
+            opened_by: ctx.accounts.signer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Split a disputed job's remaining escrow between payer and worker
+    /// (authority only). Shares are in basis points and must sum to 10000.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        payer_share_bps: u16,
+        worker_share_bps: u16,
+    ) -> Result<()> {
+        require!(ctx.accounts.job.disputed, AgentVaultError::JobNotDisputed);
+        require!(
+            payer_share_bps as u32 + worker_share_bps as u32 == BPS_DENOMINATOR as u32,
+            AgentVaultError::InvalidDisputeShares
+        );
+
+        let remaining = ctx.accounts.job.amount - ctx.accounts.job.released_amount;
+        let worker_amount = (remaining as u128 * worker_share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        // The payer takes the rounding remainder
+        let payer_amount = remaining - worker_amount;
+
+        let job_key = ctx.accounts.job.key();
+        let vault_seeds: &[&[u8]] = &[b"job_vault", job_key.as_ref(), &[ctx.accounts.job.vault_bump]];
+        if worker_amount > 0 {
+            token_transfer_checked(
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.job_vault.to_account_info(),
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.worker_token_account.to_account_info(),
+                &ctx.accounts.job_vault.to_account_info(),
+                worker_amount,
+                ctx.accounts.mint.decimals,
+                &[vault_seeds],
+            )?;
+        }
+        if payer_amount > 0 {
+            token_transfer_checked(
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.job_vault.to_account_info(),
+                &ctx.accounts.mint.to_account_info(),
+                &ctx.accounts.payer_token_account.to_account_info(),
+                &ctx.accounts.job_vault.to_account_info(),
+                payer_amount,
+                ctx.accounts.mint.decimals,
+                &[vault_seeds],
+            )?;
+        }
+        token_close_account(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.job_vault.to_account_info(),
+            &[vault_seeds],
+        )?;
+
+        let job = &mut ctx.accounts.job;
+        let clock = Clock::get()?;
+
+        job.disputed = false;
+        job.released_amount = job.amount;
+        job.status = JobStatus::Resolved;
+
+        // Only a ruling wholly for the worker counts as a delivery
+        let worker = &mut ctx.accounts.worker_profile;
+        if worker_share_bps == BPS_DENOMINATOR {
+            worker.jobs_completed += 1;
+        } else {
+            worker.jobs_failed += 1;
+        }
+
+        emit!(DisputeResolved {
+            job: job.key(),
+            payer_amount,
+            worker_amount,
+            payer_share_bps,
+            worker_share_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record a score an agent earned on another platform (reputation oracle
+    /// only). It doesn't move `reputation` until the next recompute, and an
+    /// empty `source` clears it.
+    pub fn import_external_reputation(
+        ctx: Context<ImportExternalReputation>,
+        external_reputation: u8,
+        source: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(source.len() <= MAX_EXTERNAL_SOURCE_LEN, AgentVaultError::InvalidExternalSource);
+        require!(
+            external_reputation <= 100 && (!source.is_empty() || external_reputation == 0),
+            AgentVaultError::InvalidParameter
+        );
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        agent.external_reputation = external_reputation;
+        agent.external_source = source.clone();
+        update_leaderboard(&ctx.accounts.leaderboard, agent.agent_id, agent.reputation)?;
+
+        emit!(ExternalReputationImported {
+            wallet: agent.wallet,
+            external_reputation,
+            source,
+            oracle: ctx.accounts.oracle.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move an agent's reputation to the blend of its endorsement and
+    /// review scores (permissionless), e.g. after `set_reputation_weights`.
+    /// See `blended_reputation`. The blend starts from the agent's stored
+    /// endorsement weights and adds back its `reputation_adjustment`, so
+    /// slashes, decay and job credit survive it; the move goes through
+    /// `apply_reputation_delta`, so the verified floor still holds. The
+    /// blend depends only on stored fields, so repeating the call is a no-op.
+    pub fn recompute_reputation(ctx: Context<RecomputeReputation>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let stats = &ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        let old_reputation = agent.reputation;
+        let target = blended_reputation(agent, stats);
+        apply_reputation_delta(agent, target as i16 - old_reputation as i16, stats);
+        if agent.reputation != old_reputation {
+            log_reputation_change(
+                &mut ctx.accounts.reputation_history,
+                agent,
+                old_reputation,
+                ReputationReason::Recomputed,
+                clock.unix_timestamp,
+            );
+        }
+        update_leaderboard(&ctx.accounts.leaderboard, agent.agent_id, agent.reputation)?;
+
+        Ok(())
+    }
+
+    /// Move an agent's reputation to what its live endorsements give, each
+    /// counted at its stored weight decayed by age (authority only; see
+    /// `time_decayed_weight`). This is the alternative to
+    /// `recompute_reputation` for registries that age endorsements out; the
+    /// two formulas disagree, so a registry should settle on one, and only
+    /// the authority can run this one so no one else can flip an agent
+    /// between them.
+    /// `remaining_accounts` must hold every one of the agent's endorsements,
+    /// each once, so none can be left out to drag the score down. The
+    /// agent's `reputation_adjustment` is added as in `blended_reputation`.
+    pub fn recompute_time_weighted_reputation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecomputeTimeWeightedReputation<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let stats = &ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.remaining_accounts.len() == agent.endorsements_received as usize,
+            AgentVaultError::BatchAccountsMismatch
+        );
+
+        let mut points: u64 = 0;
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                !ctx.remaining_accounts[..i].iter().any(|other| other.key == info.key),
+                AgentVaultError::BatchAccountsMismatch
+            );
+            // Only this program creates endorsement accounts, so any it owns
+            // that names this agent is one of its live endorsements
+            let endorsement = Account::<Endorsement>::try_from(info)?;
+            require!(
+                endorsement.target == agent.agent_id,
+                AgentVaultError::BatchAccountsMismatch
+            );
+            let age = clock.unix_timestamp.saturating_sub(endorsement.timestamp);
+            points += time_decayed_weight(endorsement.weight, age, stats.reputation_half_life);
+        }
+
+        let old_reputation = agent.reputation;
+        let earned = (points + (1 << 15)) >> 16;
+        let target = std::cmp::min(
+            stats.max_reputation as i64,
+            stats.base_reputation as i64 + earned as i64 + agent.reputation_adjustment as i64,
+        )
+        .max(0) as u8;
+        apply_reputation_delta(agent, target as i16 - old_reputation as i16, stats);
+        if agent.reputation != old_reputation {
+            log_reputation_change(
+                &mut ctx.accounts.reputation_history,
+                agent,
+                old_reputation,
+                ReputationReason::Recomputed,
+                clock.unix_timestamp,
+            );
+        }
+        update_leaderboard(&ctx.accounts.leaderboard, agent.agent_id, agent.reputation)?;
+
+        Ok(())
+    }
+
+    /// Rewrite up to `MAX_BATCH_RECOMPUTE` profiles' reputations under the
+    /// current parameters (authority only), as `recompute_reputation` would,
+    /// after a formula change. `remaining_accounts` holds a profile and its
+    /// `ReputationHistory` per entry; the blend reads only the profile's own
+    /// counters, so no endorsement accounts are needed. Page through the
+    /// registry over several calls.
+    pub fn recompute_batch<'info>(ctx: Context<'_, '_, 'info, 'info, RecomputeBatch<'info>>) -> Result<()> {
+        let stats = &ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        let count = ctx.remaining_accounts.len() / 2;
+        require!(
+            count > 0 && count <= MAX_BATCH_RECOMPUTE,
+            AgentVaultError::InvalidBatchSize
+        );
+        require!(
+            ctx.remaining_accounts.len() == count * 2,
+            AgentVaultError::BatchAccountsMismatch
+        );
+
+        let mut changed: u32 = 0;
+        for accounts in ctx.remaining_accounts.chunks(2) {
+            let [profile_info, history_info] = accounts else {
+                return err!(AgentVaultError::BatchAccountsMismatch);
+            };
+            let mut agent = Account::<AgentProfile>::try_from(profile_info)?;
+            pda_bump(history_info, &[b"reputation_history", agent.agent_id.as_ref()])?;
+            let mut history = Account::<ReputationHistory>::try_from(history_info)?;
+
+            let old_reputation = agent.reputation;
+            let target = blended_reputation(&agent, stats);
+            apply_reputation_delta(&mut agent, target as i16 - old_reputation as i16, stats);
+            if agent.reputation != old_reputation {
+                log_reputation_change(
+                    &mut history,
+                    &agent,
+                    old_reputation,
+                    ReputationReason::Recomputed,
+                    clock.unix_timestamp,
+                );
+                changed += 1;
+            }
+
+            agent.exit(&crate::ID)?;
+            history.exit(&crate::ID)?;
+        }
+
+        emit!(BatchRecomputed {
+            count: count as u32,
+            changed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Bring a skill's verified flag in line with its endorsement count and
+    /// the current threshold (permissionless), e.g. after the threshold
+    /// changes or a skill is re-declared
+    pub fn check_skill_verification(ctx: Context<CheckSkillVerification>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let skill_count = &ctx.accounts.skill_count;
+        sync_skill_verification(
+            &mut ctx.accounts.agent_profile,
+            &skill_count.skill,
+            skill_count.count,
+            ctx.accounts.registry_stats.skill_verification_threshold,
+            Clock::get()?.unix_timestamp,
+        );
+
+        Ok(())
+    }
+
+    /// Record an agent's current reputation under `snapshot_id` (authority
+    /// or snapshot authority), for use as voting weight at that point in
+    /// time. Each agent can be captured once per snapshot id, so later
+    /// endorsements never change a recorded weight.
+    pub fn snapshot_reputation(ctx: Context<SnapshotReputation>, snapshot_id: u64) -> Result<()> {
+        let agent = &ctx.accounts.agent_profile;
+        let snapshot = &mut ctx.accounts.reputation_snapshot;
+        let clock = Clock::get()?;
+
+        snapshot.snapshot_id = snapshot_id;
+        snapshot.agent = agent.agent_id;
+        snapshot.wallet = agent.wallet;
+        snapshot.reputation = agent.reputation;
+        snapshot.slot = clock.slot;
+        snapshot.timestamp = clock.unix_timestamp;
+        snapshot.bump = ctx.bumps.reputation_snapshot;
+
+        emit!(ReputationSnapshotted {
+            snapshot_id,
+            wallet: agent.wallet,
+            reputation: agent.public_score(agent.reputation),
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Apply inactivity decay to an agent's reputation (permissionless).
+    /// Each whole interval takes `decay_fraction_bps` of the reputation
+    /// above the floor, so high scores fall fastest; see `decay_penalty`.
+    pub fn decay_reputation(ctx: Context<DecayReputation>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        // Decay is measured from the later of the last activity and the last
+        // decay, so the same dormant period is never charged twice. Setting
+        // an away window counts as activity, so the window always starts
+        // before `since` and skipping it just means starting at its end.
+        let since = agent.last_active.max(agent.last_decay_at).max(agent.away_until);
+        let days_inactive = clock.unix_timestamp.saturating_sub(since) / SECONDS_PER_DAY;
+        let intervals = days_inactive / DECAY_INTERVAL_DAYS;
+
+        let stats = &ctx.accounts.registry_stats;
+        let floor = std::cmp::max(stats.decay_floor, stats.reputation_floor(agent));
+        require!(
+            intervals > 0 && agent.reputation > floor,
+            AgentVaultError::NothingToDecay
+        );
+
+        let old_reputation = agent.reputation;
+        let penalty = decay_penalty(
+            agent.reputation,
+            floor,
+            intervals,
+            stats.decay_fraction(),
+        );
+        let applied = adjust_reputation(agent, -(penalty as i16), stats);
+        agent.decayed_amount = agent.decayed_amount.saturating_add(applied.unsigned_abs() as u8);
+
+        // Only consume whole intervals; a partial one carries over
+        agent.last_decay_at = since + intervals * DECAY_INTERVAL_DAYS * SECONDS_PER_DAY;
+
+        log_reputation_change(
+            &mut ctx.accounts.reputation_history,
+            agent,
+            old_reputation,
+            ReputationReason::Decayed,
+            clock.unix_timestamp,
+        );
+        update_leaderboard(&ctx.accounts.leaderboard, agent.agent_id, agent.reputation)?;
+
+        let (old_reputation, new_reputation) = agent.public_reputation(old_reputation, agent.reputation);
+        emit!(ReputationDecayed {
+            wallet: agent.wallet,
+            old_reputation,
+            new_reputation,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Mark the agent away until `away_until` (owner only), suspending
+    /// inactivity decay for up to `MAX_AWAY_DURATION`. Pass a past time,
+    /// such as 0, to come back early.
+    pub fn set_away(ctx: Context<UpdateProfile>, away_until: i64) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(
+            away_until <= clock.unix_timestamp + MAX_AWAY_DURATION,
+            AgentVaultError::AwayTooLong
+        );
+
+        agent.away_until = away_until;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(AwayModeSet {
+            wallet: agent.wallet,
+            away_until,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Hand an agent profile (and its reputation) over to a new wallet.
+    ///
+    /// The profile PDA is seeded on the stable `agent_id` rather than the
+    /// current owner, so it keeps its address and every endorsement pointing
+    /// at it. Profiles created before `agent_id` existed were seeded on their
+    /// wallet, so migrating them only needs `agent_id = wallet` and an
+    /// `OwnerIndex` for that wallet.
+    pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let old_owner = agent.wallet;
+        agent.wallet = new_owner;
+        agent.last_active = clock.unix_timestamp;
+        // Domain proofs were bound to the old wallet
+        agent.verified_domains.clear();
+
+        let new_owner_index = &mut ctx.accounts.new_owner_index;
+        new_owner_index.profile = agent.key();
+        new_owner_index.bump = ctx.bumps.new_owner_index;
+
+        emit!(OwnershipTransferred {
+            agent_id: agent.agent_id,
+            old_owner,
+            new_owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Name the guardians who can recover the profile and how many of them
+    /// must approve (owner only). An empty list with a threshold of 0 turns
+    /// recovery off.
+    pub fn set_guardians(ctx: Context<UpdateProfile>, guardians: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        require!(guardians.len() <= MAX_GUARDIANS, AgentVaultError::InvalidGuardians);
+        require!(
+            guardians.is_empty() && threshold == 0
+                || threshold >= 1 && threshold as usize <= guardians.len(),
+            AgentVaultError::InvalidGuardians
+        );
+        for (i, guardian) in guardians.iter().enumerate() {
+            require!(
+                *guardian != agent.wallet && !guardians[..i].contains(guardian),
+                AgentVaultError::InvalidGuardians
+            );
+        }
+
+        agent.guardians = guardians;
+        agent.guardian_threshold = threshold;
+        agent.last_active = clock.unix_timestamp;
+
+        emit!(GuardiansUpdated {
+            agent_id: agent.agent_id,
+            guardians: agent.guardians.clone(),
+            threshold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a recovery moving the profile to `new_owner` (guardian only).
+    /// The opening guardian's approval counts, and the recovery can run
+    /// `RECOVERY_TIMELOCK` after opening once enough guardians approve.
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &ctx.accounts.agent_profile;
+        let guardian = ctx.accounts.guardian.key();
+        let clock = Clock::get()?;
+
+        require!(agent.guardians.contains(&guardian), AgentVaultError::NotGuardian);
+        require!(new_owner != agent.wallet, AgentVaultError::InvalidRecoveryOwner);
+
+        let recovery = &mut ctx.accounts.recovery;
+        recovery.agent_id = agent.agent_id;
+        recovery.new_owner = new_owner;
+        recovery.initiator = guardian;
+        recovery.approvals = vec![guardian];
+        recovery.initiated_at = clock.unix_timestamp;
+        recovery.executable_at = clock.unix_timestamp + RECOVERY_TIMELOCK;
+        recovery.bump = ctx.bumps.recovery;
+
+        emit!(RecoveryInitiated {
+            agent_id: agent.agent_id,
+            new_owner,
+            initiator: guardian,
+            executable_at: recovery.executable_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Add the signing guardian's approval to a pending recovery
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &ctx.accounts.agent_profile;
+        let recovery = &mut ctx.accounts.recovery;
+        let guardian = ctx.accounts.guardian.key();
+        let clock = Clock::get()?;
+
+        require!(agent.guardians.contains(&guardian), AgentVaultError::NotGuardian);
+        require!(!recovery.approvals.contains(&guardian), AgentVaultError::RecoveryAlreadyApproved);
+
+        recovery.approvals.push(guardian);
+
+        emit!(RecoveryApproved {
+            agent_id: agent.agent_id,
+            guardian,
+            approvals: recovery.approvals.len() as u8,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Carry out a recovery once its timelock has passed and enough of the
+    /// profile's current guardians have approved (anyone may submit). Like
+    /// `transfer_ownership`, the profile keeps its address and reputation.
+    pub fn recover_account(ctx: Context<RecoverAccount>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let agent = &mut ctx.accounts.agent_profile;
+        let recovery = &ctx.accounts.recovery;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= recovery.executable_at,
+            AgentVaultError::RecoveryTimelockActive
+        );
+        // Guardians removed since they approved no longer count
+        let approvals = recovery
+            .approvals
+            .iter()
+            .filter(|guardian| agent.guardians.contains(*guardian))
+            .count();
+        require!(
+            agent.guardian_threshold > 0 && approvals >= agent.guardian_threshold as usize,
+            AgentVaultError::GuardianThresholdNotMet
+        );
+
+        let old_owner = agent.wallet;
+        agent.wallet = recovery.new_owner;
+        agent.last_active = clock.unix_timestamp;
+        // Domain proofs were bound to the old wallet
+        agent.verified_domains.clear();
+
+        let new_owner_index = &mut ctx.accounts.new_owner_index;
+        new_owner_index.profile = agent.key();
+        new_owner_index.bump = ctx.bumps.new_owner_index;
+
+        emit!(AccountRecovered {
+            agent_id: agent.agent_id,
+            old_owner,
+            new_owner: agent.wallet,
+            approvals: approvals as u8,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Call off a pending recovery (owner only); works while paused so a
+    /// hijack attempt can always be stopped
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        emit!(RecoveryCancelled {
+            agent_id: ctx.accounts.recovery.agent_id,
+            new_owner: ctx.accounts.recovery.new_owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a profile written under an older layout to `PROFILE_VERSION`
+    /// (permissionless; the payer covers any extra rent). The account is
+    /// grown to the current size first, so fields added since it was written
+    /// deserialize as zero and are then given their defaults here.
+    pub fn migrate_profile(ctx: Context<MigrateProfile>) -> Result<()> {
+        let info = ctx.accounts.agent_profile.to_account_info();
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let clock = Clock::get()?;
+
+        let (old_version, old_len) = profile_layout(&info.try_borrow_data()?)?;
+        require!(old_version < PROFILE_VERSION, AgentVaultError::ProfileAlreadyMigrated);
+
+        grow_account(&info, 8 + AgentProfile::INIT_SPACE, &payer, &system_program)?;
+        // Bytes past the old fields are left over from strings and lists
+        // that have since shrunk, and would be read as the new fields
+        info.try_borrow_mut_data()?[old_len..].fill(0);
+
+        let mut agent = AgentProfile::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+
+        // Profiles from before `agent_id` were seeded on their wallet
+        if agent.agent_id == Pubkey::default() {
+            agent.agent_id = agent.wallet;
+        }
+        require!(
+            pda_bump(&info, &[b"agent", agent.agent_id.as_ref()])? == agent.bump,
+            ErrorCode::ConstraintSeeds
+        );
+        if agent.last_decay_at == 0 {
+            agent.last_decay_at = agent.last_active;
+        }
+        if agent.skill_capacity == 0 {
+            agent.skill_capacity = DEFAULT_SKILL_CAPACITY;
+        }
+        // Older profiles don't know their endorsements' weights; credit each
+        // a full boost, and put whatever else moved the score in the
+        // adjustment so a recompute leaves it where it is
+        if old_version < 28 {
+            let stats = &ctx.accounts.registry_stats;
+            agent.endorsement_points = agent.endorsements_received.saturating_mul(stats.endorsement_boost as u32);
+            let earned = std::cmp::min(
+                stats.max_reputation as u32,
+                (stats.base_reputation as u32).saturating_add(agent.endorsement_points),
+            );
+            agent.reputation_adjustment = agent.reputation as i16 - earned as i16;
+        }
+        agent.version = PROFILE_VERSION;
+
+        // Profiles with grown skill capacity also need that extra room on
+        // top of the current layout
+        grow_account(&info, AgentProfile::space_for(agent.skill_capacity), &payer, &system_program)?;
+        agent.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(ProfileMigrated {
+            agent_id: agent.agent_id,
+            old_version,
+            new_version: agent.version,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade the registry written under an older layout to
+    /// `REGISTRY_VERSION` (permissionless; the payer covers any extra rent).
+    /// Its fields are all fixed-size, so growing it is enough for the new
+    /// ones to read as zero; those `initialize_registry` gives a default
+    /// are then set to it.
+    pub fn migrate_registry(ctx: Context<MigrateRegistry>) -> Result<()> {
+        let info = ctx.accounts.registry_stats.to_account_info();
+        let clock = Clock::get()?;
+
+        grow_account(
+            &info,
+            8 + RegistryStats::INIT_SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let mut stats = RegistryStats::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        let old_version = stats.version;
+        require!(old_version < REGISTRY_VERSION, AgentVaultError::RegistryAlreadyMigrated);
+
+        if old_version < 3 {
+            stats.min_endorser_age = DEFAULT_MIN_ENDORSER_AGE;
+        }
+        if old_version < 6 {
+            stats.reputation_half_life = DEFAULT_REPUTATION_HALF_LIFE;
+        }
+        if old_version < 7 {
+            stats.agent_ttl = DEFAULT_AGENT_TTL;
+        }
+        if old_version < 9 {
+            stats.skill_verification_threshold = DEFAULT_SKILL_VERIFICATION_THRESHOLD;
+        }
+        if old_version < 11 {
+            stats.max_skills = DEFAULT_SKILL_CAPACITY;
+            stats.max_name_len = MAX_NAME_LEN as u8;
+            stats.max_uri_len = MAX_METADATA_URI_LEN as u16;
+        }
+        if old_version < 15 {
+            stats.recovery_rate_bps = DEFAULT_RECOVERY_RATE_BPS;
+        }
+        if old_version < 23 {
+            stats.max_endorsement_credits = DEFAULT_MAX_ENDORSEMENT_CREDITS;
+            stats.credits_per_hour = DEFAULT_CREDITS_PER_HOUR;
+        }
+        if old_version < 25 {
+            stats.decay_fraction_bps = DEFAULT_DECAY_FRACTION_BPS;
+        }
+        if old_version < 26 {
+            stats.job_credit_cooldown = DEFAULT_JOB_CREDIT_COOLDOWN;
+        }
+        if old_version < 27 {
+            stats.decay_floor = DEFAULT_DECAY_FLOOR;
+        }
+        stats.version = REGISTRY_VERSION;
+        stats.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(RegistryMigrated {
+            old_version,
+            new_version: stats.version,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Initialize the registry (one-time setup)
+    pub fn initialize_registry(ctx: Context<InitializeRegistry>) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        stats.total_agents = 0;
+        stats.total_endorsements = 0;
+        stats.authority = ctx.accounts.authority.key();
+        stats.bump = ctx.bumps.registry_stats;
+        stats.paused = false;
+        stats.pending_authority = Pubkey::default();
+        stats.revoke_cooldown = DEFAULT_REVOKE_COOLDOWN;
+        stats.endorsement_ttl = DEFAULT_ENDORSEMENT_TTL;
+        stats.base_reputation = DEFAULT_BASE_REPUTATION;
+        stats.endorsement_boost = DEFAULT_ENDORSEMENT_BOOST;
+        stats.max_reputation = DEFAULT_MAX_REPUTATION;
+        stats.endorsement_weight_bps = DEFAULT_ENDORSEMENT_WEIGHT_BPS;
+        stats.review_weight_bps = DEFAULT_REVIEW_WEIGHT_BPS;
+        stats.version = REGISTRY_VERSION;
+        stats.min_endorser_age = DEFAULT_MIN_ENDORSER_AGE;
+        stats.reputation_half_life = DEFAULT_REPUTATION_HALF_LIFE;
+        stats.agent_ttl = DEFAULT_AGENT_TTL;
+        stats.skill_verification_threshold = DEFAULT_SKILL_VERIFICATION_THRESHOLD;
+        stats.recovery_rate_bps = DEFAULT_RECOVERY_RATE_BPS;
+        stats.max_skills = DEFAULT_SKILL_CAPACITY;
+        stats.max_name_len = MAX_NAME_LEN as u8;
+        stats.max_uri_len = MAX_METADATA_URI_LEN as u16;
+        stats.max_endorsement_credits = DEFAULT_MAX_ENDORSEMENT_CREDITS;
+        stats.credits_per_hour = DEFAULT_CREDITS_PER_HOUR;
+        stats.decay_fraction_bps = DEFAULT_DECAY_FRACTION_BPS;
+        stats.job_credit_cooldown = DEFAULT_JOB_CREDIT_COOLDOWN;
+        stats.decay_floor = DEFAULT_DECAY_FLOOR;
+        Ok(())
+    }
+
+    /// Adopt the top-agents leaderboard (authority only). The board is too
+    /// large to create through a CPI, so the caller allocates it first with
+    /// a system `create_account` of `Leaderboard::SPACE` bytes owned by this
+    /// program, in the same transaction.
+    pub fn initialize_leaderboard(ctx: Context<InitializeLeaderboard>) -> Result<()> {
+        let mut leaderboard = ctx.accounts.leaderboard.load_init()?;
+        leaderboard.len = 0;
+
+        ctx.accounts.registry_stats.leaderboard = ctx.accounts.leaderboard.key();
+        Ok(())
+    }
+
+    /// Create the leaderboard for `skill` (permissionless; the payer covers
+    /// rent). `skill` must already be in canonical form, as for endorsements.
+    pub fn initialize_skill_leaderboard(ctx: Context<InitializeSkillLeaderboard>, skill: String) -> Result<()> {
+        require!(normalize_skill(&skill)? == skill, AgentVaultError::InvalidSkillFormat);
+
+        let mut leaderboard = ctx.accounts.skill_leaderboard.load_init()?;
+        leaderboard.skill[..skill.len()].copy_from_slice(skill.as_bytes());
+        leaderboard.len = 0;
+
+        Ok(())
+    }
+
+    /// Create the registry's `SkillFrequency` (authority only). Until it
+    /// exists every skill earns the plain endorsement boost; afterwards,
+    /// counts cover skills declared from then on, so create it early.
+    pub fn initialize_skill_frequency(ctx: Context<InitializeSkillFrequency>) -> Result<()> {
+        let skill_frequency = &mut ctx.accounts.skill_frequency;
+        skill_frequency.entries = Vec::new();
+        skill_frequency.bump = ctx.bumps.skill_frequency;
+
+        Ok(())
+    }
+
+    /// Halt or resume all state-changing instructions (authority only)
+    ///
+    /// Pause and freeze are independent flags. `paused` is checked by the
+    /// user-facing instructions and leaves the authority free to adjust
+    /// parameters while it is on. `frozen` takes precedence: it fails
+    /// account validation of every instruction that loads the registry,
+    /// authority and moderator ones included, except `unfreeze`.
+    pub fn set_pause(ctx: Context<UpdateRegistry>, paused: bool) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.paused = paused;
+
+        emit!(PauseChanged {
+            paused,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze the whole program during an incident (authority only).
+    /// `reason` is a client-defined code telling responders what happened.
+    /// See `set_pause` for how this differs from pausing.
+    pub fn emergency_freeze(ctx: Context<FreezeRegistry>, reason: u8) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.frozen = true;
+        stats.frozen_reason = reason;
+
+        emit!(RegistryFrozen {
+            reason,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lift an emergency freeze (authority only). The pause flag is left as
+    /// it was.
+    pub fn unfreeze(ctx: Context<FreezeRegistry>) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        require!(stats.frozen, AgentVaultError::InvalidParameter);
+        let reason = stats.frozen_reason;
+        stats.frozen = false;
+        stats.frozen_reason = 0;
+
+        emit!(RegistryUnfrozen {
+            reason,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long, in seconds, an endorsement must exist before it can be
+    /// revoked (authority only)
+    pub fn set_revoke_cooldown(ctx: Context<UpdateRegistry>, seconds: i64) -> Result<()> {
+        require!(seconds >= 0, AgentVaultError::InvalidParameter);
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.revoke_cooldown = seconds;
+
+        emit!(RevokeCooldownUpdated {
+            revoke_cooldown: seconds,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the lifetime, in seconds, of newly created endorsements (authority
+    /// only). Goes through `queue_action` while a timelock is set.
+    pub fn set_endorsement_ttl(ctx: Context<UpdateRegistry>, seconds: i64) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::EndorsementTtl { seconds },
+            false,
+        )
+    }
+
+    /// Set the skill count, name length and metadata URI length that
+    /// registration and profile edits accept (authority only). Caps can't
+    /// exceed what the account layout holds. Lowering one leaves existing
+    /// profiles as they are; it only applies to later writes. Goes through
+    /// `queue_action` while a timelock is set.
+    pub fn set_limits(
+        ctx: Context<UpdateRegistry>,
+        max_skills: u8,
+        max_name_len: u8,
+        max_uri_len: u16,
+    ) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::Limits {
+                max_skills,
+                max_name_len,
+                max_uri_len,
+            },
+            false,
+        )
+    }
+
+    /// Set how old, in seconds, a profile must be before it can endorse
+    /// (authority only). Zero lifts the restriction.
+    pub fn set_min_endorser_age(ctx: Context<UpdateRegistry>, seconds: i64) -> Result<()> {
+        require!(seconds >= 0, AgentVaultError::InvalidParameter);
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.min_endorser_age = seconds;
+
+        emit!(MinEndorserAgeUpdated {
+            min_endorser_age: seconds,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the refundable lamport deposit locked on each new endorsement
+    /// (authority only). Zero makes endorsing free again; existing
+    /// endorsements keep the deposit they were made with. Goes through
+    /// `queue_action` while a timelock is set.
+    pub fn set_endorsement_deposit(ctx: Context<UpdateRegistry>, lamports: u64) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::EndorsementDeposit { lamports },
+            false,
+        )
+    }
+
+    /// Turn peer review on or off (authority only): while on, a profile can
+    /// only endorse skills it declares itself
+    pub fn set_require_endorser_has_skill(ctx: Context<UpdateRegistry>, required: bool) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.require_endorser_has_skill = required;
+
+        emit!(EndorserSkillRequirementUpdated {
+            required,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Choose what revocations take back from their target (authority
+    /// only); see `RevokePenaltyMode` for the tradeoff
+    pub fn set_revoke_penalty_mode(ctx: Context<UpdateRegistry>, mode: RevokePenaltyMode) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.revoke_penalty_mode = mode;
+
+        emit!(RevokePenaltyModeUpdated {
+            mode,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Require endorsement targets to have complete profiles (authority
+    /// only); see `AgentProfile::is_complete`. Existing endorsements stay.
+    pub fn set_require_complete_profile(ctx: Context<UpdateRegistry>, required: bool) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.require_complete_profile = required;
+
+        emit!(CompleteProfileRequirementUpdated {
+            required,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Require new registrations to prove personhood through
+    /// `personhood_program` (authority only); see `check_personhood`.
+    /// Existing agents are unaffected.
+    pub fn set_personhood_requirement(
+        ctx: Context<UpdateRegistry>,
+        required: bool,
+        personhood_program: Pubkey,
+    ) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        require!(
+            !required || (personhood_program != Pubkey::default() && personhood_program != crate::ID),
+            AgentVaultError::InvalidParameter
+        );
+        stats.require_personhood = required;
+        stats.personhood_program = personhood_program;
+
+        emit!(PersonhoodRequirementUpdated {
+            required,
+            personhood_program,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the reputation that penalties can't take verified agents below
+    /// (authority only). Zero removes the protection. Goes through
+    /// `queue_action` while a timelock is set.
+    pub fn set_verified_floor(ctx: Context<UpdateRegistry>, verified_floor: u8) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::VerifiedFloor { verified_floor },
+            false,
+        )
+    }
+
+    /// Size the endorsement credit pool every agent gets and how many credits
+    /// it regains per hour (authority only). Both must be non-zero; agents
+    /// holding more than a lowered maximum are cut down on their next
+    /// endorsement.
+    pub fn set_endorsement_credits(
+        ctx: Context<UpdateRegistry>,
+        max_endorsement_credits: u16,
+        credits_per_hour: u16,
+    ) -> Result<()> {
+        require!(
+            max_endorsement_credits > 0 && credits_per_hour > 0,
+            AgentVaultError::InvalidParameter
+        );
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.max_endorsement_credits = max_endorsement_credits;
+        stats.credits_per_hour = credits_per_hour;
+
+        emit!(EndorsementCreditsUpdated {
+            max_endorsement_credits,
+            credits_per_hour,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the reputation a profile needs to endorse (authority only). Zero
+    /// lets anyone endorse. Goes through `queue_action` while a timelock is
+    /// set.
+    pub fn set_min_endorser_reputation(ctx: Context<UpdateRegistry>, reputation: u8) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::MinEndorserReputation { reputation },
+            false,
+        )
+    }
+
+    /// Set the share of an agent's outstanding decay, in basis points, that
+    /// each heartbeat or new endorsement restores (authority only). Zero
+    /// turns recovery off.
+    pub fn set_recovery_rate_bps(ctx: Context<UpdateRegistry>, recovery_rate_bps: u16) -> Result<()> {
+        require!(recovery_rate_bps <= BPS_DENOMINATOR, AgentVaultError::InvalidParameter);
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.recovery_rate_bps = recovery_rate_bps;
+
+        emit!(RecoveryRateUpdated {
+            recovery_rate_bps,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the endorsement count at which a skill is flagged verified
+    /// (authority only). Zero turns verification off. Existing flags follow
+    /// on the skill's next endorsement change or `check_skill_verification`.
+    pub fn set_skill_verification_threshold(ctx: Context<UpdateRegistry>, threshold: u32) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.skill_verification_threshold = threshold;
+
+        emit!(SkillVerificationThresholdUpdated {
+            threshold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long, in seconds, registrations and renewals last (authority
+    /// only). Zero stops new registrations from expiring; existing expiry
+    /// dates are left as they are. Goes through `queue_action` while a
+    /// timelock is set.
+    pub fn set_agent_ttl(ctx: Context<UpdateRegistry>, seconds: i64) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::AgentTtl { seconds },
+            false,
+        )
+    }
+
+    /// Set the key whose signatures `verify_domain` accepts (authority
+    /// only). `Pubkey::default()` turns domain verification off.
+    pub fn set_domain_verifier(ctx: Context<UpdateRegistry>, domain_verifier: Pubkey) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.domain_verifier = domain_verifier;
+
+        emit!(DomainVerifierUpdated {
+            domain_verifier,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the key allowed to take reputation snapshots alongside the
+    /// authority (authority only), typically a governance program's PDA
+    /// signing through CPI. `Pubkey::default()` leaves it to the authority.
+    pub fn set_snapshot_authority(ctx: Context<UpdateRegistry>, snapshot_authority: Pubkey) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.snapshot_authority = snapshot_authority;
+
+        emit!(SnapshotAuthorityUpdated {
+            snapshot_authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the key allowed to import external reputation (authority only);
+    /// `Pubkey::default()` disables imports
+    pub fn set_reputation_oracle(ctx: Context<UpdateRegistry>, reputation_oracle: Pubkey) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.reputation_oracle = reputation_oracle;
+
+        emit!(ReputationOracleUpdated {
+            reputation_oracle,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Tune the reputation economics (authority only). Existing scores are
+    /// left as they are. Goes through `queue_action` while a timelock is set.
+    pub fn set_reputation_params(
+        ctx: Context<UpdateRegistry>,
+        base_reputation: u8,
+        endorsement_boost: u8,
+        max_reputation: u8,
+    ) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::ReputationParams {
+                base_reputation,
+                endorsement_boost,
+                max_reputation,
+            },
+            false,
+        )
+    }
+
+    /// Set the age, in seconds, at which `recompute_time_weighted_reputation`
+    /// counts an endorsement at half weight (authority only)
+    pub fn set_reputation_half_life(ctx: Context<UpdateRegistry>, seconds: i64) -> Result<()> {
+        require!(seconds > 0, AgentVaultError::InvalidParameter);
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.reputation_half_life = seconds;
+
+        emit!(ReputationHalfLifeUpdated {
+            reputation_half_life: seconds,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of the reputation above the floor that each decay
+    /// interval takes, in basis points (authority only). Goes through
+    /// `queue_action` while a timelock is set.
+    pub fn set_decay_fraction(ctx: Context<UpdateRegistry>, decay_fraction_bps: u16) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::DecayFraction { decay_fraction_bps },
+            false,
+        )
+    }
+
+    /// Set the reputation inactivity decay stops at (authority only). It
+    /// can't exceed `max_reputation`. Goes through `queue_action` while a
+    /// timelock is set.
+    pub fn set_decay_floor(ctx: Context<UpdateRegistry>, decay_floor: u8) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::DecayFloor { decay_floor },
+            false,
+        )
+    }
+
+    /// Set which completed jobs credit their worker reputation: the smallest
+    /// amount, the mint (`Pubkey::default()` for any) and the gap between
+    /// credits for one payer and worker (authority only)
+    pub fn set_job_credit_policy(
+        ctx: Context<UpdateRegistry>,
+        min_amount: u64,
+        mint: Pubkey,
+        cooldown: i64,
+    ) -> Result<()> {
+        require!(cooldown > 0, AgentVaultError::InvalidParameter);
+
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.min_credited_job_amount = min_amount;
+        stats.credited_job_mint = mint;
+        stats.job_credit_cooldown = cooldown;
+
+        emit!(JobCreditPolicyUpdated {
+            min_amount,
+            mint,
+            cooldown,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how `recompute_reputation` blends endorsements and reviews
+    /// (authority only). Weights are basis points and must sum to 10000.
+    /// Goes through `queue_action` while a timelock is set.
+    pub fn set_reputation_weights(
+        ctx: Context<UpdateRegistry>,
+        endorsement_weight_bps: u16,
+        review_weight_bps: u16,
+    ) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::ReputationWeights {
+                endorsement_weight_bps,
+                review_weight_bps,
+            },
+            false,
+        )
+    }
+
+    /// Set how much imported external reputation counts in
+    /// `recompute_reputation` (authority only), in basis points. Goes
+    /// through `queue_action` while a timelock is set.
+    pub fn set_external_reputation_weight(ctx: Context<UpdateRegistry>, external_weight_bps: u16) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::ExternalReputationWeight { external_weight_bps },
+            false,
+        )
+    }
+
+    /// Choose how new endorsements are weighted (authority only); see
+    /// `ReputationAlgorithm`. Goes through `queue_action` while a timelock
+    /// is set.
+    pub fn set_reputation_algorithm(ctx: Context<UpdateRegistry>, algorithm: ReputationAlgorithm) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::ReputationAlgorithm { algorithm },
+            false,
+        )
+    }
+
+    /// Punish a misbehaving agent (authority or moderator): cut `reputation_penalty`
+    /// points and send up to `stake_penalty` of its stake to the program's
+    /// stake treasury, which only `withdraw_stake_treasury` empties. While a timelock is set, the authority queues the
+    /// matching `TimelockedAction::Slash` first and it is passed here.
+    pub fn slash_agent(
+        ctx: Context<SlashAgent>,
+        reputation_penalty: u8,
+        stake_penalty: u64,
+        reason: u8,
+    ) -> Result<()> {
+        apply_slash(ctx.accounts, &ctx.bumps, reputation_penalty, stake_penalty, reason)
+    }
+
+    /// Configure the token and minimum amount agents must stake to register
+    /// (authority only), creating the program's vault and stake treasury
+    /// for that mint if needed.
+    /// A zero amount makes staking optional again. While a timelock is set,
+    /// the matching `TimelockedAction::StakeConfig` is queued first and
+    /// passed here.
+    pub fn set_stake_config(ctx: Context<SetStakeConfig>, registration_stake: u64) -> Result<()> {
+        let mint_key = ctx.accounts.stake_mint.key();
+        let vault = &ctx.accounts.stake_vault;
+        let clock = Clock::get()?;
+
+        consume_queued_action(
+            &ctx.accounts.registry_stats,
+            &ctx.accounts.queued_action,
+            &TimelockedAction::StakeConfig {
+                stake_mint: mint_key,
+                registration_stake,
+            },
+            &ctx.accounts.authority.to_account_info(),
+        )?;
+
+        if vault.data_is_empty() {
+            let vault_seeds: &[&[u8]] = &[b"stake_vault", mint_key.as_ref(), &[ctx.bumps.stake_vault]];
+            create_token_account(
+                &ctx.accounts.authority.to_account_info(),
+                &vault.to_account_info(),
+                &ctx.accounts.stake_mint.to_account_info(),
+                &vault.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                vault_seeds,
+            )?;
+        }
+        let stake_treasury = &ctx.accounts.stake_treasury;
+        if stake_treasury.data_is_empty() {
+            let treasury_seeds: &[&[u8]] = &[b"stake_treasury", mint_key.as_ref(), &[ctx.bumps.stake_treasury]];
+            create_token_account(
+                &ctx.accounts.authority.to_account_info(),
+                &stake_treasury.to_account_info(),
+                &ctx.accounts.stake_mint.to_account_info(),
+                &stake_treasury.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                treasury_seeds,
+            )?;
+        }
+
+        let stats = &mut ctx.accounts.registry_stats;
+        stats.stake_mint = mint_key;
+        stats.registration_stake = registration_stake;
+
+        emit!(StakeConfigUpdated {
+            mint: mint_key,
+            registration_stake,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the lamport fee charged on registration and the wallet it is paid
+    /// to (authority only). A zero fee makes registration free again. Point
+    /// the fee at the `treasury` PDA to collect it with `withdraw_treasury`.
+    /// Goes through `queue_action` while a timelock is set.
+    pub fn set_fee_config(
+        ctx: Context<UpdateRegistry>,
+        registration_fee: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::FeeConfig {
+                registration_fee,
+                treasury,
+            },
+            false,
+        )
+    }
+
+    /// Set the share of the registration fee, in basis points, paid to a new
+    /// agent's referrer instead of the treasury (authority only). Goes
+    /// through `queue_action` while a timelock is set.
+    pub fn set_referral_fee_bps(ctx: Context<UpdateRegistry>, referral_fee_bps: u16) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::ReferralFeeBps { referral_fee_bps },
+            false,
+        )
+    }
+
+    /// Set the delay, in seconds, between queuing a `TimelockedAction` and
+    /// executing it (authority only). While it is non-zero the direct
+    /// setters for those actions are disabled, and changing the delay
+    /// itself has to be queued too.
+    pub fn set_timelock_delay(ctx: Context<UpdateRegistry>, seconds: i64) -> Result<()> {
+        apply_timelocked_action(
+            &mut ctx.accounts.registry_stats,
+            TimelockedAction::TimelockDelay { seconds },
+            false,
+        )
+    }
+
+    /// Queue a `TimelockedAction` by its hash (authority only). Only the
+    /// hash goes on chain until execution, but anyone watching can check a
+    /// candidate action against it during the delay.
+    pub fn queue_action(ctx: Context<QueueAction>, action_hash: [u8; 32]) -> Result<()> {
+        let queued = &mut ctx.accounts.queued_action;
+        let clock = Clock::get()?;
+
+        queued.action_hash = action_hash;
+        queued.queued_at = clock.unix_timestamp;
+        queued.execute_after = clock
+            .unix_timestamp
+            .saturating_add(ctx.accounts.registry_stats.timelock_delay);
+        queued.bump = ctx.bumps.queued_action;
+
+        emit!(ActionQueued {
+            action_hash,
+            execute_after: queued.execute_after,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Run a queued action once its delay has passed (authority only). The
+    /// `QueuedAction` PDA is seeded on the hash of `action`, so only the
+    /// exact parameters that were queued can be executed. Stake config,
+    /// treasury withdrawals and slashes instead take their queued action
+    /// through their own instructions.
+    pub fn execute_action(ctx: Context<ExecuteAction>, action: TimelockedAction) -> Result<()> {
+        let queued = &ctx.accounts.queued_action;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= queued.execute_after,
+            AgentVaultError::TimelockNotElapsed
+        );
+        let action_hash = queued.action_hash;
+
+        apply_timelocked_action(&mut ctx.accounts.registry_stats, action, true)?;
+
+        emit!(ActionExecuted {
+            action_hash,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a queued action before it runs (authority only)
+    pub fn cancel_action(ctx: Context<CancelAction>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        emit!(ActionCancelled {
+            action_hash: ctx.accounts.queued_action.action_hash,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move `amount` lamports out of the program's treasury PDA (authority
+    /// only). The treasury always keeps enough to stay rent-exempt. While a
+    /// timelock is set, the matching `TimelockedAction::TreasuryWithdrawal`
+    /// is queued first and passed here.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        let clock = Clock::get()?;
+
+        consume_queued_action(
+            &ctx.accounts.registry_stats,
+            &ctx.accounts.queued_action,
+            &TimelockedAction::TreasuryWithdrawal {
+                amount,
+                destination: ctx.accounts.destination.key(),
+            },
+            &ctx.accounts.authority.to_account_info(),
+        )?;
+
+        let reserve = Rent::get()?.minimum_balance(0);
+        let available = treasury.lamports().saturating_sub(reserve);
+        require!(amount <= available, AgentVaultError::InsufficientTreasuryBalance);
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: treasury.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                },
+                &[&[b"treasury", &[ctx.bumps.treasury]]],
+            ),
+            amount,
+        )?;
+
+        emit!(TreasuryWithdrawn {
+            amount,
+            destination: ctx.accounts.destination.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move `amount` of slashed stake out of the program's stake treasury
+    /// for `stake_mint` (authority only). While a timelock is set, the
+    /// matching `TimelockedAction::StakeTreasuryWithdrawal` is queued first
+    /// and passed here.
+    pub fn withdraw_stake_treasury(ctx: Context<WithdrawStakeTreasury>, amount: u64) -> Result<()> {
+        let mint = &ctx.accounts.stake_mint;
+        let stake_treasury = &ctx.accounts.stake_treasury;
+        let destination = &ctx.accounts.destination;
+        let clock = Clock::get()?;
+
+        consume_queued_action(
+            &ctx.accounts.registry_stats,
+            &ctx.accounts.queued_action,
+            &TimelockedAction::StakeTreasuryWithdrawal {
+                stake_mint: mint.key(),
+                amount,
+                destination: destination.key(),
+            },
+            &ctx.accounts.authority.to_account_info(),
+        )?;
+        require!(
+            amount <= token_amount(&stake_treasury.to_account_info())?,
+            AgentVaultError::InsufficientTreasuryBalance
+        );
+
+        let mint_key = mint.key();
+        token_transfer_checked(
+            &ctx.accounts.token_program.to_account_info(),
+            &stake_treasury.to_account_info(),
+            &mint.to_account_info(),
+            &destination.to_account_info(),
+            &stake_treasury.to_account_info(),
+            amount,
+            mint.decimals,
+            &[&[b"stake_treasury", mint_key.as_ref(), &[ctx.bumps.stake_treasury]]],
+        )?;
+
+        emit!(StakeTreasuryWithdrawn {
+            mint: mint_key,
+            amount,
+            destination: destination.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Start handing the registry authority to a new key (authority only).
+    /// Nothing changes until the proposed key calls `accept_authority`.
+    pub fn propose_authority(ctx: Context<UpdateRegistry>, new_authority: Pubkey) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        stats.pending_authority = new_authority;
+
+        emit!(AuthorityTransferProposed {
+            authority: stats.authority,
+            pending_authority: new_authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Complete an authority transfer; must be signed by the pending authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let stats = &mut ctx.accounts.registry_stats;
+        let clock = Clock::get()?;
+
+        let old_authority = stats.authority;
+        stats.authority = ctx.accounts.new_authority.key();
+        stats.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            old_authority,
+            new_authority: stats.authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Grant a wallet moderation powers (authority only)
+    pub fn add_moderator(ctx: Context<AddModerator>, wallet: Pubkey) -> Result<()> {
+        let moderator = &mut ctx.accounts.moderator;
+        let clock = Clock::get()?;
+
+        moderator.wallet = wallet;
+        moderator.added_at = clock.unix_timestamp;
+        moderator.bump = ctx.bumps.moderator;
+
+        emit!(ModeratorAdded {
+            wallet,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a wallet's moderation powers (authority only)
+    pub fn remove_moderator(ctx: Context<RemoveModerator>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        emit!(ModeratorRemoved {
+            wallet: ctx.accounts.moderator.wallet,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Bar a wallet from registering (authority or moderator)
+    pub fn blacklist_wallet(ctx: Context<BlacklistWallet>, wallet: Pubkey) -> Result<()> {
+        let entry = &mut ctx.accounts.blacklist_entry;
+        let clock = Clock::get()?;
+
+        entry.wallet = wallet;
+        entry.blacklisted_at = clock.unix_timestamp;
+        entry.bump = ctx.bumps.blacklist_entry;
+
+        emit!(BlacklistUpdated {
+            wallet,
+            blacklisted: true,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a wallet's blacklisting (authority or moderator)
+    pub fn unblacklist_wallet(ctx: Context<UnblacklistWallet>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        emit!(BlacklistUpdated {
+            wallet: ctx.accounts.blacklist_entry.wallet,
+            blacklisted: false,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set an agent's verification badge (authority or moderator); 0 means
+    /// unverified
+    pub fn set_verification(ctx: Context<SetVerification>, tier: u8) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_profile;
+        let clock = Clock::get()?;
+
+        let old_tier = agent.verification_tier;
+        agent.verification_tier = tier;
+
+        emit!(AgentVerified {
+            wallet: agent.wallet,
+            old_tier,
+            new_tier: tier,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Flag an agent for moderator review. A reporter can hold only one open
+    /// report per agent; `note` may be empty.
+    pub fn report_agent(ctx: Context<ReportAgent>, reason: u8, note: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(note.len() <= MAX_REPORT_NOTE_LEN, AgentVaultError::ReportNoteTooLong);
+
+        let report = &mut ctx.accounts.report;
+        let target = &mut ctx.accounts.target_agent;
+        let clock = Clock::get()?;
+
+        report.reporter = ctx.accounts.reporter.key();
+        report.target = target.agent_id;
+        report.reason = reason;
+        report.note = note;
+        report.created_at = clock.unix_timestamp;
+        report.bump = ctx.bumps.report;
+
+        target.report_count = target.report_count.saturating_add(1);
+
+        emit!(AgentReported {
+            reporter: report.reporter,
+            target: report.target,
+            reason,
+            report_count: target.report_count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close a report (authority or moderator), returning its rent to the
+    /// reporter. Non-zero penalties also slash the agent as `slash_agent`
+    /// would, with the report's reason code.
+    pub fn resolve_report(
+        ctx: Context<ResolveReport>,
+        reputation_penalty: u8,
+        stake_penalty: u64,
+    ) -> Result<()> {
+        let slashed = reputation_penalty > 0 || stake_penalty > 0;
+        if slashed {
+            apply_slash(
+                &mut ctx.accounts.slash,
+                &ctx.bumps.slash,
+                reputation_penalty,
+                stake_penalty,
+                ctx.accounts.report.reason,
+            )?;
+        }
+
+        let clock = Clock::get()?;
+
+        emit!(ReportResolved {
+            reporter: ctx.accounts.report.reporter,
+            target: ctx.accounts.report.target,
+            resolved_by: ctx.accounts.slash.signer.key(),
+            slashed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create a team owned by the signer. Teams are seeded on the owner and
+    /// the name, so one wallet can run several crews under distinct names.
+    pub fn create_team(ctx: Context<CreateTeam>, name: String) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+        require!(
+            !name.trim().is_empty() && name.len() <= MAX_NAME_LEN,
+            AgentVaultError::InvalidTeamName
+        );
+
+        let team = &mut ctx.accounts.team;
+        let clock = Clock::get()?;
+
+        team.owner = ctx.accounts.owner.key();
+        team.name = name;
+        team.members = Vec::new();
+        team.reputation = 0;
+        team.created_at = clock.unix_timestamp;
+        team.updated_at = clock.unix_timestamp;
+        team.bump = ctx.bumps.team;
+
+        emit!(TeamCreated {
+            team: team.key(),
+            owner: team.owner,
+            name: team.name.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Add an agent to a team. Both the team owner and the agent's owner
+    /// sign, so no agent can be listed in a crew it didn't join.
+    pub fn add_member(ctx: Context<AddMember>) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let team = &mut ctx.accounts.team;
+        let agent_id = ctx.accounts.agent_profile.agent_id;
+        let clock = Clock::get()?;
+
+        require!(!team.members.contains(&agent_id), AgentVaultError::AlreadyTeamMember);
+        require!(team.members.len() < MAX_TEAM_MEMBERS, AgentVaultError::TeamFull);
+
+        team.members.push(agent_id);
+        team.updated_at = clock.unix_timestamp;
+
+        emit!(MemberAdded {
+            team: team.key(),
+            agent_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Drop an agent (by `agent_id`) from a team (team owner only). Works for
+    /// agents that have since deregistered.
+    pub fn remove_member(ctx: Context<RemoveMember>, agent_id: Pubkey) -> Result<()> {
+        let team = &mut ctx.accounts.team;
+        let clock = Clock::get()?;
+
+        let index = team
+            .members
+            .iter()
+            .position(|member| *member == agent_id)
+            .ok_or(AgentVaultError::NotTeamMember)?;
+        team.members.remove(index);
+        team.updated_at = clock.unix_timestamp;
+
+        emit!(MemberRemoved {
+            team: team.key(),
+            agent_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Rewrite a team's reputation as the average of its members' current
+    /// reputations (permissionless). `remaining_accounts` must hold each
+    /// member's profile PDA in `members` order; members whose profile no
+    /// longer exists are left out of the average.
+    pub fn recompute_team_reputation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecomputeTeamReputation<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+
+        let team = &mut ctx.accounts.team;
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.remaining_accounts.len() == team.members.len(),
+            AgentVaultError::BatchAccountsMismatch
+        );
+
+        let mut total: u32 = 0;
+        let mut live: u32 = 0;
+        for (info, agent_id) in ctx.remaining_accounts.iter().zip(team.members.iter()) {
+            pda_bump(info, &[b"agent", agent_id.as_ref()])?;
+            if info.owner != &crate::ID {
+                continue;
+            }
+            let member = Account::<AgentProfile>::try_from(info)?;
+            total += member.reputation as u32;
+            live += 1;
+        }
+
+        team.reputation = if live == 0 { 0 } else { (total / live) as u8 };
+        team.updated_at = clock.unix_timestamp;
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Reputation boost for an endorsement.
+///
+/// `boost` is what a mid-reputation (50-99) endorser gives at a neutral
+/// rating. Endorsers below 50 give half that and endorsers at 100 one and a
+/// half times (with the default boost of 2: +1, +2, +3), and the 1-5 rating
+/// then scales around a neutral 3. Both steps round up, so a non-zero boost
+/// is always worth at least 1.
+pub fn endorsement_weight(boost: u8, endorser_reputation: u8, rating: u8) -> u8 {
+    let base = (boost as u32 * (1 + endorser_reputation as u32 / 50)).div_ceil(2);
+    std::cmp::min((base * rating as u32).div_ceil(3), u8::MAX as u32) as u8
+}
+
+/// Boost for a new endorsement under the registry's `algorithm`, before the
+/// reciprocal and multi-skill reductions. `prior_endorsements` is how many
+/// the target held before this one.
+///
+/// - `LinearBoost`: `endorsement_weight`.
+/// - `LogarithmicBoost`: that boost divided by `1 + log2(1 + prior) / 2`, so
+///   the 1st endorsement counts in full, the 2nd two thirds, the 4th half
+///   and the 8th two fifths.
+/// - `WeightedByEndorser`: `boost * endorser_reputation / 50`, so a 50-rep
+///   endorser gives the boost and a 100-rep one twice it, then scaled by
+///   rating as in `endorsement_weight`.
+///
+/// Every step rounds up, so a non-zero boost is always worth at least 1.
+pub fn algorithm_weight(stats: &RegistryStats, endorser_reputation: u8, prior_endorsements: u32, rating: u8) -> u8 {
+    let boost = stats.endorsement_boost;
+    match stats.algorithm {
+        ReputationAlgorithm::LinearBoost => endorsement_weight(boost, endorser_reputation, rating),
+        ReputationAlgorithm::LogarithmicBoost => {
+            let weight = endorsement_weight(boost, endorser_reputation, rating) as u32;
+            let log = prior_endorsements.saturating_add(1).ilog2();
+            (weight * 2).div_ceil(2 + log) as u8
+        }
+        ReputationAlgorithm::WeightedByEndorser => {
+            let base = (boost as u32 * endorser_reputation as u32).div_ceil(50);
+            std::cmp::min((base * rating as u32).div_ceil(3), u8::MAX as u32) as u8
+        }
+    }
+}
+
+/// Reputation `intervals` of inactivity decay take from `reputation`. Each
+/// interval removes `ceil((reputation - floor) * fraction_bps / 10000)` of
+/// what is left, so a score falls in proportion to its height above the
+/// floor, by at least a point per interval, and never below the floor.
+pub fn decay_penalty(reputation: u8, floor: u8, intervals: i64, fraction_bps: u16) -> u8 {
+    let mut remaining = reputation.saturating_sub(floor) as u64;
+    for _ in 0..intervals {
+        if remaining == 0 {
+            break;
+        }
+        let step = (remaining * fraction_bps as u64).div_ceil(BPS_DENOMINATOR as u64);
+        remaining -= std::cmp::min(step, remaining);
+    }
+
+    reputation.saturating_sub(floor) - remaining as u8
+}
+
+/// Reputation a revocation takes off the target under the registry's
+/// `RevokePenaltyMode`. The current-reputation mode needs the endorser's
+/// profile and falls back to the stored weight without it. `target` must
+/// still count the endorsement being revoked.
+pub fn revoke_penalty(
+    endorsement: &Endorsement,
+    endorser_profile: Option<&AgentProfile>,
+    target: &AgentProfile,
+    stats: &RegistryStats,
+) -> u8 {
+    match (stats.revoke_penalty_mode, endorser_profile) {
+        (RevokePenaltyMode::CurrentReputation, Some(endorser_profile)) => {
+            let weight = algorithm_weight(
+                stats,
+                endorser_profile.reputation,
+                target.endorsements_received.saturating_sub(1),
+                endorsement.rating,
+            );
+            if endorsement.reciprocal {
+                weight / 2
+            } else {
+                weight
+            }
+        }
+        _ => endorsement.weight,
+    }
+}
+
+/// Refill `profile`'s endorsement credits for the time since
+/// `credits_updated_at`, at `credit_rate` per hour up to `credit_limit`,
+/// then spend `count` of them. Unlike a quota reset at midnight, capacity
+/// comes back steadily, so there is no burst at the day boundary.
+pub fn spend_endorsement_credits(profile: &mut AgentProfile, stats: &RegistryStats, count: u16, now: i64) -> Result<()> {
+    let max = stats.credit_limit();
+    let rate = stats.credit_rate() as i64;
+
+    if profile.credits_updated_at == 0 {
+        profile.endorsement_credits = max;
+        profile.credits_updated_at = now;
+    }
+    let gained = (now - profile.credits_updated_at).max(0) * rate / SECONDS_PER_HOUR;
+    if profile.endorsement_credits as i64 + gained >= max as i64 {
+        profile.endorsement_credits = max;
+        profile.credits_updated_at = now;
+    } else {
+        profile.endorsement_credits += gained as u16;
+        // Only the time that earned whole credits is used up, so progress
+        // towards the next one carries over
+        profile.credits_updated_at += gained * SECONDS_PER_HOUR / rate;
+    }
+
+    require!(profile.endorsement_credits >= count, AgentVaultError::NoEndorsementCredits);
+    profile.endorsement_credits -= count;
+
+    Ok(())
+}
+
+/// Scales an endorsement's boost by how many of the target's skills the
+/// same endorser already backs: the first counts in full, the second half,
+/// the third a quarter and so on, so no single relationship can carry a
+/// target's reputation.
+pub fn diminished_weight(weight: u8, prior: u32) -> u8 {
+    weight.checked_shr(prior).unwrap_or(0)
+}
+
+/// Registry-wide requirements on who may endorse, shared by the endorsement
+/// instructions. Freshly minted profiles are a Sybil signal, so a profile
+/// must reach `min_endorser_age` first; with a `min_endorser_reputation`
+/// above the base reputation, it must also have earned some standing.
+pub fn check_endorser(endorser: &AgentProfile, stats: &RegistryStats, now: i64) -> Result<()> {
+    require!(
+        now >= endorser.registered_at.saturating_add(stats.min_endorser_age),
+        AgentVaultError::EndorserTooNew
+    );
+    require!(
+        endorser.reputation >= stats.min_endorser_reputation,
+        AgentVaultError::EndorserReputationTooLow
+    );
+
+    Ok(())
+}
+
+/// Shared body of the registration instructions: validate the profile and
+/// initialize the agent's accounts
+pub fn initialize_agent(
+    accounts: &mut RegisterAgent,
+    bumps: &RegisterAgentBumps,
+    name: String,
+    metadata_uri: String,
+    skills: Vec<String>,
+    categories: Vec<String>,
+    referrer: Option<Pubkey>,
+    bio: Option<String>,
+    capabilities: u64,
+    region: Option<String>,
+) -> Result<()> {
+    require!(!accounts.registry_stats.paused, AgentVaultError::RegistryPaused);
+    validate_capabilities(capabilities)?;
+    let region = region.unwrap_or_default();
+    validate_region(&region)?;
+    let stats = &accounts.registry_stats;
+    require!(name.len() <= stats.name_limit(), AgentVaultError::NameTooLong);
+    let bio = bio.unwrap_or_default();
+    require!(bio.len() <= MAX_BIO_LEN, AgentVaultError::BioTooLong);
+    check_personhood(
+        stats,
+        accounts.personhood_program.as_ref().map(|p| p.to_account_info()),
+        accounts.personhood_credential.as_ref().map(|c| c.to_account_info()),
+        &accounts.owner.to_account_info(),
+    )?;
+    validate_metadata_uri(&metadata_uri, stats.uri_limit())?;
+    let skills = normalize_skills(skills, stats.skill_limit().min(DEFAULT_SKILL_CAPACITY as usize))?;
+    let categories = normalize_categories(categories)?;
+
+    // A blacklist entry exists iff the address is owned by this program
+    require!(
+        accounts.blacklist_entry.owner != &crate::ID,
+        AgentVaultError::WalletBlacklisted
+    );
+
+    let owner = accounts.owner.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+    claim_name(&name, owner.key(), &accounts.name_registry.to_account_info(), &owner, &system_program)?;
+
+    // A referral needs the referrer's profile, its wallet (paid a cut of
+    // the fee) and the new agent's `Referral` address. Only this program
+    // creates `AgentProfile` accounts, so a matching `agent_id` is enough.
+    let referral_accounts = match referrer {
+        Some(referrer) => {
+            require!(referrer != owner.key(), AgentVaultError::SelfReferral);
+            let (Some(profile), Some(wallet), Some(referral)) = (
+                &accounts.referrer_profile,
+                &accounts.referrer_wallet,
+                &accounts.referral,
+            ) else {
+                return err!(AgentVaultError::InvalidReferrer);
+            };
+            require!(
+                profile.agent_id == referrer && wallet.key() == profile.wallet,
+                AgentVaultError::InvalidReferrer
+            );
+            Some((wallet.to_account_info(), referral.to_account_info()))
+        }
+        None => None,
+    };
+
+    let reward = charge_registration_fee(
+        &accounts.registry_stats,
+        accounts.treasury.as_ref().map(|t| t.to_account_info()),
+        referral_accounts.as_ref().map(|(wallet, _)| wallet.clone()),
+        &owner,
+        &system_program,
+    )?;
+
+    let agent = &mut accounts.agent_profile;
+    let clock = Clock::get()?;
+
+    agent.set_inner(AgentProfile::new(
+        owner.key(),
+        name,
+        metadata_uri,
+        skills,
+        categories,
+        accounts.registry_stats.base_reputation,
+        bumps.agent_profile,
+        clock.unix_timestamp,
+        agent_expiry(&accounts.registry_stats, clock.unix_timestamp),
+        accounts.registry_stats.next_agent_index,
+    ));
+    agent.bio = bio;
+    agent.capabilities = capabilities;
+    agent.region = region;
+    record_skill_frequency(&accounts.skill_frequency, &[], &agent.skills)?;
+
+    let agent_index = &mut accounts.agent_index;
+    agent_index.index = accounts.registry_stats.next_agent_index;
+    agent_index.agent = agent.agent_id;
+    agent_index.deregistered_at = 0;
+    agent_index.bump = bumps.agent_index;
+
+    let owner_index = &mut accounts.owner_index;
+    owner_index.profile = agent.key();
+    owner_index.bump = bumps.owner_index;
+
+    let history = &mut accounts.reputation_history;
+    history.agent = agent.key();
+    history.bump = bumps.reputation_history;
+
+    if let (Some(referrer), Some((_, referral))) = (referrer, referral_accounts) {
+        let referral_bump = pda_bump(&referral, &[b"referral", owner.key.as_ref()])?;
+        create_pda(
+            &owner,
+            &referral,
+            &system_program,
+            &[b"referral", owner.key.as_ref(), &[referral_bump]],
+            &Referral {
+                referrer,
+                referred: owner.key(),
+                reward,
+                timestamp: clock.unix_timestamp,
+                bump: referral_bump,
+            },
+        )?;
+        if let Some(profile) = accounts.referrer_profile.as_mut() {
+            profile.referral_count += 1;
+        }
+
+        emit!(ReferralRecorded {
+            referrer,
+            referred: owner.key(),
+            reward,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    // Update registry stats
+    let stats = &mut accounts.registry_stats;
+    stats.total_agents += 1;
+    stats.next_agent_index += 1;
+
+    emit!(AgentRegistered {
+        wallet: agent.wallet,
+        name: agent.name.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Shared body of `slash_agent` and `resolve_report`: cut reputation and
+/// send up to `stake_penalty` of the agent's stake to the stake treasury
+pub fn apply_slash(
+    accounts: &mut SlashAgent,
+    bumps: &SlashAgentBumps,
+    reputation_penalty: u8,
+    stake_penalty: u64,
+    reason: u8,
+) -> Result<()> {
+    consume_queued_action(
+        &accounts.registry_stats,
+        &accounts.queued_action,
+        &TimelockedAction::Slash {
+            agent_id: accounts.agent_profile.agent_id,
+            reputation_penalty,
+            stake_penalty,
+            reason,
+        },
+        &accounts.signer.to_account_info(),
+    )?;
+
+    let agent = &mut accounts.agent_profile;
+    let clock = Clock::get()?;
+
+    let old_reputation = agent.reputation;
+    adjust_reputation(agent, -(reputation_penalty as i16), &accounts.registry_stats);
+    agent.slash_count += 1;
+
+    log_reputation_change(
+        &mut accounts.reputation_history,
+        agent,
+        old_reputation,
+        ReputationReason::Slashed,
+        clock.unix_timestamp,
+    );
+    update_leaderboard(&accounts.leaderboard, agent.agent_id, agent.reputation)?;
+
+    let stake_slashed = std::cmp::min(stake_penalty, agent.staked_amount);
+    if stake_slashed > 0 {
+        let (Some(stake_mint), Some(stake_vault), Some(stake_treasury), Some(token_program)) = (
+            &accounts.stake_mint,
+            &accounts.stake_vault,
+            &accounts.stake_treasury,
+            &accounts.token_program,
+        ) else {
+            return err!(AgentVaultError::StakeAccountsMissing);
+        };
+
+        let mint_key = stake_mint.key();
+        let vault_bump = bumps.stake_vault.ok_or(AgentVaultError::StakeAccountsMissing)?;
+        token_transfer_checked(
+            &token_program.to_account_info(),
+            &stake_vault.to_account_info(),
+            &stake_mint.to_account_info(),
+            &stake_treasury.to_account_info(),
+            &stake_vault.to_account_info(),
+            stake_slashed,
+            stake_mint.decimals,
+            &[&[b"stake_vault", mint_key.as_ref(), &[vault_bump]]],
+        )?;
+        agent.staked_amount -= stake_slashed;
+    }
+
+    emit!(AgentSlashed {
+        wallet: agent.wallet,
+        reputation_penalty: agent.public_score(old_reputation - agent.reputation),
+        stake_slashed,
+        slash_count: agent.slash_count,
+        reason,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// When a profile registered or renewed at `from` lapses; 0 (never) while
+/// the registry's `agent_ttl` is 0
+pub fn agent_expiry(stats: &RegistryStats, from: i64) -> i64 {
+    if stats.agent_ttl > 0 {
+        from.saturating_add(stats.agent_ttl)
+    } else {
+        0
+    }
+}
+
+/// Return an agent's registration stake, if any, from the vault to
+/// `owner_token_account`; shared by the deregistration instructions
+pub fn refund_stake<'info>(
+    agent: &AgentProfile,
+    stake_mint: &Option<InterfaceAccount<'info, Mint>>,
+    stake_vault: &Option<UncheckedAccount<'info>>,
+    owner_token_account: &Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &Option<Interface<'info, TokenInterface>>,
+    vault_bump: Option<u8>,
+) -> Result<u64> {
+    let amount = agent.staked_amount;
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    let (Some(stake_mint), Some(stake_vault), Some(owner_token_account), Some(token_program), Some(vault_bump)) =
+        (stake_mint, stake_vault, owner_token_account, token_program, vault_bump)
+    else {
+        return err!(AgentVaultError::StakeAccountsMissing);
+    };
+
+    let mint_key = stake_mint.key();
+    token_transfer_checked(
+        &token_program.to_account_info(),
+        &stake_vault.to_account_info(),
+        &stake_mint.to_account_info(),
+        &owner_token_account.to_account_info(),
+        &stake_vault.to_account_info(),
+        amount,
+        stake_mint.decimals,
+        &[&[b"stake_vault", mint_key.as_ref(), &[vault_bump]]],
+    )?;
+
+    Ok(amount)
+}
+
+/// Mark a deregistering agent's `AgentIndex` entry as a tombstone, so its
+/// id stays taken and enumeration can skip it. Profiles registered before
+/// the index existed have no entry to mark.
+pub fn tombstone_agent_index(agent: &AgentProfile, info: &AccountInfo, now: i64) -> Result<()> {
+    if agent.index.is_none() || info.owner != &crate::ID {
+        return Ok(());
+    }
+
+    let mut entry = AgentIndex::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    if entry.agent == agent.agent_id {
+        entry.deregistered_at = now;
+        entry.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
+
+    Ok(())
+}
+
+/// The registry's `SkillFrequency`, or `None` before
+/// `initialize_skill_frequency` has created it
+pub fn load_skill_frequency(info: &AccountInfo) -> Result<Option<SkillFrequency>> {
+    if info.owner != &crate::ID {
+        return Ok(None);
+    }
+
+    Ok(Some(SkillFrequency::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+/// Count one agent off each of `removed` and onto each of `added` in the
+/// registry's `SkillFrequency`; a no-op while it doesn't exist
+pub fn record_skill_frequency(info: &AccountInfo, removed: &[String], added: &[String]) -> Result<()> {
+    let Some(mut skill_frequency) = load_skill_frequency(info)? else {
+        return Ok(());
+    };
+    for skill in removed {
+        skill_frequency.remove(skill);
+    }
+    for skill in added {
+        skill_frequency.add(skill);
+    }
+    skill_frequency.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+/// Scale an endorsement's `weight` inversely to how many agents declare
+/// `skill`: by `RARITY_NEUTRAL_AGENTS / agents`, clamped to
+/// `MIN_RARITY_BPS..=MAX_RARITY_BPS`. Skills the registry doesn't track,
+/// or any skill before `SkillFrequency` exists, keep the plain weight.
+pub fn rarity_weight(weight: u8, skill_frequency: Option<&SkillFrequency>, skill: &str) -> u8 {
+    let agents = skill_frequency.and_then(|f| f.agents(skill)).unwrap_or(0);
+    if agents == 0 {
+        return weight;
+    }
+    let bps = (RARITY_NEUTRAL_AGENTS * BPS_DENOMINATOR as u64 / agents as u64).clamp(MIN_RARITY_BPS, MAX_RARITY_BPS);
+    std::cmp::min(weight as u64 * bps / BPS_DENOMINATOR as u64, u8::MAX as u64) as u8
+}
+
+/// Send the registry's registration fee, if any, from `payer` to the
+/// treasury, less `referral_fee_bps` of it to `referrer` when there is one.
+/// Returns the referrer's cut.
+pub fn charge_registration_fee<'info>(
+    stats: &RegistryStats,
+    treasury: Option<AccountInfo<'info>>,
+    referrer: Option<AccountInfo<'info>>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let fee = stats.registration_fee;
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    let Some(treasury) = treasury else {
+        return err!(AgentVaultError::InvalidTreasury);
+    };
+    require!(payer.lamports() >= fee, AgentVaultError::InsufficientFee);
+
+    let transfer = |to: AccountInfo<'info>, amount: u64| {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to,
+                },
+            ),
+            amount,
+        )
+    };
+
+    let reward = match referrer {
+        Some(referrer) => {
+            let reward = (fee as u128 * stats.referral_fee_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+            if reward > 0 {
+                transfer(referrer, reward)?;
+            }
+            reward
+        }
+        None => 0,
+    };
+    if fee > reward {
+        transfer(treasury, fee - reward)?;
+    }
+
+    Ok(reward)
+}
+
+/// Reputation as a weighted blend of two scores, each normalized to 0-100:
+///
+/// - endorsements: `min(max, base + endorsement_points) * 100 / max`, i.e.
+///   where the agent would sit from its live endorsements' stored weights
+///   alone. With no endorsements this is the registration baseline, not zero.
+/// - reviews: the average star rating mapped linearly from 1..=5 to 0..=100,
+///   `(rating_sum - rating_count) * 100 / (4 * rating_count)`.
+///
+/// The blend is `(e * endorsement_weight_bps + r * review_weight_bps) / 10000`,
+/// scaled back to `0..=max_reputation`. An agent with no reviews uses the
+/// endorsement score alone, so reviews can't drag down a newcomer by absence.
+///
+/// An agent with an imported `external_reputation` then has that blended
+/// in at `external_weight_bps`, with the score above taking the rest.
+///
+/// Last, `reputation_adjustment` is added and the result clamped to
+/// `0..=max_reputation`, so slashes, decay and job credit carry over.
+pub fn blended_reputation(agent: &AgentProfile, stats: &RegistryStats) -> u8 {
+    let max = stats.max_reputation as u64;
+    if max == 0 {
+        return 0;
+    }
+
+    let endorsement_points = std::cmp::min(
+        max,
+        stats.base_reputation as u64 + agent.endorsement_points as u64,
+    );
+    let endorsement_score = endorsement_points * 100 / max;
+
+    let blended = if agent.rating_count == 0 {
+        endorsement_score
+    } else {
+        let count = agent.rating_count as u64;
+        let review_score = agent.rating_sum.saturating_sub(count) * 100 / (4 * count);
+        (endorsement_score * stats.endorsement_weight_bps as u64
+            + std::cmp::min(review_score, 100) * stats.review_weight_bps as u64)
+            / BPS_DENOMINATOR as u64
+    };
+
+    let blended = if agent.external_source.is_empty() {
+        blended
+    } else {
+        let external_weight = stats.external_weight_bps as u64;
+        (blended * (BPS_DENOMINATOR as u64 - external_weight) + agent.external_reputation as u64 * external_weight)
+            / BPS_DENOMINATOR as u64
+    };
+
+    let blended = (blended * max / 100) as i64 + agent.reputation_adjustment as i64;
+    blended.clamp(0, max as i64) as u8
+}
+
+/// An endorsement's `weight` after `age` seconds of exponential decay with
+/// the given half-life, as 16.16 fixed point: full at age 0, half at one
+/// half-life, a quarter at two. Within a half-life the curve is
+/// interpolated linearly, which matches the exponential at each boundary
+/// and stays within 6% of it in between. A non-positive half-life
+/// disables decay.
+pub fn time_decayed_weight(weight: u8, age: i64, half_life: i64) -> u64 {
+    let full = (weight as u64) << 16;
+    if half_life <= 0 || age <= 0 {
+        return full;
+    }
+
+    let halvings = age / half_life;
+    if halvings >= 24 {
+        return 0;
+    }
+    let value = full >> halvings;
+    let rem = (age % half_life) as u64;
+    value - value * rem / (2 * half_life as u64)
+}
+
+/// Whether a job completing at `now` earns its worker reputation: the
+/// payer must be an active registered agent, the worker must have
+/// submitted the work, the job must meet the registry's amount and mint
+/// policy, and the pair must not have earned credit within
+/// `job_credit_interval`
+pub fn job_earns_credit(
+    job: &Job,
+    submitted: bool,
+    payer_profile: Option<&AgentProfile>,
+    credit: &JobCredit,
+    stats: &RegistryStats,
+    now: i64,
+) -> bool {
+    matches!(payer_profile, Some(profile) if profile.status == AgentStatus::Active)
+        && submitted
+        && job.amount >= stats.min_credited_job_amount
+        && (stats.credited_job_mint == Pubkey::default() || job.mint == stats.credited_job_mint)
+        && now.saturating_sub(credit.last_credited_at) >= stats.job_credit_interval()
+}
+
+/// Mark a job completed and, when `credited`, give its worker one
+/// endorsement boost
+pub fn complete_job(
+    job: &mut Job,
+    worker: &mut AgentProfile,
+    history: &mut ReputationHistory,
+    stats: &RegistryStats,
+    credited: bool,
+    timestamp: i64,
+) {
+    job.status = JobStatus::Completed;
+    worker.jobs_completed += 1;
+    if !credited {
+        return;
+    }
+
+    let old_reputation = worker.reputation;
+    adjust_reputation(worker, stats.endorsement_boost as i16, stats);
+    log_reputation_change(
+        history,
+        worker,
+        old_reputation,
+        ReputationReason::JobCompleted,
+        timestamp,
+    );
+}
+
+/// Check an endpoint is empty (none) or fits `MAX_ENDPOINT_LEN` and uses
+/// one of `ENDPOINT_SCHEMES`
+pub fn validate_endpoint(endpoint: &str) -> Result<()> {
+    if endpoint.is_empty() {
+        return Ok(());
+    }
+    require!(endpoint.len() <= MAX_ENDPOINT_LEN, AgentVaultError::EndpointTooLong);
+    require!(
+        ENDPOINT_SCHEMES.iter().any(|scheme| endpoint.starts_with(scheme)),
+        AgentVaultError::InvalidEndpoint
+    );
+
+    Ok(())
+}
+
+/// Canonical protocol names ("json-rpc", "grpc"): trimmed, lowercased,
+/// 1 to `MAX_PROTOCOL_LEN` bytes of [a-z0-9-.], without duplicates
+pub fn normalize_protocols(protocols: Vec<String>) -> Result<Vec<String>> {
+    require!(protocols.len() <= MAX_PROTOCOLS, AgentVaultError::TooManyProtocols);
+
+    let mut normalized: Vec<String> = Vec::with_capacity(protocols.len());
+    for protocol in &protocols {
+        let protocol = protocol.trim().to_lowercase();
+        require!(
+            (1..=MAX_PROTOCOL_LEN).contains(&protocol.len())
+                && protocol
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"-.".contains(&b))
+                && !normalized.contains(&protocol),
+            AgentVaultError::InvalidProtocol
+        );
+        normalized.push(protocol);
+    }
+
+    Ok(normalized)
+}
+
+/// Call `hook_program` about a newly registered profile.
+///
+/// The callee ABI is an Anchor-style instruction `on_agent_registered(profile:
+/// Pubkey)`: data is `REGISTRATION_HOOK_DISCRIMINATOR` followed by the
+/// profile's 32-byte address, and the accounts are the profile (read-only),
+/// the owner (signer, so the hook can charge it, e.g. for minting), then
+/// `remaining[1..]` as passed in. `remaining[0]` must be the hook program
+/// itself. A hook that errors fails the calling instruction.
+pub fn invoke_registration_hook<'info>(
+    hook_program: Pubkey,
+    profile: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    remaining: &[AccountInfo<'info>],
+) -> Result<()> {
+    let [program_info, extra @ ..] = remaining else {
+        return err!(AgentVaultError::InvalidHookProgram);
+    };
+    require!(
+        program_info.key() == hook_program && program_info.executable && hook_program != crate::ID,
+        AgentVaultError::InvalidHookProgram
+    );
+
+    let mut data = REGISTRATION_HOOK_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(profile.key().as_ref());
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(profile.key(), false),
+        AccountMeta::new_readonly(owner.key(), true),
+    ];
+    accounts.extend(extra.iter().map(|info| {
+        if info.is_writable {
+            AccountMeta::new(info.key(), info.is_signer)
+        } else {
+            AccountMeta::new_readonly(info.key(), info.is_signer)
+        }
+    }));
+
+    let mut infos = vec![profile.clone(), owner.clone()];
+    infos.extend(extra.iter().cloned());
+    infos.push(program_info.clone());
+
+    let ix = Instruction {
+        program_id: hook_program,
+        accounts,
+        data,
+    };
+    invoke(&ix, &infos)?;
+
+    Ok(())
+}
+
+/// Ask the registry's `personhood_program` whether `owner` is a verified
+/// person, when the registry requires it.
+///
+/// The verifier ABI is an Anchor-style instruction `verify_personhood(owner:
+/// Pubkey)`: data is `PERSONHOOD_CHECK_DISCRIMINATOR` followed by the
+/// owner's 32-byte address, and the accounts are the credential (read-only,
+/// in whatever form the verifier issues) and the owner (read-only signer).
+/// The verifier returns success for a valid, unrevoked credential held by
+/// the owner and errors otherwise. A failed CPI can't be caught, so a
+/// rejection fails the registration with the verifier's own error; missing
+/// or mismatched accounts fail it with `PersonhoodRequired`.
+pub fn check_personhood<'info>(
+    stats: &RegistryStats,
+    program: Option<AccountInfo<'info>>,
+    credential: Option<AccountInfo<'info>>,
+    owner: &AccountInfo<'info>,
+) -> Result<()> {
+    if !stats.require_personhood {
+        return Ok(());
+    }
+    let (Some(program), Some(credential)) = (program, credential) else {
+        return err!(AgentVaultError::PersonhoodRequired);
+    };
+    require!(
+        program.key() == stats.personhood_program && program.executable,
+        AgentVaultError::PersonhoodRequired
+    );
+
+    let mut data = PERSONHOOD_CHECK_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(owner.key().as_ref());
+
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(credential.key(), false),
+            AccountMeta::new_readonly(owner.key(), true),
+        ],
+        data,
+    };
+    invoke(&ix, &[credential, owner.clone(), program])?;
+
+    Ok(())
+}
+
+/// Check a region is empty (unset) or one of `REGIONS`. Codes are matched
+/// exactly, so clients can compare them byte for byte.
+pub fn validate_region(region: &str) -> Result<()> {
+    require!(
+        region.is_empty() || REGIONS.contains(&region),
+        AgentVaultError::InvalidRegion
+    );
+
+    Ok(())
+}
+
+/// Reject capability bits outside `KNOWN_CAPABILITIES`
+pub fn validate_capabilities(capabilities: u64) -> Result<()> {
+    require!(
+        capabilities & !KNOWN_CAPABILITIES == 0,
+        AgentVaultError::UnknownCapabilityBit
+    );
+
+    Ok(())
+}
+
+/// Check a metadata URI fits within `max_len` bytes and uses one of
+/// `METADATA_URI_SCHEMES`
+pub fn validate_metadata_uri(uri: &str, max_len: usize) -> Result<()> {
+    require!(uri.len() <= max_len, AgentVaultError::MetadataUriTooLong);
+    require!(
+        METADATA_URI_SCHEMES.iter().any(|scheme| uri.starts_with(scheme)),
+        AgentVaultError::InvalidMetadataUri
+    );
+
+    Ok(())
+}
+
+/// Canonical form of an agent name, the seed of its `NameRegistry`: trimmed
+/// and lowercased, so "Bot" and " bot" collide
+pub fn normalize_name(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    // Lowercasing can lengthen some non-ASCII text
+    require!(name.len() <= MAX_NAME_LEN, AgentVaultError::NameTooLong);
+
+    Ok(name)
+}
+
+/// Reserve `name` for `agent_id` by creating its `NameRegistry` PDA at
+/// `registry`, failing with `NameTaken` if another agent holds it
+pub fn claim_name<'info>(
+    name: &str,
+    agent_id: Pubkey,
+    registry: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let canonical = normalize_name(name)?;
+    let bump = pda_bump(registry, &[b"name", canonical.as_bytes()])?;
+    require!(registry.owner != &crate::ID, AgentVaultError::NameTaken);
+
+    create_pda(
+        payer,
+        registry,
+        system_program,
+        &[b"name", canonical.as_bytes(), &[bump]],
+        &NameRegistry { agent: agent_id, bump },
+    )
+}
+
+/// Free `name` if `agent_id` holds it, sending the `NameRegistry` rent to
+/// `destination`. Agents registered before names were reserved may hold
+/// nothing, or share the name with the agent that later claimed it.
+pub fn release_name<'info>(
+    name: &str,
+    agent_id: Pubkey,
+    registry: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let canonical = normalize_name(name)?;
+    pda_bump(registry, &[b"name", canonical.as_bytes()])?;
+    if registry.owner != &crate::ID {
+        return Ok(());
+    }
+
+    let holder = NameRegistry::try_deserialize(&mut &registry.try_borrow_data()?[..])?;
+    if holder.agent == agent_id {
+        close_pda(registry, destination)?;
+    }
+
+    Ok(())
+}
+
+/// Canonical form of a skill: trimmed, lowercased, within the byte limit
+/// and limited to `[a-z0-9-+#.]`. Skills double as PDA seeds, so this is
+/// what keeps "Rust" and " rust" from splitting one skill's endorsements.
+pub fn normalize_skill(skill: &str) -> Result<String> {
+    let skill = skill.trim().to_lowercase();
+    require!(skill.len() <= MAX_SKILL_LEN, AgentVaultError::SkillNameTooLong);
+    require!(
+        !skill.is_empty()
+            && skill
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"-+#.".contains(&b)),
+        AgentVaultError::InvalidSkillFormat
+    );
+
+    Ok(skill)
+}
+
+/// Normalize a full skill list: at most `capacity` entries, each canonical,
+/// and no repeats once normalized
+pub fn normalize_skills(skills: Vec<String>, capacity: usize) -> Result<Vec<String>> {
+    require!(skills.len() <= capacity, AgentVaultError::TooManySkills);
+
+    let normalized = skills
+        .iter()
+        .map(|s| normalize_skill(s))
+        .collect::<Result<Vec<String>>>()?;
+    for (i, skill) in normalized.iter().enumerate() {
+        require!(!normalized[..i].contains(skill), AgentVaultError::DuplicateSkill);
+    }
+
+    Ok(normalized)
+}
+
+/// Canonical form of a domain to verify: trimmed, lowercased and limited
+/// to `[a-z0-9-._/]`, which also admits handles like "github.com/alice"
+pub fn normalize_domain(domain: &str) -> Result<String> {
+    let domain = domain.trim().to_lowercase();
+    require!(
+        !domain.is_empty()
+            && domain.len() <= MAX_DOMAIN_LEN
+            && domain
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"-._/".contains(&b)),
+        AgentVaultError::InvalidDomain
+    );
+
+    Ok(domain)
+}
+
+/// Message the registry's domain verifier signs once it has checked that
+/// `wallet` controls `domain`
+pub fn domain_challenge(domain: &str, wallet: &Pubkey) -> Vec<u8> {
+    format!("agentvault:verify-domain:{}:{}", domain, wallet).into_bytes()
+}
+
+/// Check that `ix` is an ed25519 program instruction verifying exactly
+/// `signature` by `signer` over `message`, all held in its own data. The
+/// ed25519 program fails the whole transaction on a bad signature, so a
+/// matching instruction proves the signature valid.
+pub fn verify_ed25519_signature(
+    ix: &Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<()> {
+    let data = &ix.data;
+    require!(
+        ix.program_id == ed25519_program::ID && data.len() >= 16 && data[0] == 1,
+        AgentVaultError::DomainSignatureInvalid
+    );
+
+    // One offsets record after the 2-byte header: signature, public key and
+    // message offsets, each with the index of the instruction holding it
+    // (u16::MAX for this one)
+    let read = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let field = |offset: usize, len: usize| data.get(offset..offset + len);
+    require!(
+        read(4) == u16::MAX as usize && read(8) == u16::MAX as usize && read(14) == u16::MAX as usize,
+        AgentVaultError::DomainSignatureInvalid
+    );
+    require!(
+        field(read(2), 64) == Some(&signature[..])
+            && field(read(6), 32) == Some(signer.as_ref())
+            && read(12) == message.len()
+            && field(read(10), message.len()) == Some(message),
+        AgentVaultError::DomainSignatureInvalid
+    );
+
+    Ok(())
+}
+
+/// Normalize a category list: categories follow the skill format (see
+/// `normalize_skill`) with a tighter length limit, and no repeats
+pub fn normalize_categories(categories: Vec<String>) -> Result<Vec<String>> {
+    require!(categories.len() <= MAX_CATEGORIES, AgentVaultError::TooManyCategories);
+
+    let mut normalized: Vec<String> = Vec::with_capacity(categories.len());
+    for category in &categories {
+        let category = normalize_skill(category)?;
+        require!(category.len() <= MAX_CATEGORY_LEN, AgentVaultError::CategoryTooLong);
+        require!(!normalized.contains(&category), AgentVaultError::DuplicateCategory);
+        normalized.push(category);
+    }
+
+    Ok(normalized)
+}
+
+/// Set or clear `skill`'s verified flag on `agent` from its endorsement
+/// count, emitting `SkillVerificationChanged` when it flips. Skills no
+/// longer declared have no flag and are left alone.
+pub fn sync_skill_verification(
+    agent: &mut AgentProfile,
+    skill: &str,
+    endorsements: u32,
+    threshold: u32,
+    now: i64,
+) {
+    let Some(index) = agent.skills.iter().position(|s| s == skill) else {
+        return;
+    };
+    let verified = threshold > 0 && endorsements >= threshold;
+    if agent.is_skill_verified(skill) != verified {
+        agent.verified_skills ^= 1 << index;
+        emit!(SkillVerificationChanged {
+            wallet: agent.wallet,
+            skill: skill.to_string(),
+            verified,
+            endorsements,
+            timestamp: now,
+        });
+    }
+}
+
+/// Emit `EndorsementMilestone` when `agent`'s endorsement count has just
+/// reached one of `ENDORSEMENT_MILESTONES`. The count moves one endorsement
+/// at a time, so no endorsement can cross two milestones.
+pub fn emit_endorsement_milestone(agent: &AgentProfile, now: i64) {
+    if ENDORSEMENT_MILESTONES.contains(&agent.endorsements_received) {
+        emit!(EndorsementMilestone {
+            wallet: agent.wallet,
+            milestone: agent.endorsements_received,
+            timestamp: now,
+        });
+    }
+}
+
+/// Undo an endorsement of stored `weight` on its target and the
+/// registry-wide count, taking `penalty` off the target's reputation
+/// (clamped at 0)
+pub fn unwind_endorsement(
+    weight: u8,
+    penalty: u8,
+    target: &mut AgentProfile,
+    history: &mut ReputationHistory,
+    stats: &mut RegistryStats,
+    reason: ReputationReason,
+    timestamp: i64,
+) {
+    target.endorsements_received = target.endorsements_received.saturating_sub(1);
+
+    let old_reputation = target.reputation;
+    take_endorsement_weight(target, weight, penalty, stats);
+    log_reputation_change(history, target, old_reputation, reason, timestamp);
+
+    // Endorsements created before the counter was maintained were never
+    // counted, so don't underflow
+    stats.total_endorsements = stats.total_endorsements.saturating_sub(1);
+}
+
+/// Shared body of `expire_endorsement` and `slash_endorsement`: unwind the
+/// endorsement from its target and counters before it closes
+pub fn remove_endorsement(accounts: &mut ExpireEndorsement, reason: ReputationReason, timestamp: i64) -> Result<()> {
+    let endorsement = &accounts.endorsement;
+    let target = &mut accounts.target_agent;
+    let stats = &mut accounts.registry_stats;
+    // Remove exactly the boost this endorsement applied
+    unwind_endorsement(
+        endorsement.weight,
+        endorsement.weight,
+        target,
+        &mut accounts.reputation_history,
+        stats,
+        reason,
+        timestamp,
+    );
+    update_leaderboard(&accounts.leaderboard, target.agent_id, target.reputation)?;
+
+    let skill_count = &mut accounts.skill_count;
+    skill_count.count = skill_count.count.saturating_sub(1);
+    sync_skill_verification(
+        target,
+        &endorsement.skill,
+        skill_count.count,
+        stats.skill_verification_threshold,
+        timestamp,
+    );
+
+    let skill_reputation = &mut accounts.skill_reputation;
+    skill_reputation.score = skill_reputation.score.saturating_sub(endorsement.skill_weight);
+    update_skill_leaderboard(&accounts.skill_leaderboard, target.agent_id, skill_reputation.score)?;
+
+    // The caller isn't the endorser and can't be asked to pay for a
+    // missing pair counter, so only an existing one is updated
+    let pair_info = accounts.pair_count.to_account_info();
+    if pair_info.owner == &crate::ID {
+        let mut pair_count = PairEndorsementCount::try_deserialize(&mut &pair_info.try_borrow_data()?[..])?;
+        remove_pair_endorsement(&mut pair_count, target);
+        pair_count.try_serialize(&mut &mut pair_info.try_borrow_mut_data()?[..])?;
+    }
+
+    // Likewise for the endorser's index
+    let index_info = accounts.endorser_index.to_account_info();
+    if index_info.owner == &crate::ID {
+        let mut endorser_index = EndorserIndex::try_deserialize(&mut &index_info.try_borrow_data()?[..])?;
+        endorser_index.remove(endorsement.target, &endorsement.skill);
+        endorser_index.try_serialize(&mut &mut index_info.try_borrow_mut_data()?[..])?;
+    }
+
+    Ok(())
+}
+
+/// Move the registry's `endorsement_deposit` from `endorser` onto a new
+/// endorsement account, on top of its rent. Closing the endorsement returns
+/// it with the rent. Returns the amount locked.
+pub fn lock_endorsement_deposit<'info>(
+    stats: &RegistryStats,
+    endorser: &AccountInfo<'info>,
+    endorsement: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let deposit = stats.endorsement_deposit;
+    if deposit == 0 {
+        return Ok(0);
+    }
+    require!(endorser.lamports() >= deposit, AgentVaultError::InsufficientDeposit);
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: endorser.clone(),
+                to: endorsement.clone(),
+            },
+        ),
+        deposit,
+    )?;
+
+    Ok(deposit)
+}
+
+/// Validate and apply a registry parameter change. Direct setters pass
+/// `queued = false` and are refused while a timelock is set; `execute_action`
+/// passes `true` once the delay has run.
+pub fn apply_timelocked_action(stats: &mut RegistryStats, action: TimelockedAction, queued: bool) -> Result<()> {
+    require!(queued || stats.timelock_delay == 0, AgentVaultError::TimelockRequired);
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    match action {
+        TimelockedAction::ReputationParams {
+            base_reputation,
+            endorsement_boost,
+            max_reputation,
+        } => {
+            require!(base_reputation <= max_reputation, AgentVaultError::InvalidParameter);
+
+            stats.base_reputation = base_reputation;
+            stats.endorsement_boost = endorsement_boost;
+            stats.max_reputation = max_reputation;
+
+            emit!(ReputationParamsUpdated {
+                base_reputation,
+                endorsement_boost,
+                max_reputation,
+                timestamp,
+            });
+        }
+        TimelockedAction::ReputationWeights {
+            endorsement_weight_bps,
+            review_weight_bps,
+        } => {
+            require!(
+                endorsement_weight_bps as u32 + review_weight_bps as u32 == BPS_DENOMINATOR as u32,
+                AgentVaultError::InvalidParameter
+            );
+
+            stats.endorsement_weight_bps = endorsement_weight_bps;
+            stats.review_weight_bps = review_weight_bps;
+
+            emit!(ReputationWeightsUpdated {
+                endorsement_weight_bps,
+                review_weight_bps,
+                timestamp,
+            });
+        }
+        TimelockedAction::FeeConfig {
+            registration_fee,
+            treasury,
+        } => {
+            require!(
+                registration_fee == 0 || treasury != Pubkey::default(),
+                AgentVaultError::InvalidParameter
+            );
+
+            stats.registration_fee = registration_fee;
+            stats.treasury = treasury;
+
+            emit!(FeeConfigUpdated {
+                registration_fee,
+                treasury,
+                timestamp,
+            });
+        }
+        TimelockedAction::ReferralFeeBps { referral_fee_bps } => {
+            require!(referral_fee_bps <= BPS_DENOMINATOR, AgentVaultError::InvalidParameter);
+
+            stats.referral_fee_bps = referral_fee_bps;
+
+            emit!(ReferralFeeUpdated {
+                referral_fee_bps,
+                timestamp,
+            });
+        }
+        TimelockedAction::TimelockDelay { seconds } => {
+            require!(seconds >= 0, AgentVaultError::InvalidParameter);
+
+            stats.timelock_delay = seconds;
+
+            emit!(TimelockDelayUpdated {
+                timelock_delay: seconds,
+                timestamp,
+            });
+        }
+        TimelockedAction::ExternalReputationWeight { external_weight_bps } => {
+            require!(external_weight_bps <= BPS_DENOMINATOR, AgentVaultError::InvalidParameter);
+
+            stats.external_weight_bps = external_weight_bps;
+
+            emit!(ExternalReputationWeightUpdated {
+                external_weight_bps,
+                timestamp,
+            });
+        }
+        TimelockedAction::ReputationAlgorithm { algorithm } => {
+            stats.algorithm = algorithm;
+
+            emit!(ReputationAlgorithmUpdated { algorithm, timestamp });
+        }
+        TimelockedAction::EndorsementTtl { seconds } => {
+            require!(seconds > 0, AgentVaultError::InvalidParameter);
+
+            stats.endorsement_ttl = seconds;
+
+            emit!(EndorsementTtlUpdated {
+                endorsement_ttl: seconds,
+                timestamp,
+            });
+        }
+        TimelockedAction::Limits {
+            max_skills,
+            max_name_len,
+            max_uri_len,
+        } => {
+            require!(
+                (1..=MAX_SKILL_CAPACITY).contains(&max_skills)
+                    && (1..=MAX_NAME_LEN).contains(&(max_name_len as usize))
+                    && (1..=MAX_METADATA_URI_LEN).contains(&(max_uri_len as usize)),
+                AgentVaultError::InvalidParameter
+            );
+
+            stats.max_skills = max_skills;
+            stats.max_name_len = max_name_len;
+            stats.max_uri_len = max_uri_len;
+
+            emit!(LimitsUpdated {
+                max_skills,
+                max_name_len,
+                max_uri_len,
+                timestamp,
+            });
+        }
+        TimelockedAction::EndorsementDeposit { lamports } => {
+            stats.endorsement_deposit = lamports;
+
+            emit!(EndorsementDepositUpdated {
+                endorsement_deposit: lamports,
+                timestamp,
+            });
+        }
+        TimelockedAction::VerifiedFloor { verified_floor } => {
+            require!(verified_floor <= stats.max_reputation, AgentVaultError::InvalidParameter);
+
+            stats.verified_floor = verified_floor;
+
+            emit!(VerifiedFloorUpdated {
+                verified_floor,
+                timestamp,
+            });
+        }
+        TimelockedAction::MinEndorserReputation { reputation } => {
+            require!(reputation <= stats.max_reputation, AgentVaultError::InvalidParameter);
+
+            stats.min_endorser_reputation = reputation;
+
+            emit!(MinEndorserReputationUpdated {
+                min_endorser_reputation: reputation,
+                timestamp,
+            });
+        }
+        TimelockedAction::AgentTtl { seconds } => {
+            require!(seconds >= 0, AgentVaultError::InvalidParameter);
+
+            stats.agent_ttl = seconds;
+
+            emit!(AgentTtlUpdated {
+                agent_ttl: seconds,
+                timestamp,
+            });
+        }
+        TimelockedAction::DecayFraction { decay_fraction_bps } => {
+            require!(
+                decay_fraction_bps > 0 && decay_fraction_bps <= BPS_DENOMINATOR,
+                AgentVaultError::InvalidParameter
+            );
+
+            stats.decay_fraction_bps = decay_fraction_bps;
+
+            emit!(DecayFractionUpdated {
+                decay_fraction_bps,
+                timestamp,
+            });
+        }
+        TimelockedAction::DecayFloor { decay_floor } => {
+            require!(decay_floor <= stats.max_reputation, AgentVaultError::InvalidParameter);
+
+            stats.decay_floor = decay_floor;
+
+            emit!(DecayFloorUpdated {
+                decay_floor,
+                timestamp,
+            });
+        }
+        TimelockedAction::StakeConfig { .. }
+        | TimelockedAction::TreasuryWithdrawal { .. }
+        | TimelockedAction::Slash { .. }
+        | TimelockedAction::StakeTreasuryWithdrawal { .. } => {
+            return err!(AgentVaultError::ActionNotExecutable);
+        }
+    }
+
+    Ok(())
+}
+
+/// The timelock for actions that need accounts `execute_action` doesn't
+/// take, checked by their own instructions. While a timelock is set,
+/// `queued` must hold `action`, queued by the authority and past its delay;
+/// it is then closed to `destination`. Without a timelock it is ignored.
+pub fn consume_queued_action<'info>(
+    stats: &RegistryStats,
+    queued: &Option<Account<'info, QueuedAction>>,
+    action: &TimelockedAction,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    if stats.timelock_delay == 0 {
+        return Ok(());
+    }
+
+    let Some(queued) = queued else {
+        return err!(AgentVaultError::TimelockRequired);
+    };
+    let action_hash = action.hash();
+    // Only `queue_action` creates these, seeded on the hash it records
+    require!(queued.action_hash == action_hash, AgentVaultError::TimelockRequired);
+    let timestamp = Clock::get()?.unix_timestamp;
+    require!(timestamp >= queued.execute_after, AgentVaultError::TimelockNotElapsed);
+
+    close_pda(&queued.to_account_info(), destination)?;
+
+    emit!(ActionExecuted { action_hash, timestamp });
+
+    Ok(())
+}
+
+/// Count a new live endorsement from the pair's endorser, which becomes one
+/// of the target's `distinct_endorsers` if it had none left
+pub fn add_pair_endorsement(pair_count: &mut PairEndorsementCount, target: &mut AgentProfile) {
+    if pair_count.count == 0 {
+        target.distinct_endorsers += 1;
+    }
+    pair_count.count += 1;
+}
+
+/// Undo `add_pair_endorsement` when one of the pair's endorsements goes.
+/// Endorsements from before pair counters were never counted, so an empty
+/// counter is left alone.
+pub fn remove_pair_endorsement(pair_count: &mut PairEndorsementCount, target: &mut AgentProfile) {
+    if pair_count.count == 1 {
+        target.distinct_endorsers = target.distinct_endorsers.saturating_sub(1);
+    }
+    pair_count.count = pair_count.count.saturating_sub(1);
+}
+
+/// Check that `account` is the program PDA for `seeds` and return its bump
+pub fn pda_bump(account: &AccountInfo, seeds: &[&[u8]]) -> Result<u8> {
+    let (address, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(account.key(), address, ErrorCode::ConstraintSeeds);
+    Ok(bump)
+}
+
+/// Create the program-owned PDA `account` (`seeds` include the bump) and
+/// write `value` into it. This is Anchor's `init` for PDAs that arrive
+/// through `remaining_accounts`, including its handling of an address
+/// someone has already sent lamports to.
+pub fn create_pda<'info, T: AccountSerialize + anchor_lang::Space>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    value: &T,
+) -> Result<()> {
+    let space = 8 + T::INIT_SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+    let funded = account.lamports();
+
+    if funded == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        if rent > funded {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: account.clone(),
+                    },
+                ),
+                rent - funded,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: account.clone(),
+                },
+                &[seeds],
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: account.clone(),
+                },
+                &[seeds],
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    value.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+}
+
+/// Layout version of a stored `AgentProfile` and the bytes its fields take
+/// up under that layout, discriminator included. Each version only appended
+/// fields, so this walks the fields the version had, in order. Profiles from
+/// before versioning have no marker and read as version 0 as long as the
+/// byte after `report_count` is zero.
+pub fn profile_layout(data: &[u8]) -> Result<(u8, usize)> {
+    fn skip<T: AnchorDeserialize>(buf: &mut &[u8]) -> Result<()> {
+        T::deserialize(buf).map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        Ok(())
+    }
+
+    require!(
+        data.starts_with(&AgentProfile::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let buf = &mut &data[8..];
+
+    // `wallet` through `report_count`, the layout before versioning
+    skip::<(Pubkey, String, String, Vec<String>, u8, u32, i64, i64, u8, i64)>(buf)?;
+    skip::<(Pubkey, AgentStatus, u8, i64, u16, Pubkey, u64, u32, u64, u32, u32)>(buf)?;
+    let version = buf.first().copied().unwrap_or(0);
+
+    for added in 1..=version.min(PROFILE_VERSION) {
+        match added {
+            1 => skip::<u8>(buf)?,                     // version
+            2 => skip::<u8>(buf)?,                     // skill_capacity
+            3 => skip::<i64>(buf)?,                    // last_heartbeat_at
+            4 => skip::<Vec<String>>(buf)?,            // categories
+            5 => skip::<Vec<Attribute>>(buf)?,         // attributes
+            6 => skip::<Vec<String>>(buf)?,            // verified_domains
+            7 => skip::<i64>(buf)?,                    // expires_at
+            8 => skip::<u32>(buf)?,                    // referral_count
+            9 => skip::<u32>(buf)?,                    // verified_skills
+            10 => skip::<String>(buf)?,                // bio
+            11 => skip::<[u8; 32]>(buf)?,              // avatar_hash
+            12 => skip::<bool>(buf)?,                  // reputation_token_minted
+            13 => skip::<Option<u64>>(buf)?,           // index
+            14 => skip::<u8>(buf)?,                    // decayed_amount
+            15 => skip::<u32>(buf)?,                   // distinct_endorsers
+            16 => skip::<u64>(buf)?,                   // capabilities
+            17 => skip::<(String, Vec<String>)>(buf)?, // endpoint, protocols
+            18 => skip::<(u32, u32)>(buf)?,            // jobs_completed, jobs_failed
+            19 => skip::<(Vec<Pubkey>, u8)>(buf)?,     // guardians, guardian_threshold
+            20 => skip::<i64>(buf)?,                   // away_until
+            21 => skip::<(u8, String)>(buf)?,          // external_reputation, external_source
+            22 => skip::<i64>(buf)?,                   // reactivate_before
+            23 => skip::<Option<Pubkey>>(buf)?,        // hook_program
+            24 => skip::<(u32, u32)>(buf)?,            // current_streak, longest_streak
+            25 => skip::<(u16, i64)>(buf)?,            // endorsement_credits, credits_updated_at
+            26 => skip::<bool>(buf)?,                  // private_reputation
+            27 => skip::<String>(buf)?,                // region
+            _ => skip::<(u32, i16)>(buf)?,             // endorsement_points, reputation_adjustment
+        }
+    }
+
+    Ok((version, data.len() - buf.len()))
+}
+
+/// Realloc a program-owned account up to `space` bytes (no-op if it's
+/// already that large), with `payer` topping it up to rent exemption
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if account.data_len() >= space {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(space);
+    if rent > account.lamports() {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent - account.lamports(),
+        )?;
+    }
+    account.realloc(space, true)?;
+
+    Ok(())
+}
+
+/// Close a program-owned account, sending its rent to `destination`. This is
+/// Anchor's `close` for accounts that arrive through `remaining_accounts`.
+pub fn close_pda<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? += lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+/// Move `profile`'s reputation by `delta`, clamped to
+/// `0..=stats.max_reputation`, and return the change actually applied.
+/// Every reputation update goes through here so none can wrap; the
+/// recomputes apply the difference to the score they derive.
+///
+/// Decreases also stop at the profile's `reputation_floor`, so revocations,
+/// slashes, expiries and decay can't push a verified agent below it. An
+/// agent already under the floor isn't lifted, only held where it is.
+pub fn apply_reputation_delta(profile: &mut AgentProfile, delta: i16, stats: &RegistryStats) -> i16 {
+    let old = profile.reputation as i16;
+    let max = stats.max_reputation as i16;
+    let floor = (stats.reputation_floor(profile) as i16).min(old).min(max);
+    let new = old.saturating_add(delta).clamp(floor, max);
+    profile.reputation = new as u8;
+
+    new - old
+}
+
+/// `apply_reputation_delta` for a move that isn't an endorsement's weight:
+/// a slash, decay, recovery or job credit. What was applied is added to
+/// `reputation_adjustment`, so `blended_reputation` keeps it.
+pub fn adjust_reputation(profile: &mut AgentProfile, delta: i16, stats: &RegistryStats) -> i16 {
+    let applied = apply_reputation_delta(profile, delta, stats);
+    profile.reputation_adjustment = profile.reputation_adjustment.saturating_add(applied);
+
+    applied
+}
+
+/// Take a removed endorsement's stored `weight` out of `endorsement_points`
+/// and `penalty` off the reputation. Where the two differ (a penalty mode
+/// other than the stored weight, or a clamp), the difference goes to
+/// `reputation_adjustment`.
+pub fn take_endorsement_weight(target: &mut AgentProfile, weight: u8, penalty: u8, stats: &RegistryStats) {
+    target.endorsement_points = target.endorsement_points.saturating_sub(weight as u32);
+    let applied = apply_reputation_delta(target, -(penalty as i16), stats);
+    target.reputation_adjustment = target.reputation_adjustment.saturating_add(applied + weight as i16);
+}
+
+/// Give back part of what inactivity decay took from an agent that is
+/// active again: `recovery_rate_bps` of the outstanding `decayed_amount`,
+/// at least one point. Never restores more than was lost; points that would
+/// pass `max_reputation` are forfeited.
+pub fn recover_reputation(
+    agent: &mut AgentProfile,
+    stats: &RegistryStats,
+    history: &mut ReputationHistory,
+    now: i64,
+) {
+    if agent.decayed_amount == 0 || stats.recovery_rate_bps == 0 {
+        return;
+    }
+
+    let share = agent.decayed_amount as u32 * stats.recovery_rate_bps as u32 / BPS_DENOMINATOR as u32;
+    let share = share.max(1) as u8;
+    let old_reputation = agent.reputation;
+    adjust_reputation(agent, share as i16, stats);
+    agent.decayed_amount -= share;
+
+    if agent.reputation != old_reputation {
+        log_reputation_change(
+            history,
+            agent,
+            old_reputation,
+            ReputationReason::Recovered,
+            now,
+        );
+    }
+}
+
+/// Re-rank `agent` on the leaderboard, if the caller supplied it
+pub fn update_leaderboard(
+    leaderboard: &Option<AccountLoader<Leaderboard>>,
+    agent: Pubkey,
+    reputation: u8,
+) -> Result<()> {
+    if let Some(leaderboard) = leaderboard {
+        leaderboard.load_mut()?.update(agent, reputation);
+    }
+
+    Ok(())
+}
+
+/// Drop `agent` from the leaderboard, if the caller supplied it
+pub fn remove_from_leaderboard(leaderboard: &Option<AccountLoader<Leaderboard>>, agent: Pubkey) -> Result<()> {
+    if let Some(leaderboard) = leaderboard {
+        leaderboard.load_mut()?.remove(agent);
+    }
+
+    Ok(())
+}
+
+/// Re-rank `agent` on a skill's leaderboard, if the caller supplied it
+pub fn update_skill_leaderboard(
+    leaderboard: &Option<AccountLoader<SkillLeaderboard>>,
+    agent: Pubkey,
+    score: u8,
+) -> Result<()> {
+    if let Some(leaderboard) = leaderboard {
+        leaderboard.load_mut()?.update(agent, score);
+    }
+
+    Ok(())
+}
+
+/// Re-rank `agent` on `skill`'s leaderboard passed through
+/// `remaining_accounts`; a board that doesn't exist yet is skipped
+pub fn update_skill_leaderboard_info<'info>(
+    info: &'info AccountInfo<'info>,
+    skill: &str,
+    agent: Pubkey,
+    score: u8,
+) -> Result<()> {
+    pda_bump(info, &[b"skill_leaderboard", skill.as_bytes()])?;
+    if info.owner == &crate::ID {
+        AccountLoader::<SkillLeaderboard>::try_from(info)?.load_mut()?.update(agent, score);
+    }
+
+    Ok(())
+}
+
+/// Record the change from `old_reputation` to `agent`'s current reputation
+/// in its history and emit `ReputationChanged`. Every path that mutates
+/// reputation goes through here. Agents with `private_reputation` get the
+/// scores zeroed in the event.
+pub fn log_reputation_change(
+    history: &mut ReputationHistory,
+    agent: &AgentProfile,
+    old_reputation: u8,
+    reason: ReputationReason,
+    timestamp: i64,
+) {
+    history.record(timestamp, agent.reputation, reason);
+
+    let (old_reputation, new_reputation) = agent.public_reputation(old_reputation, agent.reputation);
+    emit!(ReputationChanged {
+        wallet: agent.wallet,
+        old_reputation,
+        new_reputation,
+        reason,
+        timestamp,
+    });
+}
+
+// ============================================================================
+// SPL Token
+// ============================================================================
+//
+// Thin wrappers over `anchor_spl::token_interface`, which speaks to both the
+// SPL Token and Token-2022 programs; callers pass the program owning the mint.
+
+/// CPI to `TransferChecked`. Pass `signer_seeds` when `authority` is a PDA.
+pub fn token_transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TransferChecked {
+                from: from.clone(),
+                mint: mint.clone(),
+                to: to.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        decimals,
+    )
+}
+
+/// CPI to `CloseAccount`, sending the (empty) token account's rent to
+/// `destination`
+pub fn token_close_account<'info>(
+    token_program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: account.clone(),
+            destination: destination.clone(),
+            authority: authority.clone(),
+        },
+        signer_seeds,
+    ))
+}
+
+/// Create a token account for `mint` at the PDA `account`, owned by
+/// `authority`. The token program reports the size, so Token-2022 mints
+/// whose extensions need room on every account work too.
+pub fn create_token_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    account_seeds: &[&[u8]],
+) -> Result<()> {
+    let space = token_interface::get_account_data_size(
+        CpiContext::new(token_program.clone(), GetAccountDataSize { mint: mint.clone() }),
+        &[],
+    )?;
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+            &[account_seeds],
+        ),
+        Rent::get()?.minimum_balance(space as usize),
+        space,
+        token_program.key,
+    )?;
+
+    token_interface::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        InitializeAccount3 {
+            account: account.clone(),
+            mint: mint.clone(),
+            authority: authority.clone(),
+        },
+    ))
+}
+
+/// Balance of the token account at `info`
+pub fn token_amount(info: &AccountInfo) -> Result<u64> {
+    Ok(TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?.amount)
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RegistryStats::INIT_SPACE,
+        seeds = [b"registry_stats"],
+        bump
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(skill: String)]
+pub struct InitializeSkillLeaderboard<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = SkillLeaderboard::SPACE,
+        seeds = [b"skill_leaderboard", skill.as_bytes()],
+        bump
+    )]
+    pub skill_leaderboard: AccountLoader<'info, SkillLeaderboard>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSkillFrequency<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SkillFrequency::INIT_SPACE,
+        seeds = [b"skill_frequency"],
+        bump
+    )]
+    pub skill_frequency: Account<'info, SkillFrequency>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLeaderboard<'info> {
+    #[account(zero)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+/// Anchor creates `init` accounts before checking any other constraint,
+/// so a repeat registration would fail inside the system program with an
+/// opaque "already in use". A nested struct's constraints run first, which
+/// lets this one fail it with `AgentAlreadyRegistered` instead.
+///
+/// A profile keeps the address its first wallet registered at, so a wallet
+/// that transferred its profile away (or lost it to a recovery) can't
+/// register again: the address is still taken. That fails with
+/// `ProfileAddressTransferred` rather than claiming the wallet has a profile.
+#[derive(Accounts)]
+pub struct RegistrationGuard<'info> {
+    /// CHECK: the wallet's `OwnerIndex` address, which exists while the
+    /// wallet owns a profile, including one handed over by
+    /// `transfer_ownership` or a recovery
+    #[account(
+        seeds = [b"owner", owner.key().as_ref()],
+        bump,
+        constraint = existing_owner_index.data_is_empty() @ AgentVaultError::AgentAlreadyRegistered
+    )]
+    pub existing_owner_index: UncheckedAccount<'info>,
+    
+    /// CHECK: the wallet's profile address, which must not exist yet. Checked
+    /// after the `OwnerIndex`, so a profile here belongs to another wallet.
+    #[account(
+        seeds = [b"agent", owner.key().as_ref()],
+        bump,
+        constraint = existing_profile.data_is_empty() @ AgentVaultError::ProfileAddressTransferred
+    )]
+    pub existing_profile: UncheckedAccount<'info>,
+    
+    /// CHECK: the wallet's `DeregisteredAgent` address, which exists if it
+    /// registered and deregistered before
+    #[account(
+        seeds = [b"deregistered", owner.key().as_ref()],
+        bump,
+        constraint = existing_deregistration.data_is_empty() @ AgentVaultError::AgentIdDeregistered
+    )]
+    pub existing_deregistration: UncheckedAccount<'info>,
+    
+    /// CHECK: the registering wallet, matched against `RegisterAgent::owner`
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct RegisterAgent<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + AgentProfile::INIT_SPACE,
+        seeds = [b"agent", owner.key().as_ref()],
+        bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + OwnerIndex::INIT_SPACE,
+        seeds = [b"owner", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ReputationHistory::INIT_SPACE,
+        seeds = [b"reputation_history", owner.key().as_ref()],
+        bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// CHECK: the owner's `BlacklistEntry` address, which normally doesn't
+    /// exist. Anchor can't require an account to be absent, so the seeds pin
+    /// the address (callers can't swap or omit it) and `initialize_agent`
+    /// rejects the registration if the program owns it.
+    #[account(seeds = [b"blacklist", owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    /// Rejects a wallet that already has a profile before the `init`
+    /// accounts above are created
+    pub guard: RegistrationGuard<'info>,
+    
+    /// CHECK: the registry's `SkillFrequency` address, updated by
+    /// `record_skill_frequency` once it exists
+    #[account(mut, seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: UncheckedAccount<'info>,
+    
+    /// CHECK: the `NameRegistry` PDA of the normalized name, validated and
+    /// created by `initialize_agent`
+    #[account(mut)]
+    pub name_registry: UncheckedAccount<'info>,
+    
+    /// CHECK: receives the registration fee; only needed while the fee is
+    /// non-zero
+    #[account(mut, address = registry_stats.treasury @ AgentVaultError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + AgentIndex::INIT_SPACE,
+        seeds = [b"agent_idx", registry_stats.next_agent_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_index: Account<'info, AgentIndex>,
+    
+    #[account(mut, constraint = owner.key() == guard.owner.key() @ AgentVaultError::Unauthorized)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    // The remaining accounts are only needed with a `referrer`
+    
+    #[account(mut)]
+    pub referrer_profile: Option<Account<'info, AgentProfile>>,
+    
+    /// CHECK: the referrer's wallet, paid its cut of the fee; matched
+    /// against `referrer_profile` by `initialize_agent`
+    #[account(mut)]
+    pub referrer_wallet: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: the new agent's `Referral` PDA, validated and created by
+    /// `initialize_agent`
+    #[account(mut)]
+    pub referral: Option<UncheckedAccount<'info>>,
+    
+    // The remaining accounts are only needed while the registry requires
+    // proof of personhood
+    
+    /// CHECK: the registry's `personhood_program`, matched and called by
+    /// `check_personhood`
+    pub personhood_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: the owner's credential, only interpreted by the verifier
+    pub personhood_credential: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAgentWithStake<'info> {
+    pub register: RegisterAgent<'info>,
+    
+    #[account(address = register.registry_stats.stake_mint @ AgentVaultError::InvalidStakeMint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: the registry's token vault for `stake_mint`, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"stake_vault", stake_mint.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == stake_mint.key() @ AgentVaultError::InvalidStakeMint
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAgentsBatch<'info> {
+    /// CHECK: the registry's `SkillFrequency` address, updated by
+    /// `record_skill_frequency` once it exists
+    #[account(mut, seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: UncheckedAccount<'info>,
+    
+    /// CHECK: receives the registration fees; only needed while the fee is
+    /// non-zero
+    #[account(mut, address = registry_stats.treasury @ AgentVaultError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterAgent<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"owner", owner.key().as_ref()],
+        bump = owner_index.bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Bars the `agent_id` from registering again
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + DeregisteredAgent::INIT_SPACE,
+        seeds = [b"deregistered", agent_profile.agent_id.as_ref()],
+        bump
+    )]
+    pub deregistered_agent: Account<'info, DeregisteredAgent>,
+    
+    /// CHECK: the `NameRegistry` PDA of the agent's normalized name,
+    /// validated and closed by `release_name`
+    #[account(mut)]
+    pub name_registry: UncheckedAccount<'info>,
+    
+    /// CHECK: the agent's `AgentIndex` entry, tombstoned by
+    /// `tombstone_agent_index`; profiles from before the index have none
+    #[account(
+        mut,
+        seeds = [b"agent_idx", agent_profile.index.unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_index: UncheckedAccount<'info>,
+    
+    /// CHECK: the registry's `SkillFrequency` address, updated by
+    /// `record_skill_frequency` once it exists
+    #[account(mut, seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// Best-effort: when supplied, the agent is dropped from the leaderboard
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    // The remaining accounts are only needed to return a registration stake
+    
+    #[account(address = agent_profile.stake_mint @ AgentVaultError::InvalidStakeMint)]
+    pub stake_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: the registry's token vault for the staked mint, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"stake_vault", agent_profile.stake_mint.as_ref()],
+        bump
+    )]
+    pub stake_vault: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == agent_profile.stake_mint @ AgentVaultError::InvalidStakeMint
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterExpired<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"owner", owner.key().as_ref()],
+        bump = owner_index.bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Bars the `agent_id` from registering again
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + DeregisteredAgent::INIT_SPACE,
+        seeds = [b"deregistered", agent_profile.agent_id.as_ref()],
+        bump
+    )]
+    pub deregistered_agent: Account<'info, DeregisteredAgent>,
+    
+    /// CHECK: the `NameRegistry` PDA of the agent's normalized name,
+    /// validated and closed by `release_name`
+    #[account(mut)]
+    pub name_registry: UncheckedAccount<'info>,
+    
+    /// CHECK: the agent's `AgentIndex` entry, tombstoned by
+    /// `tombstone_agent_index`; profiles from before the index have none
+    #[account(
+        mut,
+        seeds = [b"agent_idx", agent_profile.index.unwrap_or_default().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_index: UncheckedAccount<'info>,
+    
+    /// CHECK: the registry's `SkillFrequency` address, updated by
+    /// `record_skill_frequency` once it exists
+    #[account(mut, seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// Best-effort: when supplied, the agent is dropped from the leaderboard
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    /// CHECK: the agent's owner, refunded the rent
+    #[account(mut, address = agent_profile.wallet @ AgentVaultError::Unauthorized)]
+    pub owner: UncheckedAccount<'info>,
+    
+    /// Pays for the `DeregisteredAgent` record
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    // The remaining accounts are only needed to return a registration stake
+    
+    #[account(address = agent_profile.stake_mint @ AgentVaultError::InvalidStakeMint)]
+    pub stake_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: the registry's token vault for the staked mint, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"stake_vault", agent_profile.stake_mint.as_ref()],
+        bump
+    )]
+    pub stake_vault: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == agent_profile.stake_mint @ AgentVaultError::InvalidStakeMint,
+        constraint = owner_token_account.owner == agent_profile.wallet @ AgentVaultError::Unauthorized
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct RenewAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// CHECK: receives the renewal fee; only needed while the fee is non-zero
+    #[account(mut, address = registry_stats.treasury @ AgentVaultError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut, constraint = owner.key() == agent_profile.wallet)]
+    pub owner: Signer<'info>,
+    
+    /// CHECK: the registry's `SkillFrequency` address. Only used by
+    /// `add_skill` and `remove_skill`, which require it.
+    #[account(mut, seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyDomain<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub owner: Signer<'info>,
+    
+    /// CHECK: the instructions sysvar, read for the ed25519 verification
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+/// Instructions the owner or its delegate may sign
+#[derive(Accounts)]
+pub struct OperateAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    /// The profile's `Delegate` record; omitted when the owner signs
+    #[account(
+        seeds = [b"delegate", agent_profile.wallet.as_ref()],
+        bump = delegate.bump,
+        constraint = delegate.agent == agent_profile.agent_id @ AgentVaultError::Unauthorized
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(
+        constraint = signer.key() == agent_profile.wallet
+            || delegate.as_ref().is_some_and(|d| d.delegate == signer.key())
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+    
+    /// Only read by `heartbeat`, which restores decayed reputation when it
+    /// is passed
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Box<Account<'info, ReputationHistory>>>,
+    
+    /// CHECK: the registry's `SkillFrequency` address. Only used by
+    /// `update_profile`, which requires it when replacing skills.
+    #[account(mut, seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Delegate::INIT_SPACE,
+        seeds = [b"delegate", owner.key().as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+    
+    #[account(
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"delegate", owner.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintReputationToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"reputation_mint", agent_profile.agent_id.as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        mint::freeze_authority = mint_authority,
+        mint::token_program = token_program
+    )]
+    pub reputation_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// The owner's token account for the reputation mint
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"reputation_token", agent_profile.agent_id.as_ref()],
+        bump,
+        token::mint = reputation_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub reputation_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: mint and freeze authority of every reputation mint; holds no
+    /// data and signs through its seeds
+    #[account(seeds = [b"reputation_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u8)]
+pub struct GrowSkillCapacity<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized,
+        realloc = AgentProfile::space_for(capacity),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RenameAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    /// CHECK: the `NameRegistry` PDA of the current name, validated and
+    /// closed by `release_name`
+    #[account(mut)]
+    pub old_name_registry: UncheckedAccount<'info>,
+    
+    /// CHECK: the `NameRegistry` PDA of the new name, validated and created
+    /// by `claim_name`; the same account as `old_name_registry` when only
+    /// the case changes
+    #[account(mut)]
+    pub new_name_registry: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(skill: String)]
+pub struct EndorseSkill<'info> {
+    #[account(
+        init,
+        payer = endorser,
+        space = 8 + Endorsement::INIT_SPACE,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            target_agent.agent_id.as_ref(),
+            skill.as_bytes()
+        ],
+        bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+    
+    /// `Account` already rejects anything that isn't an initialized
+    /// `AgentProfile` owned by this program
+    #[account(
+        mut,
+        seeds = [b"agent", endorser_profile.agent_id.as_ref()],
+        bump = endorser_profile.bump,
+        constraint = endorser_profile.wallet == endorser.key() @ AgentVaultError::EndorserNotRegistered
+    )]
+    pub endorser_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    /// The target's endorsement of the endorser for the same skill, if any.
+    /// Optional: callers that omit it get the full boost.
+    #[account(
+        mut,
+        seeds = [
+            b"endorsement",
+            target_agent.wallet.as_ref(),
+            endorser_profile.agent_id.as_ref(),
+            skill.as_bytes()
+        ],
+        bump = reverse_endorsement.bump
+    )]
+    pub reverse_endorsement: Option<Account<'info, Endorsement>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + PairEndorsementCount::INIT_SPACE,
+        seeds = [b"pair_endorsements", endorser.key().as_ref(), target_agent.agent_id.as_ref()],
+        bump
+    )]
+    pub pair_count: Box<Account<'info, PairEndorsementCount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + EndorserIndex::INIT_SPACE,
+        seeds = [b"endorser_index", endorser.key().as_ref()],
+        bump
+    )]
+    pub endorser_index: Box<Account<'info, EndorserIndex>>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", target_agent.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + SkillEndorsementCount::INIT_SPACE,
+        seeds = [b"skill_count", target_agent.key().as_ref(), skill.as_bytes()],
+        bump
+    )]
+    pub skill_count: Account<'info, SkillEndorsementCount>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + SkillReputation::INIT_SPACE,
+        seeds = [b"skill_reputation", target_agent.key().as_ref(), skill.as_bytes()],
+        bump
+    )]
+    pub skill_reputation: Box<Account<'info, SkillReputation>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    /// Best-effort, like `leaderboard`, with the new skill reputation
+    #[account(mut, seeds = [b"skill_leaderboard", skill.as_bytes()], bump)]
+    pub skill_leaderboard: Option<AccountLoader<'info, SkillLeaderboard>>,
+    
+    /// CHECK: the registry's `SkillFrequency` address, read by
+    /// `load_skill_frequency`. The seeds pin it, so an endorser can't dodge
+    /// a common skill's lower boost by omitting it.
+    #[account(seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EndorseSkills<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", endorser_profile.agent_id.as_ref()],
+        bump = endorser_profile.bump,
+        constraint = endorser_profile.wallet == endorser.key() @ AgentVaultError::EndorserNotRegistered
+    )]
+    pub endorser_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", target_agent.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + PairEndorsementCount::INIT_SPACE,
+        seeds = [b"pair_endorsements", endorser.key().as_ref(), target_agent.agent_id.as_ref()],
+        bump
+    )]
+    pub pair_count: Box<Account<'info, PairEndorsementCount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + EndorserIndex::INIT_SPACE,
+        seeds = [b"endorser_index", endorser.key().as_ref()],
+        bump
+    )]
+    pub endorser_index: Box<Account<'info, EndorserIndex>>,
+    
+    /// CHECK: the registry's `SkillFrequency` address, read by
+    /// `load_skill_frequency`; pinned as in `EndorseSkill`
+    #[account(seeds = [b"skill_frequency"], bump)]
+    pub skill_frequency: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeEndorsement<'info> {
+    #[account(
+        mut,
+        close = endorser,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            target_agent.agent_id.as_ref(),
+            endorsement.skill.as_bytes()
+        ],
+        bump = endorsement.bump,
+        has_one = endorser
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", target_agent.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + SkillEndorsementCount::INIT_SPACE,
+        seeds = [b"skill_count", target_agent.key().as_ref(), endorsement.skill.as_bytes()],
+        bump
+    )]
+    pub skill_count: Account<'info, SkillEndorsementCount>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + SkillReputation::INIT_SPACE,
+        seeds = [b"skill_reputation", target_agent.key().as_ref(), endorsement.skill.as_bytes()],
+        bump
+    )]
+    pub skill_reputation: Box<Account<'info, SkillReputation>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + PairEndorsementCount::INIT_SPACE,
+        seeds = [b"pair_endorsements", endorser.key().as_ref(), target_agent.agent_id.as_ref()],
+        bump
+    )]
+    pub pair_count: Box<Account<'info, PairEndorsementCount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + EndorserIndex::INIT_SPACE,
+        seeds = [b"endorser_index", endorser.key().as_ref()],
+        bump
+    )]
+    pub endorser_index: Box<Account<'info, EndorserIndex>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    /// Best-effort, like `leaderboard`, with the new skill reputation
+    #[account(mut, seeds = [b"skill_leaderboard", endorsement.skill.as_bytes()], bump)]
+    pub skill_leaderboard: Option<AccountLoader<'info, SkillLeaderboard>>,
+    
+    /// The endorser's profile, read by `RevokePenaltyMode::CurrentReputation`
+    #[account(
+        seeds = [b"agent", endorser_profile.agent_id.as_ref()],
+        bump = endorser_profile.bump,
+        constraint = endorser_profile.wallet == endorser.key() @ AgentVaultError::Unauthorized
+    )]
+    pub endorser_profile: Option<Box<Account<'info, AgentProfile>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(job_id: u64)]
+pub struct CreateJob<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Job::INIT_SPACE,
+        seeds = [b"job", payer.key().as_ref(), &job_id.to_le_bytes()],
+        bump
+    )]
+    pub job: Account<'info, Job>,
+    
+    /// CHECK: the job's token vault, validated by seeds and created here
+    #[account(
+        mut,
+        seeds = [b"job_vault", job.key().as_ref()],
+        bump
+    )]
+    pub job_vault: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"agent", worker_profile.agent_id.as_ref()],
+        bump = worker_profile.bump
+    )]
+    pub worker_profile: Account<'info, AgentProfile>,
+    
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == mint.key() @ AgentVaultError::MintMismatch
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitWork<'info> {
+    #[account(
+        mut,
+        seeds = [b"job", job.payer.as_ref(), &job.job_id.to_le_bytes()],
+        bump = job.bump
+    )]
+    pub job: Account<'info, Job>,
+    
+    #[account(
+        seeds = [b"agent", job.worker.as_ref()],
+        bump = worker_profile.bump,
+        constraint = worker_profile.wallet == worker.key() @ AgentVaultError::NotJobParty
+    )]
+    pub worker_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub worker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"job", job.payer.as_ref(), &job.job_id.to_le_bytes()],
+        bump = job.bump,
+        constraint = job.payer == payer.key() @ AgentVaultError::NotJobParty
+    )]
+    pub job: Account<'info, Job>,
+    
+    /// CHECK: the job's token vault, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"job_vault", job.key().as_ref()],
+        bump = job.vault_bump
+    )]
+    pub job_vault: UncheckedAccount<'info>,
+    
+    #[account(address = job.mint @ AgentVaultError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", job.worker.as_ref()],
+        bump = worker_profile.bump
+    )]
+    pub worker_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        constraint = worker_token_account.mint == job.mint @ AgentVaultError::MintMismatch,
+        constraint = worker_token_account.owner == worker_profile.wallet @ AgentVaultError::NotJobParty
+    )]
+    pub worker_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", job.worker.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// The payer's own profile; jobs from payers without one complete
+    /// without crediting the worker
+    #[account(
+        seeds = [b"agent", payer_profile.agent_id.as_ref()],
+        bump = payer_profile.bump,
+        constraint = payer_profile.wallet == payer.key() @ AgentVaultError::NotJobParty
+    )]
+    pub payer_profile: Option<Box<Account<'info, AgentProfile>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + JobCredit::INIT_SPACE,
+        seeds = [b"job_credit", payer.key().as_ref(), job.worker.as_ref()],
+        bump
+    )]
+    pub job_credit: Box<Account<'info, JobCredit>>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseMilestone<'info> {
+    pub release: ReleasePayment<'info>,
+    
+    /// Receives leftover escrow when the last milestone is released
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == release.job.mint @ AgentVaultError::MintMismatch
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CancelJob<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"job", job.payer.as_ref(), &job.job_id.to_le_bytes()],
+        bump = job.bump,
+        constraint = job.payer == payer.key() @ AgentVaultError::NotJobParty
+    )]
+    pub job: Account<'info, Job>,
+    
+    /// CHECK: the job's token vault, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"job_vault", job.key().as_ref()],
+        bump = job.vault_bump
+    )]
+    pub job_vault: UncheckedAccount<'info>,
+    
+    #[account(address = job.mint @ AgentVaultError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == job.mint @ AgentVaultError::MintMismatch
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitReview<'info> {
+    #[account(
+        init,
+        payer = reviewer,
+        space = 8 + Review::INIT_SPACE,
+        seeds = [b"review", reviewer.key().as_ref(), target_agent.agent_id.as_ref(), job.key().as_ref()],
+        bump
+    )]
+    pub review: Account<'info, Review>,
+    
+    /// The pair's credit record, which names the last job to earn credit;
+    /// reviewing it uses that up
+    #[account(
+        mut,
+        seeds = [b"job_credit", reviewer.key().as_ref(), target_agent.agent_id.as_ref()],
+        bump = job_credit.bump,
+        constraint = job_credit.last_credited_job != Pubkey::default()
+            && job_credit.last_credited_job == job.key() @ AgentVaultError::JobNotCompleted
+    )]
+    pub job_credit: Account<'info, JobCredit>,
+    
+    /// CHECK: the completed (and so closed) job, matched against `job_credit`
+    pub job: UncheckedAccount<'info>,
+    
+    /// The reviewer's own profile; reviews come only from registered agents
+    #[account(
+        seeds = [b"agent", reviewer_profile.agent_id.as_ref()],
+        bump = reviewer_profile.bump,
+        constraint = reviewer_profile.wallet == reviewer.key() @ AgentVaultError::NotJobParty,
+        constraint = reviewer_profile.status == AgentStatus::Active @ AgentVaultError::AgentNotActive
+    )]
+    pub reviewer_profile: Box<Account<'info, AgentProfile>>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"job", job.payer.as_ref(), &job.job_id.to_le_bytes()],
+        bump = job.bump,
+        constraint = job.payer == signer.key() || worker_profile.wallet == signer.key()
+            @ AgentVaultError::NotJobParty
+    )]
+    pub job: Account<'info, Job>,
+    
+    #[account(
+        seeds = [b"agent", job.worker.as_ref()],
+        bump = worker_profile.bump
+    )]
+    pub worker_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"job", job.payer.as_ref(), &job.job_id.to_le_bytes()],
+        bump = job.bump
+    )]
+    pub job: Account<'info, Job>,
+    
+    /// CHECK: the job's token vault, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"job_vault", job.key().as_ref()],
+        bump = job.vault_bump
+    )]
+    pub job_vault: UncheckedAccount<'info>,
+    
+    #[account(address = job.mint @ AgentVaultError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", job.worker.as_ref()],
+        bump = worker_profile.bump
+    )]
+    pub worker_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        constraint = worker_token_account.mint == job.mint @ AgentVaultError::MintMismatch,
+        constraint = worker_token_account.owner == worker_profile.wallet @ AgentVaultError::NotJobParty
+    )]
+    pub worker_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == job.mint @ AgentVaultError::MintMismatch,
+        constraint = payer_token_account.owner == job.payer @ AgentVaultError::NotJobParty
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: receives the vault's rent; must be the job's payer
+    #[account(mut, address = job.payer @ AgentVaultError::NotJobParty)]
+    pub payer: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ImportExternalReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        constraint = registry_stats.reputation_oracle == oracle.key() @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// Best-effort: when supplied, the agent's leaderboard entry is
+    /// refreshed with its current reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    pub oracle: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecomputeReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+}
+
+#[derive(Accounts)]
+pub struct RecomputeTimeWeightedReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct SnapshotReputation<'info> {
+    #[account(
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ReputationSnapshot::INIT_SPACE,
+        seeds = [
+            b"reputation_snapshot",
+            snapshot_id.to_le_bytes().as_ref(),
+            agent_profile.agent_id.as_ref()
+        ],
+        bump
+    )]
+    pub reputation_snapshot: Account<'info, ReputationSnapshot>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(
+        constraint = signer.key() == registry_stats.authority
+            || signer.key() == registry_stats.snapshot_authority
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecomputeBatch<'info> {
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CheckSkillVerification<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"skill_count", agent_profile.key().as_ref(), skill_count.skill.as_bytes()],
+        bump = skill_count.bump
+    )]
+    pub skill_count: Account<'info, SkillEndorsementCount>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+}
+
+#[derive(Accounts)]
+pub struct DecayReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"owner", owner.key().as_ref()],
+        bump = owner_index.bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+    
+    /// Fails to initialize if `new_owner` already owns a profile
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + OwnerIndex::INIT_SPACE,
+        seeds = [b"owner", new_owner.as_ref()],
+        bump
+    )]
+    pub new_owner_index: Account<'info, OwnerIndex>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    /// Fails to initialize while another recovery is pending
+    #[account(
+        init,
+        payer = guardian,
+        space = 8 + Recovery::INIT_SPACE,
+        seeds = [b"recovery", agent_profile.agent_id.as_ref()],
+        bump
+    )]
+    pub recovery: Account<'info, Recovery>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    #[account(
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"recovery", agent_profile.agent_id.as_ref()],
+        bump = recovery.bump
+    )]
+    pub recovery: Account<'info, Recovery>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecoverAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"recovery", agent_profile.agent_id.as_ref()],
+        bump = recovery.bump,
+        has_one = initiator
+    )]
+    pub recovery: Account<'info, Recovery>,
+    
+    #[account(
+        mut,
+        close = old_owner,
+        seeds = [b"owner", agent_profile.wallet.as_ref()],
+        bump = owner_index.bump
+    )]
+    pub owner_index: Account<'info, OwnerIndex>,
+    
+    /// Fails to initialize if the new owner already owns a profile
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + OwnerIndex::INIT_SPACE,
+        seeds = [b"owner", recovery.new_owner.as_ref()],
+        bump
+    )]
+    pub new_owner_index: Account<'info, OwnerIndex>,
+    
+    /// CHECK: the lost wallet, refunded the rent of its owner index
+    #[account(mut, address = agent_profile.wallet)]
+    pub old_owner: UncheckedAccount<'info>,
+    
+    /// CHECK: the guardian who opened the recovery and paid for it
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"recovery", agent_profile.agent_id.as_ref()],
+        bump = recovery.bump,
+        has_one = initiator
+    )]
+    pub recovery: Account<'info, Recovery>,
+    
+    /// CHECK: the guardian who opened the recovery and paid for it
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Reactivate<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == signer.key()
+            || agent_profile.guardians.contains(&signer.key()) @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeDeactivation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// Best-effort: when supplied, the agent is dropped from the leaderboard
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProfile<'info> {
+    /// CHECK: an `AgentProfile` that may not deserialize under the current
+    /// layout; ownership is checked here and the discriminator and seeds by
+    /// `migrate_profile`
+    #[account(mut, owner = crate::ID)]
+    pub agent_profile: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateRegistry<'info> {
+    /// CHECK: the `RegistryStats`, which may not deserialize under the
+    /// current layout; the discriminator is checked by `migrate_registry`
+    #[account(mut, owner = crate::ID, seeds = [b"registry_stats"], bump)]
+    pub registry_stats: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+/// `UpdateRegistry` without the frozen check, so a frozen registry can
+/// still be unfrozen
+#[derive(Accounts)]
+pub struct FreezeRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(action_hash: [u8; 32])]
+pub struct QueueAction<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + QueuedAction::INIT_SPACE,
+        seeds = [b"queued_action", action_hash.as_ref()],
+        bump
+    )]
+    pub queued_action: Account<'info, QueuedAction>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(action: TimelockedAction)]
+pub struct ExecuteAction<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"queued_action", action.hash().as_ref()],
+        bump = queued_action.bump
+    )]
+    pub queued_action: Account<'info, QueuedAction>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAction<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"queued_action", queued_action.action_hash.as_ref()],
+        bump = queued_action.bump
+    )]
+    pub queued_action: Account<'info, QueuedAction>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent_profile.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    /// The signer's `Moderator` record; omitted when the authority signs
+    #[account(seeds = [b"moderator", signer.key().as_ref()], bump = moderator.bump)]
+    pub moderator: Option<Account<'info, Moderator>>,
+    
+    #[account(
+        constraint = signer.key() == registry_stats.authority || moderator.is_some()
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+    
+    /// The queued `TimelockedAction::Slash`, required while a timelock is
+    /// set and closed to the signer
+    #[account(mut)]
+    pub queued_action: Option<Account<'info, QueuedAction>>,
+    
+    // The remaining accounts are only needed to slash stake
+    
+    #[account(address = agent_profile.stake_mint @ AgentVaultError::InvalidStakeMint)]
+    pub stake_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: the registry's token vault for the staked mint, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"stake_vault", agent_profile.stake_mint.as_ref()],
+        bump
+    )]
+    pub stake_vault: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: the registry's stake treasury for the staked mint, a token
+    /// account the program owns, validated by seeds
+    #[account(
+        mut,
+        seeds = [b"stake_treasury", agent_profile.stake_mint.as_ref()],
+        bump
+    )]
+    pub stake_treasury: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct SetStakeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mint::token_program = token_program)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: the registry's token vault for `stake_mint`, validated by seeds
+    /// and created here on first use
+    #[account(
+        mut,
+        seeds = [b"stake_vault", stake_mint.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: UncheckedAccount<'info>,
+    
+    /// CHECK: the registry's treasury for stake slashed in `stake_mint`,
+    /// validated by seeds and created here on first use
+    #[account(
+        mut,
+        seeds = [b"stake_treasury", stake_mint.key().as_ref()],
+        bump
+    )]
+    pub stake_treasury: UncheckedAccount<'info>,
+    
+    /// The queued `TimelockedAction::StakeConfig`, required while a
+    /// timelock is set and closed to the authority
+    #[account(mut)]
+    pub queued_action: Option<Account<'info, QueuedAction>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    /// Program-owned fee treasury: a data-less system account, so the
+    /// program moves its lamports by signing for the PDA
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+    
+    /// CHECK: any wallet the authority chooses to pay out to
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// The queued `TimelockedAction::TreasuryWithdrawal`, required while a
+    /// timelock is set and closed to the authority
+    #[account(mut)]
+    pub queued_action: Option<Account<'info, QueuedAction>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStakeTreasury<'info> {
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: the registry's stake treasury for `stake_mint`, validated by
+    /// seeds
+    #[account(
+        mut,
+        seeds = [b"stake_treasury", stake_mint.key().as_ref()],
+        bump
+    )]
+    pub stake_treasury: UncheckedAccount<'info>,
+    
+    /// Any token account of `stake_mint` the authority chooses to pay out to
+    #[account(
+        mut,
+        constraint = destination.mint == stake_mint.key() @ AgentVaultError::InvalidStakeMint
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// The queued `TimelockedAction::StakeTreasuryWithdrawal`, required
+    /// while a timelock is set and closed to the authority
+    #[account(mut)]
+    pub queued_action: Option<Account<'info, QueuedAction>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        constraint = registry_stats.pending_authority == new_authority.key() @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerification<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// The signer's `Moderator` record; omitted when the authority signs
+    #[account(seeds = [b"moderator", signer.key().as_ref()], bump = moderator.bump)]
+    pub moderator: Option<Account<'info, Moderator>>,
+    
+    #[account(
+        constraint = signer.key() == registry_stats.authority || moderator.is_some()
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddModerator<'info> {
+    #[account(
         init,
         payer = authority,
-        space = 8 + RegistryStats::INIT_SPACE,
+        space = 8 + Moderator::INIT_SPACE,
+        seeds = [b"moderator", wallet.as_ref()],
+        bump
+    )]
+    pub moderator: Account<'info, Moderator>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveModerator<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"moderator", moderator.wallet.as_ref()],
+        bump = moderator.bump
+    )]
+    pub moderator: Account<'info, Moderator>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen,
+        has_one = authority @ AgentVaultError::Unauthorized
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct BlacklistWallet<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + BlacklistEntry::INIT_SPACE,
+        seeds = [b"blacklist", wallet.as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// The signer's `Moderator` record; omitted when the authority signs
+    #[account(seeds = [b"moderator", signer.key().as_ref()], bump = moderator.bump)]
+    pub moderator: Option<Account<'info, Moderator>>,
+    
+    #[account(
+        mut,
+        constraint = signer.key() == registry_stats.authority || moderator.is_some()
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnblacklistWallet<'info> {
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"blacklist", blacklist_entry.wallet.as_ref()],
+        bump = blacklist_entry.bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    /// The signer's `Moderator` record; omitted when the authority signs
+    #[account(seeds = [b"moderator", signer.key().as_ref()], bump = moderator.bump)]
+    pub moderator: Option<Account<'info, Moderator>>,
+    
+    #[account(
+        mut,
+        constraint = signer.key() == registry_stats.authority || moderator.is_some()
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReportAgent<'info> {
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + Report::INIT_SPACE,
+        seeds = [b"report", reporter.key().as_ref(), target_agent.agent_id.as_ref()],
+        bump
+    )]
+    pub report: Account<'info, Report>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveReport<'info> {
+    #[account(
+        mut,
+        close = reporter,
+        seeds = [b"report", report.reporter.as_ref(), report.target.as_ref()],
+        bump = report.bump,
+        constraint = report.target == slash.agent_profile.agent_id @ AgentVaultError::ReportTargetMismatch
+    )]
+    pub report: Account<'info, Report>,
+    
+    /// CHECK: receives the report's rent; must be the reporter
+    #[account(mut, address = report.reporter @ AgentVaultError::Unauthorized)]
+    pub reporter: UncheckedAccount<'info>,
+    
+    /// The reported agent and the moderation signer; the stake accounts are
+    /// only needed when the resolution slashes stake
+    pub slash: SlashAgent<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMany<'info> {
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + EndorserIndex::INIT_SPACE,
+        seeds = [b"endorser_index", endorser.key().as_ref()],
+        bump
+    )]
+    pub endorser_index: Box<Account<'info, EndorserIndex>>,
+    
+    /// The endorser's profile, read by `RevokePenaltyMode::CurrentReputation`
+    #[account(
+        seeds = [b"agent", endorser_profile.agent_id.as_ref()],
+        bump = endorser_profile.bump,
+        constraint = endorser_profile.wallet == endorser.key() @ AgentVaultError::Unauthorized
+    )]
+    pub endorser_profile: Option<Box<Account<'info, AgentProfile>>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with each
+    /// target's new reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAllForTarget<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", target_agent.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + PairEndorsementCount::INIT_SPACE,
+        seeds = [b"pair_endorsements", endorser.key().as_ref(), target_agent.agent_id.as_ref()],
+        bump
+    )]
+    pub pair_count: Box<Account<'info, PairEndorsementCount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = endorser,
+        space = 8 + EndorserIndex::INIT_SPACE,
+        seeds = [b"endorser_index", endorser.key().as_ref()],
+        bump
+    )]
+    pub endorser_index: Box<Account<'info, EndorserIndex>>,
+    
+    /// The endorser's profile, read by `RevokePenaltyMode::CurrentReputation`
+    #[account(
+        seeds = [b"agent", endorser_profile.agent_id.as_ref()],
+        bump = endorser_profile.bump,
+        constraint = endorser_profile.wallet == endorser.key() @ AgentVaultError::Unauthorized
+    )]
+    pub endorser_profile: Option<Box<Account<'info, AgentProfile>>>,
+    
+    #[account(
+        mut,
         seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireEndorsement<'info> {
+    #[account(
+        mut,
+        close = endorser,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            target_agent.agent_id.as_ref(),
+            endorsement.skill.as_bytes()
+        ],
+        bump = endorsement.bump,
+        has_one = endorser
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+    
+    #[account(
+        mut,
+        seeds = [b"agent", target_agent.agent_id.as_ref()],
+        bump = target_agent.bump
+    )]
+    pub target_agent: Account<'info, AgentProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"reputation_history", target_agent.agent_id.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Box<Account<'info, ReputationHistory>>,
+    
+    #[account(
+        mut,
+        seeds = [b"skill_count", target_agent.key().as_ref(), endorsement.skill.as_bytes()],
+        bump = skill_count.bump
+    )]
+    pub skill_count: Account<'info, SkillEndorsementCount>,
+    
+    #[account(
+        mut,
+        seeds = [b"skill_reputation", target_agent.key().as_ref(), endorsement.skill.as_bytes()],
+        bump = skill_reputation.bump
+    )]
+    pub skill_reputation: Box<Account<'info, SkillReputation>>,
+    
+    /// CHECK: the endorser's pair counter for this target; only updated
+    /// when it exists, i.e. when the program owns it
+    #[account(
+        mut,
+        seeds = [b"pair_endorsements", endorser.key().as_ref(), target_agent.agent_id.as_ref()],
         bump
     )]
+    pub pair_count: UncheckedAccount<'info>,
+    
+    /// CHECK: the endorser's `EndorserIndex`; only updated when it exists
+    #[account(mut, seeds = [b"endorser_index", endorser.key().as_ref()], bump)]
+    pub endorser_index: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
     pub registry_stats: Account<'info, RegistryStats>,
     
+    /// Best-effort: when supplied, the leaderboard is updated with the new
+    /// reputation
+    #[account(mut, address = registry_stats.leaderboard @ AgentVaultError::InvalidLeaderboard)]
+    pub leaderboard: Option<AccountLoader<'info, Leaderboard>>,
+    
+    /// Best-effort, like `leaderboard`, with the new skill reputation
+    #[account(mut, seeds = [b"skill_leaderboard", endorsement.skill.as_bytes()], bump)]
+    pub skill_leaderboard: Option<AccountLoader<'info, SkillLeaderboard>>,
+    
+    /// CHECK: the original endorser, refunded the endorsement's rent;
+    /// verified by `has_one` on the endorsement
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub endorser: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestEndorsement<'info> {
+    #[account(
+        seeds = [
+            b"endorsement",
+            endorsement.endorser.as_ref(),
+            endorsement.target.as_ref(),
+            endorsement.skill.as_bytes()
+        ],
+        bump = endorsement.bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+}
+
+#[derive(Accounts)]
+pub struct SlashEndorsement<'info> {
+    /// The endorsement and the accounts it is unwound from, as on expiry
+    pub expire: ExpireEndorsement<'info>,
+    
+    /// The signer's `Moderator` record; omitted when the authority signs
+    #[account(seeds = [b"moderator", signer.key().as_ref()], bump = moderator.bump)]
+    pub moderator: Option<Account<'info, Moderator>>,
+    
+    #[account(
+        constraint = signer.key() == expire.registry_stats.authority || moderator.is_some()
+            @ AgentVaultError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+    
+    /// CHECK: receives the forfeited deposit
+    #[account(mut, address = expire.registry_stats.treasury @ AgentVaultError::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOrphanedEndorsement<'info> {
+    #[account(
+        mut,
+        close = endorser,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            endorsement.target.as_ref(),
+            endorsement.skill.as_bytes()
+        ],
+        bump = endorsement.bump,
+        has_one = endorser
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+    
+    /// CHECK: the target's profile PDA; the endorsement is orphaned when the
+    /// program no longer owns it
+    #[account(seeds = [b"agent", endorsement.target.as_ref()], bump)]
+    pub target_agent: UncheckedAccount<'info>,
+    
+    /// CHECK: the endorser's `EndorserIndex`; only updated when it exists
+    #[account(mut, seeds = [b"endorser_index", endorser.key().as_ref()], bump)]
+    pub endorser_index: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateTeam<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Team::INIT_SPACE,
+        seeds = [b"team", owner.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub team: Account<'info, Team>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
-#[derive(Accounts)]
-#[instruction(name: String)]
-pub struct RegisterAgent<'info> {
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + AgentProfile::INIT_SPACE,
-        seeds = [b"agent", owner.key().as_ref()],
-        bump
-    )]
-    pub agent_profile: Account<'info, AgentProfile>,
-    
-    #[account(
-        mut,
-        seeds = [b"registry_stats"],
-        bump = registry_stats.bump
-    )]
-    pub registry_stats: Account<'info, RegistryStats>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[derive(Accounts)]
+pub struct AddMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"team", team.owner.as_ref(), team.name.as_bytes()],
+        bump = team.bump,
+        has_one = owner @ AgentVaultError::Unauthorized
+    )]
+    pub team: Account<'info, Team>,
+    
+    #[account(
+        seeds = [b"agent", agent_profile.agent_id.as_ref()],
+        bump = agent_profile.bump,
+        constraint = agent_profile.wallet == member_owner.key() @ AgentVaultError::Unauthorized
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub owner: Signer<'info>,
+    
+    pub member_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"team", team.owner.as_ref(), team.name.as_bytes()],
+        bump = team.bump,
+        has_one = owner @ AgentVaultError::Unauthorized
+    )]
+    pub team: Account<'info, Team>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecomputeTeamReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"team", team.owner.as_ref(), team.name.as_bytes()],
+        bump = team.bump
+    )]
+    pub team: Account<'info, Team>,
+    
+    #[account(
+        seeds = [b"registry_stats"],
+        bump = registry_stats.bump,
+        constraint = !registry_stats.frozen @ AgentVaultError::RegistryFrozen
+    )]
+    pub registry_stats: Account<'info, RegistryStats>,
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryStats {
+    pub total_agents: u64,
+    pub total_endorsements: u64,
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub paused: bool,
+    /// Proposed next authority, `Pubkey::default()` when none
+    pub pending_authority: Pubkey,
+    /// Seconds an endorsement must exist before it can be revoked
+    pub revoke_cooldown: i64,
+    /// Lifetime given to new endorsements, in seconds
+    pub endorsement_ttl: i64,
+    /// Reputation a newly registered agent starts with
+    pub base_reputation: u8,
+    /// Boost from a mid-reputation endorser at a neutral rating
+    pub endorsement_boost: u8,
+    /// Endorsements can't push reputation above this
+    pub max_reputation: u8,
+    /// Token staked by `register_agent_with_stake`
+    pub stake_mint: Pubkey,
+    /// Minimum stake to register, in base units; 0 when staking is optional
+    pub registration_stake: u64,
+    /// Blend used by `recompute_reputation`; the two sum to 10000
+    pub endorsement_weight_bps: u16,
+    pub review_weight_bps: u16,
+    /// Lamports charged by the registration instructions; 0 when free
+    pub registration_fee: u64,
+    /// Wallet the registration fee is paid to, normally the `treasury` PDA
+    pub treasury: Pubkey,
+    /// Layout version, see `REGISTRY_VERSION`
+    pub version: u8,
+    /// Address of the zero-copy `Leaderboard`, set by `initialize_leaderboard`
+    pub leaderboard: Pubkey,
+    /// Seconds a profile must exist before it can endorse
+    pub min_endorser_age: i64,
+    /// Reputation a profile needs to endorse; 0 when anyone may
+    pub min_endorser_reputation: u8,
+    /// Signs `verify_domain` challenges; `Pubkey::default()` when unset
+    pub domain_verifier: Pubkey,
+    /// Endorsement age, in seconds, at which
+    /// `recompute_time_weighted_reputation` counts it at half weight
+    pub reputation_half_life: i64,
+    /// Seconds a registration or renewal lasts; 0 when agents never expire
+    pub agent_ttl: i64,
+    /// Share of the registration fee paid to a new agent's referrer
+    pub referral_fee_bps: u16,
+    /// Endorsements at which a skill is flagged verified; 0 disables
+    pub skill_verification_threshold: u32,
+    /// May take reputation snapshots besides the authority;
+    /// `Pubkey::default()` when unset
+    pub snapshot_authority: Pubkey,
+    /// Caps applied on writes, see `set_limits`. Registries from before
+    /// these fields read 0, meaning the layout maximum.
+    pub max_skills: u8,
+    pub max_name_len: u8,
+    pub max_uri_len: u16,
+    /// Id the next registration takes in the `AgentIndex`; ids are never
+    /// reused, so this also counts every registration ever made
+    pub next_agent_index: u64,
+    /// Emergency freeze, see `emergency_freeze`; overrides `paused`
+    pub frozen: bool,
+    /// Client-defined code passed to `emergency_freeze`, 0 when not frozen
+    pub frozen_reason: u8,
+    /// Seconds a `TimelockedAction` waits between `queue_action` and
+    /// `execute_action`; 0 lets the direct setters apply it at once
+    pub timelock_delay: i64,
+    /// Share of outstanding decay restored per heartbeat or endorsement
+    pub recovery_rate_bps: u16,
+    /// Endorsers must declare the skills they endorse; off by default
+    pub require_endorser_has_skill: bool,
+    /// Lamports locked on each new endorsement and refunded when it closes
+    pub endorsement_deposit: u64,
+    /// May import external reputation; `Pubkey::default()` when unset
+    pub reputation_oracle: Pubkey,
+    /// Share of `external_reputation` in `blended_reputation`, in basis
+    /// points; 0 leaves it out
+    pub external_weight_bps: u16,
+    /// How much a revocation takes back, see `RevokePenaltyMode`
+    pub revoke_penalty_mode: RevokePenaltyMode,
+    /// Only complete profiles can be endorsed; off by default
+    pub require_complete_profile: bool,
+    /// Reputation that penalties can't take a verified agent below; 0
+    /// gives verified agents no extra protection
+    pub verified_floor: u8,
+    /// How new endorsements are weighted, see `ReputationAlgorithm`
+    pub algorithm: ReputationAlgorithm,
+    /// Endorsement credit pool size and hourly refill, see
+    /// `set_endorsement_credits`. Registries from before these fields read
+    /// 0, meaning the defaults.
+    pub max_endorsement_credits: u16,
+    pub credits_per_hour: u16,
+    /// New registrations must pass `check_personhood`; off by default
+    pub require_personhood: bool,
+    /// Verifier called by `check_personhood`
+    pub personhood_program: Pubkey,
+    /// Share of the reputation above the floor each decay interval takes,
+    /// in basis points; registries from before this field read 0, meaning
+    /// `DEFAULT_DECAY_FRACTION_BPS`
+    pub decay_fraction_bps: u16,
+    /// Smallest job, in base units, that credits its worker reputation; 0
+    /// credits any amount
+    pub min_credited_job_amount: u64,
+    /// Only jobs paid in this mint credit reputation; `Pubkey::default()`
+    /// accepts any mint
+    pub credited_job_mint: Pubkey,
+    /// Seconds between two credited jobs for the same payer and worker;
+    /// registries from before this field read 0, meaning
+    /// `DEFAULT_JOB_CREDIT_COOLDOWN`
+    pub job_credit_cooldown: i64,
+    /// Inactivity decay never pushes reputation below this; set to
+    /// `DEFAULT_DECAY_FLOOR` by `migrate_registry` for older registries
+    pub decay_floor: u8,
+}
+
+impl RegistryStats {
+    /// Most skills a profile may declare, before its own `skill_capacity`
+    pub fn skill_limit(&self) -> usize {
+        match self.max_skills {
+            0 => DEFAULT_SKILL_CAPACITY as usize,
+            limit => limit as usize,
+        }
+    }
+
+    pub fn name_limit(&self) -> usize {
+        match self.max_name_len {
+            0 => MAX_NAME_LEN,
+            limit => limit as usize,
+        }
+    }
+
+    pub fn uri_limit(&self) -> usize {
+        match self.max_uri_len {
+            0 => MAX_METADATA_URI_LEN,
+            limit => limit as usize,
+        }
+    }
+
+    pub fn credit_limit(&self) -> u16 {
+        match self.max_endorsement_credits {
+            0 => DEFAULT_MAX_ENDORSEMENT_CREDITS,
+            limit => limit,
+        }
+    }
+
+    pub fn decay_fraction(&self) -> u16 {
+        match self.decay_fraction_bps {
+            0 => DEFAULT_DECAY_FRACTION_BPS,
+            fraction => fraction,
+        }
+    }
+
+    pub fn job_credit_interval(&self) -> i64 {
+        match self.job_credit_cooldown {
+            0 => DEFAULT_JOB_CREDIT_COOLDOWN,
+            cooldown => cooldown,
+        }
+    }
+
+    pub fn credit_rate(&self) -> u16 {
+        match self.credits_per_hour {
+            0 => DEFAULT_CREDITS_PER_HOUR,
+            rate => rate,
+        }
+    }
+
+    /// Lowest reputation penalties can leave `agent` at: `verified_floor`
+    /// once it holds any verification tier, otherwise 0
+    pub fn reputation_floor(&self, agent: &AgentProfile) -> u8 {
+        if agent.verification_tier > 0 {
+            self.verified_floor
+        } else {
+            0
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AgentProfile {
+    pub wallet: Pubkey,
+    #[max_len(32)]
+    pub name: String,
+    #[max_len(200)]
+    pub metadata_uri: String,
+    #[max_len(10, 32)]
+    pub skills: Vec<String>,
+    pub reputation: u8,           // 0-100
+    pub endorsements_received: u32,
+    pub registered_at: i64,
+    pub last_active: i64,
+    pub bump: u8,
+    pub last_decay_at: i64,
+    /// Wallet that originally registered the profile; the PDA seed, which
+    /// stays fixed across ownership transfers
+    pub agent_id: Pubkey,
+    pub status: AgentStatus,
+    /// Authority-issued badge, 0 = unverified
+    pub verification_tier: u8,
+    pub last_endorsement_at: i64,
+    /// Unused since endorsement credits replaced the daily quota; kept for
+    /// the account layout
+    pub endorsements_today: u16,
+    pub stake_mint: Pubkey,
+    /// Tokens locked in the stake vault, returned on deregistration
+    pub staked_amount: u64,
+    /// Times the authority has slashed this agent
+    pub slash_count: u32,
+    /// Sum and count of review stars; average = rating_sum / rating_count
+    pub rating_sum: u64,
+    pub rating_count: u32,
+    /// Reports ever filed against this agent, resolved or not
+    pub report_count: u32,
+    /// Layout version, see `PROFILE_VERSION`; raised by `migrate_profile`
+    pub version: u8,
+    /// Skills the account has room for; see `grow_skill_capacity`
+    pub skill_capacity: u8,
+    pub last_heartbeat_at: i64,
+    /// Coarse discovery tags, in the same canonical form as skills
+    #[max_len(3, 24)]
+    pub categories: Vec<String>,
+    /// Structured metadata (endpoint, model, region, ...), unique by key
+    #[max_len(8)]
+    pub attributes: Vec<Attribute>,
+    /// Domains and handles attested by the registry's domain verifier
+    #[max_len(2, 64)]
+    pub verified_domains: Vec<String>,
+    /// After this the agent can't be endorsed and anyone may close it with
+    /// `deregister_expired`; 0 means it never expires
+    pub expires_at: i64,
+    /// Agents registered with this one as their referrer
+    pub referral_count: u32,
+    /// Bit `i` set when `skills[i]` has reached the registry's
+    /// verification threshold
+    pub verified_skills: u32,
+    /// One-line description shown without fetching `metadata_uri`
+    #[max_len(160)]
+    pub bio: String,
+    /// SHA-256 of the avatar image behind `metadata_uri`, so frontends can
+    /// detect a swapped image; all zeros when there is no avatar
+    pub avatar_hash: [u8; 32],
+    /// Set once `mint_reputation_token` has issued the soulbound token
+    pub reputation_token_minted: bool,
+    /// Sequential id of the profile's `AgentIndex` entry; `None` for
+    /// profiles registered before the index existed
+    pub index: Option<u64>,
+    /// Reputation taken by inactivity decay and not yet recovered
+    pub decayed_amount: u8,
+    /// Endorsers with at least one live endorsement of this agent, where
+    /// `endorsements_received` counts the endorsements themselves
+    pub distinct_endorsers: u32,
+    /// `CAPABILITY_*` bits. Earlier fields are variable-length, so there is
+    /// no fixed offset; filter on it after fetching or decoding profiles.
+    pub capabilities: u64,
+    /// Where to reach the agent, empty when unpublished
+    #[max_len(128)]
+    pub endpoint: String,
+    /// Protocols spoken at `endpoint`
+    #[max_len(4, 16)]
+    pub protocols: Vec<String>,
+    /// Jobs paid out in full to this agent as worker, including disputes
+    /// ruled wholly in its favour
+    pub jobs_completed: u32,
+    /// Disputed jobs where the worker lost some of the escrow. Payer
+    /// cancellations before submission don't count: the payer could use
+    /// them to mark down any agent at will.
+    pub jobs_failed: u32,
+    /// Wallets that can hand the profile to a new owner if the owner key is
+    /// lost; see `initiate_recovery`
+    #[max_len(5)]
+    pub guardians: Vec<Pubkey>,
+    /// Guardian approvals a recovery needs, 0 when none are set
+    pub guardian_threshold: u8,
+    /// End of a planned absence set with `set_away`; no decay accrues
+    /// before it
+    pub away_until: i64,
+    /// Score (0-100) the reputation oracle imported from another platform,
+    /// kept apart from `reputation`; see `blended_reputation`
+    pub external_reputation: u8,
+    /// Platform `external_reputation` came from, empty when none
+    #[max_len(32)]
+    pub external_source: String,
+    /// End of the grace period of a pending `request_deactivation`, 0 when
+    /// none is pending
+    pub reactivate_before: i64,
+    /// Program called by `invoke_registration_hook` at registration, and
+    /// again on `rerun_registration_hook`
+    pub hook_program: Option<Pubkey>,
+    /// Consecutive UTC days, up to the latest heartbeat, with a heartbeat
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Endorsements the agent can give right now, as of
+    /// `credits_updated_at`; see `spend_endorsement_credits`
+    pub endorsement_credits: u16,
+    /// 0 until the first endorsement, meaning a full pool
+    pub credits_updated_at: i64,
+    /// Leave the agent's scores, and the amounts they move by, out of
+    /// events. This only keeps them off event feeds: the profile and its history are ordinary
+    /// account data that anyone can still read.
+    pub private_reputation: bool,
+    /// One of `REGIONS`, empty when unset. Like `capabilities` it has no
+    /// fixed offset, since earlier fields are variable-length; filter on it
+    /// after fetching or decoding profiles.
+    #[max_len(16)]
+    pub region: String,
+    /// Sum of the stored weights of the agent's live endorsements, the
+    /// endorsement side of `blended_reputation`
+    pub endorsement_points: u32,
+    /// Net reputation moved by everything but endorsement weights: slashes,
+    /// decay and recovery, job credit, and revocations that took more or
+    /// less than the endorsement's weight. `blended_reputation` adds it to
+    /// the blend, so recomputing doesn't undo them.
+    pub reputation_adjustment: i16,
+}
+
+impl AgentProfile {
+    /// A freshly registered profile, keyed on its owner's wallet
+    pub fn new(
+        wallet: Pubkey,
+        name: String,
+        metadata_uri: String,
+        skills: Vec<String>,
+        categories: Vec<String>,
+        reputation: u8,
+        bump: u8,
+        timestamp: i64,
+        expires_at: i64,
+        index: u64,
+    ) -> Self {
+        Self {
+            wallet,
+            name,
+            metadata_uri,
+            skills,
+            reputation,
+            endorsements_received: 0,
+            registered_at: timestamp,
+            last_active: timestamp,
+            bump,
+            last_decay_at: timestamp,
+            agent_id: wallet,
+            status: AgentStatus::Active,
+            verification_tier: 0,
+            last_endorsement_at: 0,
+            endorsements_today: 0,
+            stake_mint: Pubkey::default(),
+            staked_amount: 0,
+            slash_count: 0,
+            rating_sum: 0,
+            rating_count: 0,
+            report_count: 0,
+            version: PROFILE_VERSION,
+            skill_capacity: DEFAULT_SKILL_CAPACITY,
+            last_heartbeat_at: 0,
+            categories,
+            attributes: Vec::new(),
+            verified_domains: Vec::new(),
+            expires_at,
+            referral_count: 0,
+            verified_skills: 0,
+            bio: String::new(),
+            avatar_hash: [0; 32],
+            reputation_token_minted: false,
+            index: Some(index),
+            decayed_amount: 0,
+            distinct_endorsers: 0,
+            capabilities: 0,
+            endpoint: String::new(),
+            protocols: Vec::new(),
+            jobs_completed: 0,
+            jobs_failed: 0,
+            guardians: Vec::new(),
+            guardian_threshold: 0,
+            away_until: 0,
+            external_reputation: 0,
+            external_source: String::new(),
+            reactivate_before: 0,
+            hook_program: None,
+            current_streak: 0,
+            longest_streak: 0,
+            endorsement_credits: 0,
+            credits_updated_at: 0,
+            private_reputation: false,
+            region: String::new(),
+            endorsement_points: 0,
+            reputation_adjustment: 0,
+        }
+    }
+
+    /// Whether the registration has lapsed. Profiles registered without a
+    /// TTL, or before expiry existed, never do until renewed.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+
+    /// Share of finished jobs delivered, in basis points; `None` before the
+    /// first one
+    pub fn success_rate_bps(&self) -> Option<u16> {
+        let total = self.jobs_completed as u64 + self.jobs_failed as u64;
+        if total == 0 {
+            return None;
+        }
+
+        Some((self.jobs_completed as u64 * BPS_DENOMINATOR as u64 / total) as u16)
+    }
+
+    /// Old and new scores as they may appear in events: zeroed when the
+    /// agent keeps its reputation private
+    pub fn public_reputation(&self, old_reputation: u8, new_reputation: u8) -> (u8, u8) {
+        (self.public_score(old_reputation), self.public_score(new_reputation))
+    }
+
+    /// A score, or an amount it moved by, as it may appear in events. Exact
+    /// changes would let a feed rebuild a private agent's reputation, so
+    /// they are zeroed as well.
+    pub fn public_score(&self, score: u8) -> u8 {
+        if self.private_reputation {
+            0
+        } else {
+            score
+        }
+    }
+
+    /// Whether the profile has been filled out: a metadata URI, a bio and at
+    /// least `MIN_COMPLETE_PROFILE_SKILLS` skills. Throwaway Sybil profiles
+    /// tend to skip all three.
+    pub fn is_complete(&self) -> bool {
+        !self.metadata_uri.is_empty() && !self.bio.is_empty() && self.skills.len() >= MIN_COMPLETE_PROFILE_SKILLS
+    }
+
+    /// Whether `skill` is declared and flagged verified
+    pub fn is_skill_verified(&self, skill: &str) -> bool {
+        self.skills
+            .iter()
+            .position(|s| s == skill)
+            .is_some_and(|i| self.verified_skills & (1 << i) != 0)
+    }
+
+    /// Account size with room for `capacity` skills. `INIT_SPACE` already
+    /// covers `DEFAULT_SKILL_CAPACITY` of them.
+    pub fn space_for(capacity: u8) -> usize {
+        let extra = capacity.saturating_sub(DEFAULT_SKILL_CAPACITY) as usize;
+        8 + Self::INIT_SPACE + extra * (4 + MAX_SKILL_LEN)
+    }
+
+    /// Skills this profile may declare. Profiles not yet migrated read a
+    /// capacity of 0 and hold the registration default.
+    pub fn max_skills(&self) -> usize {
+        std::cmp::max(self.skill_capacity, DEFAULT_SKILL_CAPACITY) as usize
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Attribute {
+    #[max_len(24)]
+    pub key: String,
+    #[max_len(64)]
+    pub value: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum AgentStatus {
+    Active,
+    Suspended,
+    Retired,
+}
+
+/// How an endorsement's boost is computed, see `algorithm_weight`.
+///
+/// Each endorsement stores the boost it applied, so revocation and expiry
+/// undo it exactly whichever algorithm was active when it was made.
+/// Switching algorithms only affects new endorsements; existing scores
+/// change only when `recompute_reputation` or `recompute_batch` rewrites
+/// them from the profile's counters.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ReputationAlgorithm {
+    /// `endorsement_weight`: a flat boost in three endorser-reputation bands
+    LinearBoost,
+    /// The linear boost shrinking with the target's existing endorsements
+    LogarithmicBoost,
+    /// A boost proportional to the endorser's reputation
+    WeightedByEndorser,
+}
+
+/// What a revoked endorsement takes off its target's reputation.
+///
+/// `StoredWeight` removes exactly the boost the endorsement applied, so an
+/// endorse/revoke round trip always nets to zero. `CurrentReputation`
+/// recomputes the boost from the endorser's reputation at revoke time
+/// (ignoring the multi-skill diminishing and skill rarity applied on
+/// creation), so an endorser whose standing has grown since takes back
+/// more than it gave. That makes pumping a target and then pulling out
+/// costly for high-rep accounts, at the price of exactness: the target can
+/// lose more, or keep some, of the original boost. Expiry and slashing always use the stored
+/// weight, as does a revocation that doesn't pass the endorser's profile.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum RevokePenaltyMode {
+    StoredWeight,
+    CurrentReputation,
+}
+
+/// Why an endorser revoked, as passed to `revoke_endorsement` by code
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RevokeReason {
+    Other,
+    ChangedMind,
+    Misconduct,
+    Duplicate,
+}
+
+impl RevokeReason {
+    pub fn from_code(code: u8) -> Result<Self> {
+        match code {
+            0 => Ok(Self::Other),
+            1 => Ok(Self::ChangedMind),
+            2 => Ok(Self::Misconduct),
+            3 => Ok(Self::Duplicate),
+            _ => err!(AgentVaultError::InvalidRevokeReason),
+        }
+    }
+}
+
+/// Number of live endorsements an agent holds for one skill. Never closed,
+/// so removing and later re-adding a skill restores its history.
+#[account]
+#[derive(InitSpace)]
+pub struct SkillEndorsementCount {
+    pub agent: Pubkey,
+    #[max_len(32)]
+    pub skill: String,
+    pub count: u32,
+    pub bump: u8,
+}
+
+/// Every live endorsement one endorser has given, so a UI can list them
+/// without scanning all endorsement accounts. Entries that predate the
+/// index are missing until re-endorsed.
+#[account]
+#[derive(InitSpace)]
+pub struct EndorserIndex {
+    pub endorser: Pubkey,
+    #[max_len(64)]
+    pub endorsements: Vec<EndorsementRef>,
+    pub bump: u8,
+}
+
+impl EndorserIndex {
+    /// Record a new endorsement, failing once the index is full
+    pub fn add(&mut self, target: Pubkey, skill: &str) -> Result<()> {
+        require!(
+            self.endorsements.len() < ENDORSER_INDEX_CAPACITY,
+            AgentVaultError::EndorserIndexFull
+        );
+        self.endorsements.push(EndorsementRef {
+            target,
+            skill: skill.to_string(),
+        });
+
+        Ok(())
+    }
+
+    /// Drop an endorsement; a no-op for entries that were never indexed
+    pub fn remove(&mut self, target: Pubkey, skill: &str) {
+        self.endorsements.retain(|e| e.target != target || e.skill != skill);
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct EndorsementRef {
+    /// `agent_id` of the endorsed profile
+    pub target: Pubkey,
+    #[max_len(32)]
+    pub skill: String,
+}
+
+/// How many of a target's skills one endorser currently backs, which
+/// scales down each further endorsement's boost (see `diminished_weight`)
+#[account]
+#[derive(InitSpace)]
+pub struct PairEndorsementCount {
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub count: u32,
+    pub bump: u8,
+}
+
+/// How many agents declare each skill, registry-wide, for weighting
+/// endorsements by rarity (see `rarity_weight`). Tracks at most
+/// `MAX_TRACKED_SKILLS` skills: once full, new skills go uncounted until
+/// one drops to zero agents and frees its entry.
+#[account]
+#[derive(InitSpace)]
+pub struct SkillFrequency {
+    #[max_len(64)]
+    pub entries: Vec<SkillFrequencyEntry>,
+    pub bump: u8,
+}
+
+impl SkillFrequency {
+    /// Agents declaring `skill`, or `None` if it isn't tracked
+    pub fn agents(&self, skill: &str) -> Option<u32> {
+        self.entries.iter().find(|e| e.skill == skill).map(|e| e.agents)
+    }
+
+    /// Count one more agent declaring `skill`; dropped when the set is full
+    pub fn add(&mut self, skill: &str) {
+        match self.entries.iter_mut().find(|e| e.skill == skill) {
+            Some(entry) => entry.agents = entry.agents.saturating_add(1),
+            None if self.entries.len() < MAX_TRACKED_SKILLS => self.entries.push(SkillFrequencyEntry {
+                skill: skill.to_string(),
+                agents: 1,
+            }),
+            None => {}
+        }
+    }
+
+    /// Count one agent fewer declaring `skill`, freeing its entry at zero
+    pub fn remove(&mut self, skill: &str) {
+        if let Some(i) = self.entries.iter().position(|e| e.skill == skill) {
+            self.entries[i].agents = self.entries[i].agents.saturating_sub(1);
+            if self.entries[i].agents == 0 {
+                self.entries.swap_remove(i);
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SkillFrequencyEntry {
+    #[max_len(32)]
+    pub skill: String,
+    pub agents: u32,
+}
+
+/// An agent's 0-100 reputation in one skill, moved by that skill's
+/// endorsements only. Like `SkillEndorsementCount` it is never closed, so a
+/// removed skill keeps its score dormant until re-added. Directories rank
+/// agents per skill with a `getProgramAccounts` filter on `skill`.
+#[account]
+#[derive(InitSpace)]
+pub struct SkillReputation {
+    pub agent: Pubkey,
+    #[max_len(32)]
+    pub skill: String,
+    pub score: u8,
+    pub bump: u8,
+}
+
+/// Number of entries kept in a `ReputationHistory` ring buffer
+pub const REPUTATION_HISTORY_LEN: usize = 32;
+
+/// Ring buffer of an agent's most recent reputation changes. Fixed-size so
+/// the account never needs to grow.
+#[account]
+#[derive(InitSpace)]
+pub struct ReputationHistory {
+    pub agent: Pubkey,
+    /// Slot the next entry is written to
+    pub head: u8,
+    /// Number of populated entries, up to `REPUTATION_HISTORY_LEN`
+    pub len: u8,
+    pub entries: [ReputationHistoryEntry; REPUTATION_HISTORY_LEN],
+    pub bump: u8,
+}
+
+impl ReputationHistory {
+    /// An empty history for the profile at `agent`
+    pub fn new(agent: Pubkey, bump: u8) -> Self {
+        Self {
+            agent,
+            head: 0,
+            len: 0,
+            entries: [ReputationHistoryEntry {
+                timestamp: 0,
+                reputation: 0,
+                reason: ReputationReason::Endorsed,
+            }; REPUTATION_HISTORY_LEN],
+            bump,
+        }
+    }
+
+    /// Append an entry, overwriting the oldest once the buffer is full
+    pub fn record(&mut self, timestamp: i64, reputation: u8, reason: ReputationReason) {
+        self.entries[self.head as usize] = ReputationHistoryEntry {
+            timestamp,
+            reputation,
+            reason,
+        };
+        self.head = ((self.head as usize + 1) % REPUTATION_HISTORY_LEN) as u8;
+        self.len = std::cmp::min(self.len + 1, REPUTATION_HISTORY_LEN as u8);
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct ReputationHistoryEntry {
+    pub timestamp: i64,
+    /// Reputation after the change
+    pub reputation: u8,
+    pub reason: ReputationReason,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ReputationReason {
+    Endorsed,
+    Revoked,
+    Decayed,
+    Slashed,
+    Expired,
+    JobCompleted,
+    Recomputed,
+    Recovered,
+}
+
+/// Number of agents kept on the `Leaderboard`
+pub const LEADERBOARD_LEN: usize = 1000;
+/// Number of agents kept on each `SkillLeaderboard`
+pub const SKILL_LEADERBOARD_LEN: usize = 100;
+
+/// Top agents by reputation, highest first. Updated opportunistically by
+/// the instructions that change reputation, so entries can lag until an
+/// agent's reputation next moves. Deregistration and retirement drop the
+/// agent when the board is supplied.
+///
+/// Zero-copy: at 33KB the board is read and edited in place through an
+/// `AccountLoader` rather than deserialized.
+#[account(zero_copy)]
+pub struct Leaderboard {
+    pub entries: [LeaderboardEntry; LEADERBOARD_LEN],
+    /// Number of populated entries, up to `LEADERBOARD_LEN`
+    pub len: u16,
+}
+
+impl Leaderboard {
+    /// Account size, discriminator included
+    pub const SPACE: usize = 8 + std::mem::size_of::<Leaderboard>();
+
+    /// Move `agent` to the rank its new reputation earns (see `rerank`)
+    pub fn update(&mut self, agent: Pubkey, reputation: u8) {
+        self.len = rerank(&mut self.entries, self.len as usize, agent, Some(reputation)) as u16;
+    }
+
+    /// Drop `agent` from the board, e.g. once it's deregistered or retired
+    pub fn remove(&mut self, agent: Pubkey) {
+        self.len = rerank(&mut self.entries, self.len as usize, agent, None) as u16;
+    }
+}
+
+/// Top agents in one skill by skill reputation, highest first. Maintained
+/// like the `Leaderboard`, by `endorse_skill` and `revoke_endorsement` when
+/// the caller supplies it; agents whose score falls to 0 drop off.
+#[account(zero_copy)]
+pub struct SkillLeaderboard {
+    /// The skill's canonical name, zero-padded
+    pub skill: [u8; 32],
+    pub entries: [LeaderboardEntry; SKILL_LEADERBOARD_LEN],
+    /// Number of populated entries, up to `SKILL_LEADERBOARD_LEN`
+    pub len: u16,
+}
+
+impl SkillLeaderboard {
+    /// Account size, discriminator included
+    pub const SPACE: usize = 8 + std::mem::size_of::<SkillLeaderboard>();
+
+    /// Move `agent` to the rank its new skill score earns (see `rerank`)
+    pub fn update(&mut self, agent: Pubkey, score: u8) {
+        let score = (score > 0).then_some(score);
+        self.len = rerank(&mut self.entries, self.len as usize, agent, score) as u16;
+    }
+}
+
+/// Move `agent` within the sorted `entries[..len]` to the rank `reputation`
+/// earns, evicting the lowest entry when the board is full, or just remove
+/// it when `reputation` is `None`. Ties keep the incumbent ahead. Returns
+/// the new length.
+///
+/// Entries shift in place. Finding the agent's old entry is a linear scan,
+/// since a stale entry may not sit where its reputation suggests; the new
+/// rank is a binary search.
+pub fn rerank(entries: &mut [LeaderboardEntry], mut len: usize, agent: Pubkey, reputation: Option<u8>) -> usize {
+    if let Some(i) = entries[..len].iter().position(|e| e.agent == agent) {
+        entries.copy_within(i + 1..len, i);
+        len -= 1;
+    }
+
+    let Some(reputation) = reputation else {
+        return len;
+    };
+    let rank = entries[..len].partition_point(|e| e.reputation >= reputation);
+    if rank < entries.len() {
+        // On a full board the last entry falls off
+        let new_len = std::cmp::min(len + 1, entries.len());
+        entries.copy_within(rank..new_len - 1, rank + 1);
+        entries[rank] = LeaderboardEntry { agent, reputation };
+        len = new_len;
+    }
+
+    len
+}
+
+#[zero_copy]
+pub struct LeaderboardEntry {
+    /// `agent_id` of the ranked profile
+    pub agent: Pubkey,
+    pub reputation: u8,
+}
+
+/// Escrowed payment from a payer to a worker agent
+#[account]
+#[derive(InitSpace)]
+pub struct Job {
+    pub payer: Pubkey,
+    /// `agent_id` of the worker's profile
+    pub worker: Pubkey,
+    pub mint: Pubkey,
+    /// Escrowed amount, in base units
+    pub amount: u64,
+    /// Payer-chosen id, part of the PDA seed
+    pub job_id: u64,
+    pub status: JobStatus,
+    pub created_at: i64,
+    pub bump: u8,
+    pub vault_bump: u8,
+    /// Optional staged payouts; empty for a single lump-sum job
+    #[max_len(12)]
+    pub milestones: Vec<Milestone>,
+    /// Total paid out to the worker so far
+    pub released_amount: u64,
+    /// Escrow is frozen until the authority resolves the dispute
+    pub disputed: bool,
+}
+
+/// Job reputation credit between one payer wallet and one worker, rate
+/// limited by `RegistryStats::job_credit_interval`
+#[account]
+#[derive(InitSpace)]
+pub struct JobCredit {
+    pub payer: Pubkey,
+    /// `agent_id` of the worker's profile
+    pub worker: Pubkey,
+    /// When the pair last earned credit; 0 before the first time
+    pub last_credited_at: i64,
+    /// The job that earned it. Completed jobs are closed, so
+    /// `submit_review` checks the job against this instead.
+    pub last_credited_job: Pubkey,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Milestone {
+    pub amount: u64,
+    pub released: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum JobStatus {
+    Open,
+    Submitted,
+    Completed,
+    Cancelled,
+    /// Escrow split by the authority after a dispute
+    Resolved,
+}
+
+/// Grants a wallet the moderation instructions (verification, slashing,
+/// blacklisting) without the rest of the authority's powers
+#[account]
+#[derive(InitSpace)]
+pub struct Moderator {
+    pub wallet: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+/// Marks a wallet as barred from registering; its existence is the flag
+#[account]
+#[derive(InitSpace)]
+pub struct BlacklistEntry {
+    pub wallet: Pubkey,
+    pub blacklisted_at: i64,
+    pub bump: u8,
+}
+
+/// Left behind when a profile is deregistered; its existence bars the
+/// `agent_id` from registering again. Endorsements, skill reputation and
+/// pair counters are seeded on the profile, so a new profile at the same
+/// address would inherit them, and would start with a clean `slash_count`.
+#[account]
+#[derive(InitSpace)]
+pub struct DeregisteredAgent {
+    pub agent_id: Pubkey,
+    /// The profile's `slash_count` when it was closed
+    pub slash_count: u32,
+    pub deregistered_at: i64,
+    pub bump: u8,
+}
+
+/// An operational key allowed to act for an agent in `OperateAgent`
+/// instructions. Seeded on the owner's wallet, so it stops applying once
+/// the profile changes hands; `agent` stops it carrying over to another
+/// profile the same wallet later registers.
+#[account]
+#[derive(InitSpace)]
+pub struct Delegate {
+    /// `agent_id` of the profile the delegate acts for
+    pub agent: Pubkey,
+    pub delegate: Pubkey,
+    pub set_at: i64,
+    pub bump: u8,
+}
+
+/// Reserves a normalized agent name; seeded on that name, so each name can
+/// be held by one agent at a time
+#[account]
+#[derive(InitSpace)]
+pub struct NameRegistry {
+    /// `agent_id` of the profile holding the name
+    pub agent: Pubkey,
+    pub bump: u8,
+}
+
+/// Who referred an agent, recorded once at registration
+#[account]
+#[derive(InitSpace)]
+pub struct Referral {
+    /// `agent_id` of the referring profile
+    pub referrer: Pubkey,
+    /// `agent_id` of the referred profile
+    pub referred: Pubkey,
+    /// Lamports of the registration fee paid to the referrer
+    pub reward: u64,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+/// Entry `index` of the sequential agent index, so clients can page through
+/// every registration by id. Deregistered agents leave their entry behind
+/// as a tombstone, keeping ids stable.
+#[account]
+#[derive(InitSpace)]
+pub struct AgentIndex {
+    pub index: u64,
+    /// `agent_id` of the registered profile; its PDA gives the current wallet
+    pub agent: Pubkey,
+    /// When the agent deregistered, 0 while it is live
+    pub deregistered_at: i64,
+    pub bump: u8,
+}
+
+/// A registry parameter change that has to wait out `timelock_delay`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum TimelockedAction {
+    ReputationParams {
+        base_reputation: u8,
+        endorsement_boost: u8,
+        max_reputation: u8,
+    },
+    ReputationWeights {
+        endorsement_weight_bps: u16,
+        review_weight_bps: u16,
+    },
+    FeeConfig {
+        registration_fee: u64,
+        treasury: Pubkey,
+    },
+    ReferralFeeBps {
+        referral_fee_bps: u16,
+    },
+    TimelockDelay {
+        seconds: i64,
+    },
+    ExternalReputationWeight {
+        external_weight_bps: u16,
+    },
+    ReputationAlgorithm {
+        algorithm: ReputationAlgorithm,
+    },
+    EndorsementTtl {
+        seconds: i64,
+    },
+    Limits {
+        max_skills: u8,
+        max_name_len: u8,
+        max_uri_len: u16,
+    },
+    EndorsementDeposit {
+        lamports: u64,
+    },
+    VerifiedFloor {
+        verified_floor: u8,
+    },
+    MinEndorserReputation {
+        reputation: u8,
+    },
+    AgentTtl {
+        seconds: i64,
+    },
+    DecayFraction {
+        decay_fraction_bps: u16,
+    },
+    // The rest need accounts `execute_action` doesn't take, so they run
+    // through their own instructions; see `consume_queued_action`
+    StakeConfig {
+        stake_mint: Pubkey,
+        registration_stake: u64,
+    },
+    TreasuryWithdrawal {
+        amount: u64,
+        destination: Pubkey,
+    },
+    Slash {
+        agent_id: Pubkey,
+        reputation_penalty: u8,
+        stake_penalty: u64,
+        reason: u8,
+    },
+    StakeTreasuryWithdrawal {
+        stake_mint: Pubkey,
+        amount: u64,
+        destination: Pubkey,
+    },
+    DecayFloor {
+        decay_floor: u8,
+    },
+}
+
+impl TimelockedAction {
+    /// SHA-256 of the Borsh-encoded action, the key it is queued under
+    pub fn hash(&self) -> [u8; 32] {
+        hash(&self.try_to_vec().unwrap_or_default()).to_bytes()
+    }
+}
+
+/// A `TimelockedAction` waiting for its delay; exists from `queue_action`
+/// until `execute_action` or `cancel_action` closes it
+#[account]
+#[derive(InitSpace)]
+pub struct QueuedAction {
+    pub action_hash: [u8; 32],
+    pub queued_at: i64,
+    pub execute_after: i64,
+    pub bump: u8,
+}
+
+/// A guardian-opened move of a profile to `new_owner`, one per profile at a
+/// time; closed by `recover_account` or `cancel_recovery`
+#[account]
+#[derive(InitSpace)]
+pub struct Recovery {
+    pub agent_id: Pubkey,
+    pub new_owner: Pubkey,
+    pub initiator: Pubkey,
+    /// Guardians who have approved, the initiator first
+    #[max_len(5)]
+    pub approvals: Vec<Pubkey>,
+    pub initiated_at: i64,
+    pub executable_at: i64,
+    pub bump: u8,
+}
+
+/// Maps an owner wallet to the profile it currently controls
+#[account]
+#[derive(InitSpace)]
+pub struct OwnerIndex {
+    pub profile: Pubkey,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Endorsement {
+    pub endorser: Pubkey,
+    /// `agent_id` of the endorsed profile
+    pub target: Pubkey,
+    #[max_len(32)]
+    pub skill: String,
+    pub timestamp: i64,
+    pub bump: u8,
+    /// Reputation boost applied to the target when the endorsement was made
+    pub weight: u8,
+    /// 1 (competent) to 5 (expert)
+    pub rating: u8,
+    /// Set when the target endorsed the endorser back for the same skill
+    pub reciprocal: bool,
+    /// After this anyone may close the endorsement via `expire_endorsement`
+    pub expires_at: i64,
+    /// Boost applied to the target's `SkillReputation` for this skill
+    pub skill_weight: u8,
+    /// Lamports held beyond rent, refunded with it unless the endorsement
+    /// is slashed
+    pub deposit: u64,
+}
+
+/// A client's review of the agent that completed one of their jobs
+#[account]
+#[derive(InitSpace)]
+pub struct Review {
+    pub reviewer: Pubkey,
+    /// `agent_id` of the reviewed profile
+    pub target: Pubkey,
+    pub job: Pubkey,
+    /// 1-5
+    pub stars: u8,
+    #[max_len(140)]
+    pub comment: String,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+/// A user's flag on an agent, open until a moderator resolves it
+#[account]
+#[derive(InitSpace)]
+pub struct Report {
+    pub reporter: Pubkey,
+    /// `agent_id` of the reported profile
+    pub target: Pubkey,
+    /// Client-defined reason code, passed on as the slash reason
+    pub reason: u8,
+    #[max_len(100)]
+    pub note: String,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// An agent's reputation frozen at a governance snapshot
+#[account]
+#[derive(InitSpace)]
+pub struct ReputationSnapshot {
+    pub snapshot_id: u64,
+    /// `agent_id` of the captured profile
+    pub agent: Pubkey,
+    pub wallet: Pubkey,
+    pub reputation: u8,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+/// A named crew of agents with a shared reputation
+#[account]
+#[derive(InitSpace)]
+pub struct Team {
+    pub owner: Pubkey,
+    #[max_len(32)]
+    pub name: String,
+    /// `agent_id`s of the member profiles
+    #[max_len(10)]
+    pub members: Vec<Pubkey>,
+    /// Average member reputation as of the last `recompute_team_reputation`
+    pub reputation: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct AgentRegistered {
+    pub wallet: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeDeposited {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentDeregistered {
+    pub wallet: Pubkey,
+    pub stake_returned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentRenewed {
+    pub wallet: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentRenamed {
+    pub wallet: Pubkey,
+    pub old_name: String,
+    pub new_name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardiansUpdated {
+    pub agent_id: Pubkey,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryInitiated {
+    pub agent_id: Pubkey,
+    pub new_owner: Pubkey,
+    pub initiator: Pubkey,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryApproved {
+    pub agent_id: Pubkey,
+    pub guardian: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryCancelled {
+    pub agent_id: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountRecovered {
+    pub agent_id: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileMigrated {
+    pub agent_id: Pubkey,
+    pub old_version: u8,
+    pub new_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RegistryMigrated {
+    pub old_version: u8,
+    pub new_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateSet {
+    pub wallet: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub wallet: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileUpdated {
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SkillAdded {
+    pub wallet: Pubkey,
+    pub skill: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SkillRemoved {
+    pub wallet: Pubkey,
+    pub skill: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationTokenMinted {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CapabilitiesUpdated {
+    pub wallet: Pubkey,
+    pub capabilities: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AvatarUpdated {
+    pub wallet: Pubkey,
+    pub avatar_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SkillVerificationChanged {
+    pub wallet: Pubkey,
+    pub skill: String,
+    pub verified: bool,
+    pub endorsements: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementDepositUpdated {
+    pub endorsement_deposit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RevokePenaltyModeUpdated {
+    pub mode: RevokePenaltyMode,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementCreditsUpdated {
+    pub max_endorsement_credits: u16,
+    pub credits_per_hour: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerifiedFloorUpdated {
+    pub verified_floor: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompleteProfileRequirementUpdated {
+    pub required: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PersonhoodRequirementUpdated {
+    pub required: bool,
+    pub personhood_program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorserSkillRequirementUpdated {
+    pub required: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryRateUpdated {
+    pub recovery_rate_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SkillVerificationThresholdUpdated {
+    pub threshold: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttributeSet {
+    pub wallet: Pubkey,
+    pub key: String,
+    pub value: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttributeRemoved {
+    pub wallet: Pubkey,
+    pub key: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SkillEndorsed {
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub skill: String,
+    pub rating: u8,
+    pub weight: u8,
+    pub reciprocal: bool,
+    pub skill_endorsements: u32,
+    pub skill_reputation: u8,
+    pub total_endorsements: u64,
+    /// The target's endorsement count and how many endorsers it spans
+    pub endorsements_received: u32,
+    pub distinct_endorsers: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementAttestation {
+    pub issuer: Pubkey,
+    pub endorsement: Pubkey,
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub skill: String,
+    pub rating: u8,
+    pub endorsed_at: i64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementSlashed {
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub skill: String,
+    pub deposit_forfeited: u64,
+    pub slashed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementMilestone {
+    pub wallet: Pubkey,
+    pub milestone: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementRevoked {
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub skill: String,
+    pub skill_endorsements: u32,
+    pub skill_reputation: u8,
+    pub total_endorsements: u64,
+    pub reason: RevokeReason,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EndorsementExpired {
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub skill: String,
+    pub skill_endorsements: u32,
+    pub skill_reputation: u8,
+    pub total_endorsements: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrphanedEndorsementClosed {
+    pub endorser: Pubkey,
+    pub target: Pubkey,
+    pub skill: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JobCreated {
+    pub job: Pubkey,
+    pub payer: Pubkey,
+    pub worker: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WorkSubmitted {
+    pub job: Pubkey,
+    pub worker: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentReleased {
+    pub job: Pubkey,
+    pub payer: Pubkey,
+    pub worker: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MilestoneReleased {
+    pub job: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+    pub job_completed: bool,
+    pub dust_returned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JobCancelled {
+    pub job: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewSubmitted {
+    pub reviewer: Pubkey,
+    pub target: Pubkey,
+    pub job: Pubkey,
+    pub stars: u8,
+    pub rating_sum: u64,
+    pub rating_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub job: Pubkey,
+    pub opened_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub job: Pubkey,
+    pub payer_amount: u64,
+    pub worker_amount: u64,
+    pub payer_share_bps: u16,
+    pub worker_share_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchRecomputed {
+    /// Profiles processed in the call
+    pub count: u32,
+    /// How many of them changed reputation
+    pub changed: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreakUpdated {
+    pub wallet: Pubkey,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AwayModeSet {
+    pub wallet: Pubkey,
+    pub away_until: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationDecayed {
+    pub wallet: Pubkey,
+    pub old_reputation: u8,
+    pub new_reputation: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StatusChanged {
+    pub wallet: Pubkey,
+    pub old_status: AgentStatus,
+    pub new_status: AgentStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeactivationRequested {
+    pub wallet: Pubkey,
+    pub reactivate_before: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationChanged {
+    pub wallet: Pubkey,
+    pub old_reputation: u8,
+    pub new_reputation: u8,
+    pub reason: ReputationReason,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseChanged {
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ActionQueued {
+    pub action_hash: [u8; 32],
+    pub execute_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ActionExecuted {
+    pub action_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ActionCancelled {
+    pub action_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TimelockDelayUpdated {
+    pub timelock_delay: i64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct UpdateProfile<'info> {
-    #[account(
-        mut,
-        seeds = [b"agent", owner.key().as_ref()],
-        bump = agent_profile.bump,
-        has_one = wallet @ AgentVaultError::Unauthorized
-    )]
-    pub agent_profile: Account<'info, AgentProfile>,
-    
-    #[account(mut, constraint = owner.key() == agent_profile.wallet)]
-    pub owner: Signer<'info>,
+#[event]
+pub struct RegistryFrozen {
+    pub reason: u8,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-#[instruction(skill: String)]
-pub struct EndorseSkill<'info> {
-    #[account(
-        init,
-        payer = endorser,
-        space = 8 + Endorsement::INIT_SPACE,
-        seeds = [
-            b"endorsement",
-            endorser.key().as_ref(),
-            target_agent.wallet.as_ref(),
-            skill.as_bytes()
-        ],
-        bump
-    )]
-    pub endorsement: Account<'info, Endorsement>,
-    
-    #[account(
-        mut,
-        seeds = [b"agent", endorser.key().as_ref()],
-        bump = endorser_profile.bump
-    )]
-    pub endorser_profile: Account<'info, AgentProfile>,
-    
-    #[account(
-        mut,
-        seeds = [b"agent", target_agent.wallet.as_ref()],
-        bump = target_agent.bump
-    )]
-    pub target_agent: Account<'info, AgentProfile>,
-    
-    #[account(mut)]
-    pub endorser: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct RegistryUnfrozen {
+    /// The reason the registry had been frozen with
+    pub reason: u8,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct RevokeEndorsement<'info> {
-    #[account(
-        mut,
-        close = endorser,
-        seeds = [
-            b"endorsement",
-            endorser.key().as_ref(),
-            target_agent.wallet.as_ref(),
-            endorsement.skill.as_bytes()
-        ],
-        bump = endorsement.bump,
-        has_one = endorser
-    )]
-    pub endorsement: Account<'info, Endorsement>,
-    
-    #[account(
-        mut,
-        seeds = [b"agent", target_agent.wallet.as_ref()],
-        bump = target_agent.bump
-    )]
-    pub target_agent: Account<'info, AgentProfile>,
-    
-    #[account(mut)]
-    pub endorser: Signer<'info>,
+#[event]
+pub struct RevokeCooldownUpdated {
+    pub revoke_cooldown: i64,
+    pub timestamp: i64,
 }
 
-// ============================================================================
-// State
-// ============================================================================
+#[event]
+pub struct EndorsementTtlUpdated {
+    pub endorsement_ttl: i64,
+    pub timestamp: i64,
+}
 
-#[account]
-#[derive(InitSpace)]
-pub struct RegistryStats {
-    pub total_agents: u64,
-    pub total_endorsements: u64,
+#[event]
+pub struct MinEndorserAgeUpdated {
+    pub min_endorser_age: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinEndorserReputationUpdated {
+    pub min_endorser_reputation: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DomainVerifierUpdated {
+    pub domain_verifier: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DomainVerified {
+    pub wallet: Pubkey,
+    pub domain: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationParamsUpdated {
+    pub base_reputation: u8,
+    pub endorsement_boost: u8,
+    pub max_reputation: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationWeightsUpdated {
+    pub endorsement_weight_bps: u16,
+    pub review_weight_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentTtlUpdated {
+    pub agent_ttl: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationHalfLifeUpdated {
+    pub reputation_half_life: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DecayFractionUpdated {
+    pub decay_fraction_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DecayFloorUpdated {
+    pub decay_floor: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JobCreditPolicyUpdated {
+    pub min_amount: u64,
+    pub mint: Pubkey,
+    pub cooldown: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentSlashed {
+    pub wallet: Pubkey,
+    pub reputation_penalty: u8,
+    pub stake_slashed: u64,
+    pub slash_count: u32,
+    pub reason: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeConfigUpdated {
+    pub mint: Pubkey,
+    pub registration_stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralFeeUpdated {
+    pub referral_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralRecorded {
+    pub referrer: Pubkey,
+    pub referred: Pubkey,
+    pub reward: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeConfigUpdated {
+    pub registration_fee: u64,
+    pub treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeTreasuryWithdrawn {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
     pub authority: Pubkey,
-    pub bump: u8,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct AgentProfile {
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ModeratorAdded {
     pub wallet: Pubkey,
-    #[max_len(32)]
-    pub name: String,
-    #[max_len(200)]
-    pub metadata_uri: String,
-    #[max_len(10, 32)]
-    pub skills: Vec<String>,
-    pub reputation: u8,           // 0-100
-    pub endorsements_received: u32,
-    pub registered_at: i64,
-    pub last_active: i64,
-    pub bump: u8,
+    pub timestamp: i64,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct Endorsement {
-    pub endorser: Pubkey,
+#[event]
+pub struct ModeratorRemoved {
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BlacklistUpdated {
+    pub wallet: Pubkey,
+    pub blacklisted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentVerified {
+    pub wallet: Pubkey,
+    pub old_tier: u8,
+    pub new_tier: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentReported {
+    pub reporter: Pubkey,
     pub target: Pubkey,
-    #[max_len(32)]
-    pub skill: String,
+    pub reason: u8,
+    pub report_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReportResolved {
+    pub reporter: Pubkey,
+    pub target: Pubkey,
+    pub resolved_by: Pubkey,
+    pub slashed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnershipTransferred {
+    pub agent_id: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationSnapshotted {
+    pub snapshot_id: u64,
+    pub wallet: Pubkey,
+    pub reputation: u8,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LimitsUpdated {
+    pub max_skills: u8,
+    pub max_name_len: u8,
+    pub max_uri_len: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExternalReputationImported {
+    pub wallet: Pubkey,
+    pub external_reputation: u8,
+    pub source: String,
+    pub oracle: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationOracleUpdated {
+    pub reputation_oracle: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationAlgorithmUpdated {
+    pub algorithm: ReputationAlgorithm,
     pub timestamp: i64,
-    pub bump: u8,
 }
 
-// ============================================================================
-// Events
-// ============================================================================
+#[event]
+pub struct ExternalReputationWeightUpdated {
+    pub external_weight_bps: u16,
+    pub timestamp: i64,
+}
 
 #[event]
-pub struct AgentRegistered {
-    pub wallet: Pubkey,
-    pub name: String,
+pub struct SnapshotAuthorityUpdated {
+    pub snapshot_authority: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ProfileUpdated {
-    pub wallet: Pubkey,
+pub struct TeamCreated {
+    pub team: Pubkey,
+    pub owner: Pubkey,
+    pub name: String,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct SkillEndorsed {
-    pub endorser: Pubkey,
-    pub target: Pubkey,
-    pub skill: String,
+pub struct MemberAdded {
+    pub team: Pubkey,
+    pub agent_id: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct EndorsementRevoked {
-    pub endorser: Pubkey,
-    pub target: Pubkey,
-    pub skill: String,
+pub struct MemberRemoved {
+    pub team: Pubkey,
+    pub agent_id: Pubkey,
     pub timestamp: i64,
 }
 
@@ -359,13 +10724,13 @@ pub struct EndorsementRevoked {
 
 #[error_code]
 pub enum AgentVaultError {
-    #[msg("Name must be 32 characters or less")]
+    #[msg("Name exceeds the registry's length limit (UTF-8 encoded)")]
     NameTooLong,
-    #[msg("Metadata URI must be 200 characters or less")]
+    #[msg("Metadata URI exceeds the registry's length limit (UTF-8 encoded)")]
     MetadataUriTooLong,
-    #[msg("Cannot declare more than 10 skills")]
+    #[msg("Skill list exceeds the profile's skill capacity or the registry limit")]
     TooManySkills,
-    #[msg("Skill name must be 32 characters or less")]
+    #[msg("Skill name must be 32 bytes or less (UTF-8 encoded)")]
     SkillNameTooLong,
     #[msg("Cannot endorse yourself")]
     CannotEndorseSelf,
@@ -373,4 +10738,1216 @@ pub enum AgentVaultError {
     SkillNotDeclared,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("No full decay interval has elapsed")]
+    NothingToDecay,
+    #[msg("Endorser does not have a registered agent profile")]
+    EndorserNotRegistered,
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+    #[msg("Agent is not active")]
+    AgentNotActive,
+    #[msg("Registry is paused")]
+    RegistryPaused,
+    #[msg("Skill is already declared")]
+    SkillAlreadyDeclared,
+    #[msg("Skill is not declared on this profile")]
+    SkillNotFound,
+    #[msg("Skill list contains a duplicate")]
+    DuplicateSkill,
+    // Unused since endorsement credits replaced the daily cap; kept so the
+    // codes of later variants don't shift
+    #[msg("Daily endorsement limit reached")]
+    EndorsementRateLimited,
+    #[msg("Endorsement cannot be revoked yet")]
+    RevokeTooSoon,
+    #[msg("Invalid registry parameter")]
+    InvalidParameter,
+    #[msg("Endorsement has not expired yet")]
+    EndorsementNotExpired,
+    #[msg("Stake is below the registry minimum")]
+    InsufficientStake,
+    #[msg("Token account or mint does not match the stake mint")]
+    InvalidStakeMint,
+    #[msg("Stake vault accounts are required to return the stake")]
+    StakeAccountsMissing,
+    #[msg("Job amount must be greater than zero")]
+    InvalidJobAmount,
+    #[msg("Job is not in a state that allows this action")]
+    InvalidJobStatus,
+    #[msg("Signer is not a party to this job")]
+    NotJobParty,
+    #[msg("Token account does not match the job's mint")]
+    MintMismatch,
+    #[msg("Cannot split a job into more than 12 milestones")]
+    TooManyMilestones,
+    #[msg("Milestone index is out of range")]
+    InvalidMilestoneIndex,
+    #[msg("Milestone has already been released")]
+    MilestoneAlreadyReleased,
+    #[msg("Job escrow is frozen by an open dispute")]
+    JobDisputed,
+    #[msg("Job is not under dispute")]
+    JobNotDisputed,
+    #[msg("Dispute shares must sum to 10000 basis points")]
+    InvalidDisputeShares,
+    #[msg("Review comment must be 140 bytes or less (UTF-8 encoded)")]
+    ReviewTooLong,
+    #[msg("Job has not been completed")]
+    JobNotCompleted,
+    #[msg("Wallet is blacklisted")]
+    WalletBlacklisted,
+    #[msg("Report note must be 100 bytes or less (UTF-8 encoded)")]
+    ReportNoteTooLong,
+    #[msg("Report does not concern this agent")]
+    ReportTargetMismatch,
+    #[msg("Owner cannot cover the registration fee")]
+    InsufficientFee,
+    #[msg("Treasury account is missing or is not the registry treasury")]
+    InvalidTreasury,
+    #[msg("Withdrawal exceeds the treasury's balance above rent exemption")]
+    InsufficientTreasuryBalance,
+    #[msg("Cannot batch more than 5 skills at once")]
+    TooManyBatchedSkills,
+    #[msg("Remaining accounts do not match the skills in the batch")]
+    BatchAccountsMismatch,
+    #[msg("Skill is already endorsed by this endorser")]
+    AlreadyEndorsed,
+    #[msg("Metadata URI must start with https://, ipfs:// or ar://")]
+    InvalidMetadataUri,
+    #[msg("Skill must be lowercase a-z, 0-9 or -+#. with no surrounding whitespace")]
+    InvalidSkillFormat,
+    #[msg("Another agent already uses this name")]
+    NameTaken,
+    #[msg("Profile is already at the current layout version")]
+    ProfileAlreadyMigrated,
+    #[msg("Skill capacity must grow and stay within 20")]
+    InvalidSkillCapacity,
+    #[msg("Account is not the registry's leaderboard")]
+    InvalidLeaderboard,
+    #[msg("Batch must hold 1 to 5 entries, each with a name, URI, skill list and category list")]
+    InvalidBatchSize,
+    #[msg("Heartbeats are limited to one per hour")]
+    HeartbeatTooSoon,
+    #[msg("Endorser profile is too new to endorse")]
+    EndorserTooNew,
+    #[msg("Endorser reputation is below the registry minimum")]
+    EndorserReputationTooLow,
+    #[msg("Too many categories (max 3)")]
+    TooManyCategories,
+    #[msg("Category must be 24 bytes or less")]
+    CategoryTooLong,
+    #[msg("Category list contains a duplicate")]
+    DuplicateCategory,
+    #[msg("Too many attributes (max 8)")]
+    TooManyAttributes,
+    #[msg("Attribute key must be 1 to 24 bytes")]
+    InvalidAttributeKey,
+    #[msg("Attribute value must be 64 bytes or less")]
+    AttributeValueTooLong,
+    #[msg("Attribute not found on profile")]
+    AttributeNotFound,
+    #[msg("Domain verification is not configured")]
+    DomainVerifierNotSet,
+    #[msg("Domain must be 1 to 64 bytes of [a-z0-9-._/]")]
+    InvalidDomain,
+    #[msg("Missing or mismatched ed25519 domain signature")]
+    DomainSignatureInvalid,
+    #[msg("Too many verified domains (max 2)")]
+    TooManyDomains,
+    #[msg("Agent registration has expired")]
+    AgentExpired,
+    #[msg("Agent registration has not expired")]
+    AgentNotExpired,
+    #[msg("An agent cannot refer itself")]
+    SelfReferral,
+    #[msg("Referrer accounts are missing or don't match the referrer")]
+    InvalidReferrer,
+    #[msg("Bio must be 160 bytes or less (UTF-8 encoded)")]
+    BioTooLong,
+    #[msg("Endorser index is full (max 64 live endorsements)")]
+    EndorserIndexFull,
+    #[msg("Reputation token already minted for this agent")]
+    ReputationTokenAlreadyMinted,
+    #[msg("Team name must be 1 to 32 bytes")]
+    InvalidTeamName,
+    #[msg("Team is full (max 10 members)")]
+    TeamFull,
+    #[msg("Agent is already a member of this team")]
+    AlreadyTeamMember,
+    #[msg("Agent is not a member of this team")]
+    NotTeamMember,
+    #[msg("Endorsed agent still exists; revoke the endorsement instead")]
+    EndorsementNotOrphaned,
+    #[msg("Registry is frozen for an emergency")]
+    RegistryFrozen,
+    #[msg("A timelock is set; queue this change with queue_action")]
+    TimelockRequired,
+    #[msg("Queued action's timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Endorser must declare the skill it endorses")]
+    EndorserLacksSkill,
+    #[msg("Unknown revoke reason code")]
+    InvalidRevokeReason,
+    #[msg("Capabilities include an undefined bit")]
+    UnknownCapabilityBit,
+    #[msg("Endpoint must be 128 bytes or less (UTF-8 encoded)")]
+    EndpointTooLong,
+    #[msg("Endpoint must start with https:// or wss://")]
+    InvalidEndpoint,
+    #[msg("Too many protocols (max 4)")]
+    TooManyProtocols,
+    #[msg("Protocol must be 1 to 16 bytes of [a-z0-9-.], listed once")]
+    InvalidProtocol,
+    #[msg("Not enough lamports for the endorsement deposit")]
+    InsufficientDeposit,
+    #[msg("Guardians must be up to 5 distinct wallets besides the owner, with a threshold of 1 to their count")]
+    InvalidGuardians,
+    #[msg("Signer is not one of the profile's guardians")]
+    NotGuardian,
+    #[msg("Recovery must move the profile to a different wallet")]
+    InvalidRecoveryOwner,
+    #[msg("Guardian has already approved this recovery")]
+    RecoveryAlreadyApproved,
+    #[msg("Not enough guardians have approved the recovery")]
+    GuardianThresholdNotMet,
+    #[msg("Recovery timelock has not elapsed")]
+    RecoveryTimelockActive,
+    #[msg("Away window can last at most 90 days")]
+    AwayTooLong,
+    #[msg("External source must be 32 bytes or less")]
+    InvalidExternalSource,
+    #[msg("Target profile needs a metadata URI, a bio and at least 2 skills")]
+    ProfileIncomplete,
+    #[msg("A deactivation is already pending")]
+    DeactivationPending,
+    #[msg("No deactivation is pending")]
+    NoPendingDeactivation,
+    #[msg("Deactivation grace period has ended")]
+    DeactivationGraceElapsed,
+    #[msg("Deactivation grace period has not ended")]
+    DeactivationGraceActive,
+    #[msg("Hook program account is missing, not executable or not the profile's hook")]
+    InvalidHookProgram,
+    #[msg("Endorsement validity must be positive and at most 2 years")]
+    InvalidExpiry,
+    #[msg("This wallet already has an agent profile")]
+    AgentAlreadyRegistered,
+    #[msg("No endorsement credits left; they refill over time")]
+    NoEndorsementCredits,
+    #[msg("Profile passed for an endorsement is not its target")]
+    EndorsementTargetMismatch,
+    #[msg("Region is not one of the supported codes")]
+    InvalidRegion,
+    #[msg("The skill_frequency account is required to change skills")]
+    MissingSkillFrequency,
+    #[msg("Registration requires a proof-of-personhood check")]
+    PersonhoodRequired,
+    #[msg("Registry is already at the current layout version")]
+    RegistryAlreadyMigrated,
+    #[msg("This action runs through its own instruction, passing the queued action")]
+    ActionNotExecutable,
+    #[msg("This wallet's profile address still holds a profile it transferred away")]
+    ProfileAddressTransferred,
+    #[msg("This agent id was deregistered and can't register again")]
+    AgentIdDeregistered,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry() -> RegistryStats {
+        let data = vec![0u8; 8 + RegistryStats::INIT_SPACE];
+        let mut stats = RegistryStats::try_deserialize_unchecked(&mut &data[..]).unwrap();
+        stats.base_reputation = DEFAULT_BASE_REPUTATION;
+        stats.endorsement_boost = DEFAULT_ENDORSEMENT_BOOST;
+        stats.max_reputation = DEFAULT_MAX_REPUTATION;
+        stats
+    }
+
+    fn test_profile(reputation: u8) -> AgentProfile {
+        AgentProfile::new(
+            Pubkey::new_unique(),
+            "agent".to_string(),
+            String::new(),
+            vec![],
+            vec![],
+            reputation,
+            255,
+            0,
+            0,
+            0,
+        )
+    }
+
+    fn test_endorsement(weight: u8, rating: u8) -> Endorsement {
+        Endorsement {
+            endorser: Pubkey::new_unique(),
+            target: Pubkey::new_unique(),
+            skill: "rust".to_string(),
+            timestamp: 0,
+            bump: 255,
+            weight,
+            rating,
+            reciprocal: false,
+            expires_at: i64::MAX,
+            skill_weight: weight,
+            deposit: 0,
+        }
+    }
+
+    fn test_pair_count() -> PairEndorsementCount {
+        PairEndorsementCount {
+            endorser: Pubkey::new_unique(),
+            target: Pubkey::new_unique(),
+            count: 0,
+            bump: 255,
+        }
+    }
+
+    fn test_job(worker: Pubkey) -> Job {
+        Job {
+            payer: Pubkey::new_unique(),
+            worker,
+            mint: Pubkey::new_unique(),
+            amount: 1_000,
+            job_id: 0,
+            status: JobStatus::Submitted,
+            created_at: 0,
+            bump: 255,
+            vault_bump: 255,
+            milestones: vec![],
+            released_amount: 0,
+            disputed: false,
+        }
+    }
+
+    fn assert_error<T: std::fmt::Debug>(result: Result<T>, expected: AgentVaultError) {
+        assert_eq!(result.unwrap_err(), expected.into());
+    }
+
+    #[test]
+    fn apply_reputation_delta_clamps_extreme_deltas() {
+        let stats = test_registry();
+        let mut agent = test_profile(40);
+
+        assert_eq!(apply_reputation_delta(&mut agent, i16::MAX, &stats), 60);
+        assert_eq!(agent.reputation, DEFAULT_MAX_REPUTATION);
+        assert_eq!(apply_reputation_delta(&mut agent, i16::MIN, &stats), -100);
+        assert_eq!(agent.reputation, 0);
+        assert_eq!(apply_reputation_delta(&mut agent, -1, &stats), 0);
+    }
+
+    #[test]
+    fn apply_reputation_delta_holds_the_verified_floor() {
+        let mut stats = test_registry();
+        stats.verified_floor = 30;
+        let mut agent = test_profile(80);
+        agent.verification_tier = 1;
+
+        assert_eq!(apply_reputation_delta(&mut agent, i16::MIN, &stats), -50);
+        assert_eq!(agent.reputation, 30);
+
+        // An agent already under the floor is held, not lifted
+        agent.reputation = 10;
+        assert_eq!(apply_reputation_delta(&mut agent, -200, &stats), 0);
+        assert_eq!(agent.reputation, 10);
+    }
+
+    #[test]
+    fn apply_reputation_delta_fuzz() {
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for _ in 0..20_000 {
+            let mut stats = test_registry();
+            stats.max_reputation = next() as u8;
+            stats.verified_floor = next() as u8;
+            let old = (next() % (stats.max_reputation as u64 + 1)) as u8;
+            let mut agent = test_profile(old);
+            agent.verification_tier = (next() % 2) as u8;
+            let delta = next() as i16;
+
+            let applied = apply_reputation_delta(&mut agent, delta, &stats);
+            let new = agent.reputation;
+
+            assert_eq!(applied, new as i16 - old as i16);
+            assert!(new <= stats.max_reputation);
+            if delta >= 0 {
+                assert!(new >= old && applied <= delta);
+            } else {
+                assert!(new <= old && applied >= delta);
+                assert!(new >= stats.reputation_floor(&agent).min(old));
+            }
+        }
+    }
+
+    #[test]
+    fn time_decayed_weight_favors_recent_endorsements() {
+        let half_life = 30 * SECONDS_PER_DAY;
+        let fresh = time_decayed_weight(10, 0, half_life);
+        let older = time_decayed_weight(10, half_life / 2, half_life);
+        let old = time_decayed_weight(10, half_life, half_life);
+        let ancient = time_decayed_weight(10, 4 * half_life, half_life);
+
+        assert_eq!(fresh, 10 << 16);
+        assert!(older < fresh && old < older && ancient < old);
+        assert_eq!(old, fresh / 2);
+        assert_eq!(ancient, fresh / 16);
+    }
+
+    #[test]
+    fn time_decayed_weight_without_half_life_is_flat() {
+        assert_eq!(time_decayed_weight(7, 365 * SECONDS_PER_DAY, 0), 7 << 16);
+        assert_eq!(time_decayed_weight(7, -5, SECONDS_PER_DAY), 7 << 16);
+    }
+
+    #[test]
+    fn endorsement_weight_scales_with_endorser_reputation() {
+        assert_eq!(endorsement_weight(2, 0, 3), 1);
+        assert_eq!(endorsement_weight(2, 49, 3), 1);
+        assert_eq!(endorsement_weight(2, 50, 3), 2);
+        assert_eq!(endorsement_weight(2, 99, 3), 2);
+        assert_eq!(endorsement_weight(2, 100, 3), 3);
+    }
+
+    #[test]
+    fn endorsement_weight_scales_with_rating() {
+        assert_eq!(endorsement_weight(2, 50, 1), 1);
+        assert_eq!(endorsement_weight(2, 50, 5), 4);
+        assert_eq!(endorsement_weight(0, 100, 5), 0);
+        assert_eq!(endorsement_weight(u8::MAX, 100, 5), u8::MAX);
+    }
+
+    #[test]
+    fn algorithm_weight_follows_the_registry_algorithm() {
+        let mut stats = test_registry();
+        stats.endorsement_boost = 6;
+
+        assert_eq!(algorithm_weight(&stats, 50, 7, 3), 6);
+
+        stats.algorithm = ReputationAlgorithm::LogarithmicBoost;
+        assert_eq!(algorithm_weight(&stats, 50, 0, 3), 6);
+        assert_eq!(algorithm_weight(&stats, 50, 1, 3), 4);
+        assert_eq!(algorithm_weight(&stats, 50, 3, 3), 3);
+        assert_eq!(algorithm_weight(&stats, 50, 7, 3), 3);
+
+        stats.algorithm = ReputationAlgorithm::WeightedByEndorser;
+        assert_eq!(algorithm_weight(&stats, 25, 0, 3), 3);
+        assert_eq!(algorithm_weight(&stats, 50, 0, 3), 6);
+        assert_eq!(algorithm_weight(&stats, 100, 0, 3), 12);
+    }
+
+    #[test]
+    fn normalize_skills_rejects_case_insensitive_duplicates() {
+        assert_eq!(
+            normalize_skills(vec![" Rust ".to_string(), "solana".to_string()], 10).unwrap(),
+            vec!["rust".to_string(), "solana".to_string()]
+        );
+        assert_error(
+            normalize_skills(vec!["Rust".to_string(), "rust".to_string()], 10),
+            AgentVaultError::DuplicateSkill,
+        );
+        assert_error(
+            normalize_skills((0..11).map(|i| format!("skill{i}")).collect(), 10),
+            AgentVaultError::TooManySkills,
+        );
+    }
+
+    #[test]
+    fn text_limits_count_bytes() {
+        assert!(normalize_name(&"🦀".repeat(8)).is_ok());
+        assert_error(normalize_name(&"🦀".repeat(9)), AgentVaultError::NameTooLong);
+        // Fits before lowercasing, but each 'İ' lowercases to three bytes
+        assert_error(normalize_name(&"İ".repeat(16)), AgentVaultError::NameTooLong);
+
+        assert!(normalize_skill(&"a".repeat(MAX_SKILL_LEN)).is_ok());
+        assert_error(
+            normalize_skill(&"a".repeat(MAX_SKILL_LEN + 1)),
+            AgentVaultError::SkillNameTooLong,
+        );
+    }
+
+    #[test]
+    fn revoke_undoes_the_stored_weight_exactly() {
+        let mut stats = test_registry();
+        let mut target = test_profile(DEFAULT_BASE_REPUTATION);
+        let mut history = ReputationHistory::new(target.agent_id, 255);
+        let mut endorser = test_profile(50);
+
+        let endorsement = test_endorsement(endorsement_weight(stats.endorsement_boost, endorser.reputation, 3), 3);
+        target.endorsements_received += 1;
+        target.endorsement_points += endorsement.weight as u32;
+        apply_reputation_delta(&mut target, endorsement.weight as i16, &stats);
+
+        // The endorser's standing moving on doesn't change what comes off
+        endorser.reputation = 100;
+        let penalty = revoke_penalty(&endorsement, Some(&endorser), &target, &stats);
+        assert_eq!(penalty, endorsement.weight);
+
+        unwind_endorsement(
+            endorsement.weight,
+            penalty,
+            &mut target,
+            &mut history,
+            &mut stats,
+            ReputationReason::Revoked,
+            0,
+        );
+        assert_eq!(target.reputation, DEFAULT_BASE_REPUTATION);
+        assert_eq!(target.endorsements_received, 0);
+        assert_eq!((target.endorsement_points, target.reputation_adjustment), (0, 0));
+    }
+
+    #[test]
+    fn endorse_then_revoke_returns_every_agent_to_baseline() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let algorithms = [
+            ReputationAlgorithm::LinearBoost,
+            ReputationAlgorithm::LogarithmicBoost,
+            ReputationAlgorithm::WeightedByEndorser,
+        ];
+
+        for _ in 0..2_000 {
+            let mut stats = test_registry();
+            stats.endorsement_boost = (next() % 20) as u8;
+            stats.max_reputation = 1 + (next() % 255) as u8;
+            stats.algorithm = algorithms[(next() % 3) as usize];
+            // Often start near the cap, so later boosts clamp to nothing
+            let baseline = match next() % 3 {
+                0 => stats.max_reputation,
+                1 => stats.max_reputation.saturating_sub((next() % 5) as u8),
+                _ => (next() % (stats.max_reputation as u64 + 1)) as u8,
+            };
+            let mut target = test_profile(baseline);
+            let mut history = ReputationHistory::new(target.agent_id, 255);
+
+            // Endorse the way `endorse_skill` does: the weight stored is
+            // what the clamp let through
+            let mut endorsements = Vec::new();
+            for _ in 0..1 + next() % 12 {
+                let endorser = test_profile((next() % 101) as u8);
+                let rating = 1 + (next() % 5) as u8;
+                let boost = algorithm_weight(&stats, endorser.reputation, target.endorsements_received, rating);
+
+                let old_reputation = target.reputation;
+                apply_reputation_delta(&mut target, boost as i16, &stats);
+                let weight = target.reputation - old_reputation;
+                target.endorsements_received += 1;
+                target.endorsement_points += weight as u32;
+                stats.total_endorsements += 1;
+                endorsements.push((test_endorsement(weight, rating), endorser));
+            }
+            assert!(target.reputation <= stats.max_reputation);
+
+            // Revoke in a shuffled order
+            while !endorsements.is_empty() {
+                let (endorsement, endorser) = endorsements.swap_remove((next() % endorsements.len() as u64) as usize);
+                let penalty = revoke_penalty(&endorsement, Some(&endorser), &target, &stats);
+                unwind_endorsement(
+                    endorsement.weight,
+                    penalty,
+                    &mut target,
+                    &mut history,
+                    &mut stats,
+                    ReputationReason::Revoked,
+                    0,
+                );
+            }
+
+            assert_eq!(target.reputation, baseline);
+            assert_eq!(target.endorsements_received, 0);
+            assert_eq!((target.endorsement_points, target.reputation_adjustment), (0, 0));
+            assert_eq!(stats.total_endorsements, 0);
+        }
+    }
+
+    fn board<const N: usize>() -> [LeaderboardEntry; N] {
+        [LeaderboardEntry { agent: Pubkey::default(), reputation: 0 }; N]
+    }
+
+    fn ranking(entries: &[LeaderboardEntry], len: usize) -> Vec<(Pubkey, u8)> {
+        entries[..len].iter().map(|e| (e.agent, e.reputation)).collect()
+    }
+
+    #[test]
+    fn rerank_keeps_the_board_sorted() {
+        let mut entries = board::<4>();
+        let [a, b, c] = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let mut len = rerank(&mut entries, 0, a, Some(50));
+        len = rerank(&mut entries, len, b, Some(70));
+        len = rerank(&mut entries, len, c, Some(50));
+        // Ties keep the incumbent ahead
+        assert_eq!(ranking(&entries, len), vec![(b, 70), (a, 50), (c, 50)]);
+
+        // Re-ranking moves the agent's entry instead of adding another
+        len = rerank(&mut entries, len, c, Some(90));
+        assert_eq!(ranking(&entries, len), vec![(c, 90), (b, 70), (a, 50)]);
+
+        len = rerank(&mut entries, len, b, None);
+        assert_eq!(ranking(&entries, len), vec![(c, 90), (a, 50)]);
+    }
+
+    #[test]
+    fn validate_metadata_uri_accepts_known_schemes() {
+        for uri in [
+            "https://example.com/agent.json",
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U",
+        ] {
+            assert!(validate_metadata_uri(uri, MAX_METADATA_URI_LEN).is_ok(), "{uri}");
+        }
+    }
+
+    #[test]
+    fn validate_metadata_uri_rejects_junk() {
+        assert_error(
+            validate_metadata_uri("http://example.com/agent.json", MAX_METADATA_URI_LEN),
+            AgentVaultError::InvalidMetadataUri,
+        );
+        assert_error(
+            validate_metadata_uri(&format!("https://{}", "a".repeat(MAX_METADATA_URI_LEN)), MAX_METADATA_URI_LEN),
+            AgentVaultError::MetadataUriTooLong,
+        );
+    }
+
+    #[test]
+    fn normalize_skill_canonicalizes_and_restricts_charset() {
+        assert_eq!(normalize_skill("  Rust ").unwrap(), "rust");
+        assert_eq!(normalize_skill("C++").unwrap(), "c++");
+        assert_eq!(normalize_skill("C#").unwrap(), "c#");
+        assert_eq!(normalize_skill("node.js").unwrap(), "node.js");
+        assert_eq!(normalize_skill("smart-contracts").unwrap(), "smart-contracts");
+
+        for skill in ["", "   ", "machine learning", "rust/solana", "ru\u{0}st", "héllo"] {
+            assert_error(normalize_skill(skill), AgentVaultError::InvalidSkillFormat);
+        }
+    }
+
+    #[test]
+    fn grown_profile_fits_its_skill_capacity() {
+        assert_eq!(AgentProfile::space_for(0), 8 + AgentProfile::INIT_SPACE);
+        assert_eq!(AgentProfile::space_for(DEFAULT_SKILL_CAPACITY), 8 + AgentProfile::INIT_SPACE);
+
+        let mut agent = test_profile(DEFAULT_BASE_REPUTATION);
+        assert_eq!(agent.max_skills(), DEFAULT_SKILL_CAPACITY as usize);
+        agent.skill_capacity = MAX_SKILL_CAPACITY;
+        assert_eq!(agent.max_skills(), MAX_SKILL_CAPACITY as usize);
+
+        agent.skills = (0..MAX_SKILL_CAPACITY)
+            .map(|i| format!("{i:0>width$}", width = MAX_SKILL_LEN))
+            .collect();
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= AgentProfile::space_for(MAX_SKILL_CAPACITY));
+    }
+
+    #[test]
+    fn rerank_on_a_full_board() {
+        let mut entries = board::<3>();
+        let [a, b, c, d] = [0; 4].map(|_| Pubkey::new_unique());
+        let mut len = 0;
+        for (agent, reputation) in [(a, 90), (b, 60), (c, 30)] {
+            len = rerank(&mut entries, len, agent, Some(reputation));
+        }
+
+        // Not above the last entry: nothing changes, ties included
+        len = rerank(&mut entries, len, d, Some(30));
+        assert_eq!(ranking(&entries, len), vec![(a, 90), (b, 60), (c, 30)]);
+
+        // Above it: the last entry falls off
+        len = rerank(&mut entries, len, d, Some(31));
+        assert_eq!(ranking(&entries, len), vec![(a, 90), (b, 60), (d, 31)]);
+
+        // An entrant can also take the top slot
+        len = rerank(&mut entries, len, c, Some(100));
+        assert_eq!(ranking(&entries, len), vec![(c, 100), (a, 90), (b, 60)]);
+    }
+
+    #[test]
+    fn rerank_moves_between_the_ends() {
+        let mut entries = board::<3>();
+        let [a, b, c] = [0; 3].map(|_| Pubkey::new_unique());
+        let mut len = 0;
+        for (agent, reputation) in [(a, 90), (b, 60), (c, 30)] {
+            len = rerank(&mut entries, len, agent, Some(reputation));
+        }
+
+        // Bottom to top and back, without losing or duplicating anyone
+        len = rerank(&mut entries, len, c, Some(95));
+        assert_eq!(ranking(&entries, len), vec![(c, 95), (a, 90), (b, 60)]);
+        len = rerank(&mut entries, len, c, Some(1));
+        assert_eq!(ranking(&entries, len), vec![(a, 90), (b, 60), (c, 1)]);
+
+        // Falling below everyone keeps the agent's own slot on a full board
+        len = rerank(&mut entries, len, a, Some(0));
+        assert_eq!(ranking(&entries, len), vec![(b, 60), (c, 1), (a, 0)]);
+    }
+
+    #[test]
+    fn rerank_worst_case_on_the_full_leaderboard() {
+        let mut entries = board::<LEADERBOARD_LEN>();
+        let agents: Vec<Pubkey> = (0..LEADERBOARD_LEN).map(|_| Pubkey::new_unique()).collect();
+        let mut len = 0;
+        for (i, agent) in agents.iter().enumerate() {
+            len = rerank(&mut entries, len, *agent, Some(u8::MAX - (i * 255 / LEADERBOARD_LEN) as u8));
+        }
+        assert_eq!(len, LEADERBOARD_LEN);
+
+        // The last entry jumping to the top shifts every other entry
+        let last = agents[LEADERBOARD_LEN - 1];
+        len = rerank(&mut entries, len, last, Some(u8::MAX));
+        assert_eq!(len, LEADERBOARD_LEN);
+        assert_eq!(entries[0].agent, last);
+        assert!(entries.windows(2).all(|pair| pair[0].reputation >= pair[1].reputation));
+    }
+
+    #[test]
+    fn check_endorser_requires_age() {
+        let mut stats = test_registry();
+        stats.min_endorser_age = SECONDS_PER_DAY;
+        let mut endorser = test_profile(DEFAULT_BASE_REPUTATION);
+        endorser.registered_at = 1_000;
+
+        assert_error(check_endorser(&endorser, &stats, 1_000), AgentVaultError::EndorserTooNew);
+        assert_error(
+            check_endorser(&endorser, &stats, 1_000 + SECONDS_PER_DAY - 1),
+            AgentVaultError::EndorserTooNew,
+        );
+        assert!(check_endorser(&endorser, &stats, 1_000 + SECONDS_PER_DAY).is_ok());
+    }
+
+    #[test]
+    fn check_endorser_requires_reputation() {
+        let mut stats = test_registry();
+        stats.min_endorser_reputation = 60;
+
+        assert_error(
+            check_endorser(&test_profile(59), &stats, 0),
+            AgentVaultError::EndorserReputationTooLow,
+        );
+        assert!(check_endorser(&test_profile(60), &stats, 0).is_ok());
+    }
+
+    #[test]
+    fn repeated_endorsements_from_one_endorser_diminish() {
+        let mut stats = test_registry();
+        stats.endorsement_boost = 16;
+        let mut target = test_profile(DEFAULT_BASE_REPUTATION);
+        let mut pair_count = test_pair_count();
+
+        let mut weights = vec![];
+        for _ in 0..5 {
+            let weight = algorithm_weight(&stats, DEFAULT_BASE_REPUTATION, target.endorsements_received, 3);
+            weights.push(diminished_weight(weight, pair_count.count));
+            add_pair_endorsement(&mut pair_count, &mut target);
+        }
+
+        assert_eq!(weights, vec![16, 8, 4, 2, 1]);
+        assert_eq!(weights.iter().map(|&w| w as u32).sum::<u32>(), 31);
+        assert_eq!(pair_count.count, 5);
+        assert_eq!(target.distinct_endorsers, 1);
+    }
+
+    #[test]
+    fn diminished_weight_runs_out() {
+        assert_eq!(diminished_weight(u8::MAX, 7), 1);
+        assert_eq!(diminished_weight(u8::MAX, 8), 0);
+        assert_eq!(diminished_weight(u8::MAX, u32::MAX), 0);
+    }
+
+    #[test]
+    fn pair_counts_track_distinct_endorsers() {
+        let mut target = test_profile(DEFAULT_BASE_REPUTATION);
+        let mut first = test_pair_count();
+        let mut second = test_pair_count();
+
+        add_pair_endorsement(&mut first, &mut target);
+        add_pair_endorsement(&mut first, &mut target);
+        add_pair_endorsement(&mut second, &mut target);
+        assert_eq!(target.distinct_endorsers, 2);
+
+        remove_pair_endorsement(&mut first, &mut target);
+        assert_eq!(target.distinct_endorsers, 2);
+        remove_pair_endorsement(&mut first, &mut target);
+        assert_eq!(target.distinct_endorsers, 1);
+
+        // Counters from before pair tracking are empty and left alone
+        remove_pair_endorsement(&mut first, &mut target);
+        assert_eq!((first.count, target.distinct_endorsers), (0, 1));
+    }
+
+    #[test]
+    fn normalize_categories_at_the_cap() {
+        let full = vec!["DeFi".to_string(), " gaming".to_string(), "infra".to_string()];
+        assert_eq!(normalize_categories(full.clone()).unwrap(), vec!["defi", "gaming", "infra"]);
+
+        let mut over = full;
+        over.push("social".to_string());
+        assert_error(normalize_categories(over), AgentVaultError::TooManyCategories);
+    }
+
+    #[test]
+    fn normalize_categories_validates_each_category() {
+        let longest = "a".repeat(MAX_CATEGORY_LEN);
+        assert_eq!(normalize_categories(vec![longest.clone()]).unwrap(), vec![longest]);
+
+        assert_error(
+            normalize_categories(vec!["a".repeat(MAX_CATEGORY_LEN + 1)]),
+            AgentVaultError::CategoryTooLong,
+        );
+        assert_error(
+            normalize_categories(vec!["defi".to_string(), "DEFI".to_string()]),
+            AgentVaultError::DuplicateCategory,
+        );
+        assert_error(normalize_categories(vec!["de fi".to_string()]), AgentVaultError::InvalidSkillFormat);
+    }
+
+    #[test]
+    fn skill_leaderboard_ranks_by_skill_score() {
+        let mut skill_board = SkillLeaderboard {
+            skill: [0; 32],
+            entries: board(),
+            len: 0,
+        };
+        let [a, b] = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        // Endorsing raises an agent into the board
+        skill_board.update(a, 3);
+        skill_board.update(b, 5);
+        assert_eq!(ranking(&skill_board.entries, skill_board.len as usize), vec![(b, 5), (a, 3)]);
+
+        // Revoking its last endorsement for the skill drops it out
+        skill_board.update(b, 0);
+        assert_eq!(ranking(&skill_board.entries, skill_board.len as usize), vec![(a, 3)]);
+    }
+
+    #[test]
+    fn full_skill_leaderboard_keeps_the_top_scores() {
+        let mut skill_board = SkillLeaderboard {
+            skill: [0; 32],
+            entries: board(),
+            len: 0,
+        };
+        for _ in 0..SKILL_LEADERBOARD_LEN {
+            skill_board.update(Pubkey::new_unique(), 10);
+        }
+
+        let low = Pubkey::new_unique();
+        skill_board.update(low, 9);
+        assert_eq!(skill_board.len as usize, SKILL_LEADERBOARD_LEN);
+        assert!(skill_board.entries.iter().all(|e| e.agent != low));
+
+        let high = Pubkey::new_unique();
+        skill_board.update(high, 11);
+        assert_eq!(skill_board.len as usize, SKILL_LEADERBOARD_LEN);
+        assert_eq!(skill_board.entries[0].agent, high);
+    }
+
+    #[test]
+    fn registry_limits_default_when_unset() {
+        let mut stats = test_registry();
+        assert_eq!(stats.skill_limit(), DEFAULT_SKILL_CAPACITY as usize);
+        assert_eq!(stats.name_limit(), MAX_NAME_LEN);
+        assert_eq!(stats.uri_limit(), MAX_METADATA_URI_LEN);
+
+        stats.max_skills = 3;
+        stats.max_name_len = 8;
+        stats.max_uri_len = 64;
+        assert_eq!((stats.skill_limit(), stats.name_limit(), stats.uri_limit()), (3, 8, 64));
+    }
+
+    #[test]
+    fn normalize_skills_honors_a_lowered_limit() {
+        let mut stats = test_registry();
+        stats.max_skills = 1;
+        let skills = vec!["rust".to_string(), "go".to_string()];
+
+        assert_error(normalize_skills(skills.clone(), stats.skill_limit()), AgentVaultError::TooManySkills);
+        stats.max_skills = 2;
+        assert_eq!(normalize_skills(skills, stats.skill_limit()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn decayed_reputation_recovers_up_to_what_was_lost() {
+        let mut stats = test_registry();
+        stats.recovery_rate_bps = 5_000;
+        let mut agent = test_profile(90);
+        let mut history = ReputationHistory::new(agent.agent_id, 255);
+
+        let penalty = decay_penalty(agent.reputation, DEFAULT_DECAY_FLOOR, 2, DEFAULT_DECAY_FRACTION_BPS);
+        let applied = apply_reputation_delta(&mut agent, -(penalty as i16), &stats);
+        agent.decayed_amount = applied.unsigned_abs() as u8;
+        assert_eq!((agent.reputation, agent.decayed_amount), (64, 26));
+
+        let mut restored = vec![];
+        while agent.decayed_amount > 0 {
+            recover_reputation(&mut agent, &stats, &mut history, 0);
+            restored.push(agent.reputation);
+        }
+        assert_eq!(restored, vec![77, 83, 86, 88, 89, 90]);
+        let last = history.entries[history.len as usize - 1];
+        assert_eq!((last.reputation, last.reason), (90, ReputationReason::Recovered));
+
+        // Nothing is left to restore
+        recover_reputation(&mut agent, &stats, &mut history, 0);
+        assert_eq!(agent.reputation, 90);
+        assert_eq!(history.len as usize, restored.len());
+    }
+
+    #[test]
+    fn recovery_stops_at_max_reputation() {
+        let mut stats = test_registry();
+        stats.recovery_rate_bps = BPS_DENOMINATOR;
+        let mut agent = test_profile(60);
+        let mut history = ReputationHistory::new(agent.agent_id, 255);
+        agent.decayed_amount = 30;
+        stats.max_reputation = 70;
+
+        recover_reputation(&mut agent, &stats, &mut history, 0);
+        assert_eq!((agent.reputation, agent.decayed_amount), (70, 0));
+    }
+
+    #[test]
+    fn validate_endpoint_checks_scheme_and_length() {
+        assert!(validate_endpoint("").is_ok());
+        assert!(validate_endpoint("https://agent.example.com/rpc").is_ok());
+        assert!(validate_endpoint("wss://agent.example.com/stream").is_ok());
+
+        assert_error(validate_endpoint("http://agent.example.com"), AgentVaultError::InvalidEndpoint);
+        let long = format!("https://{}", "a".repeat(MAX_ENDPOINT_LEN));
+        assert_error(validate_endpoint(&long), AgentVaultError::EndpointTooLong);
+    }
+
+    #[test]
+    fn normalize_protocols_canonicalizes_and_bounds() {
+        let protocols = vec![" JSON-RPC".to_string(), "grpc".to_string()];
+        assert_eq!(normalize_protocols(protocols).unwrap(), vec!["json-rpc", "grpc"]);
+
+        let too_many = vec!["a", "b", "c", "d", "e"].into_iter().map(String::from).collect();
+        assert_error(normalize_protocols(too_many), AgentVaultError::TooManyProtocols);
+        for protocol in ["", "json rpc", "a".repeat(MAX_PROTOCOL_LEN + 1).as_str()] {
+            assert_error(normalize_protocols(vec![protocol.to_string()]), AgentVaultError::InvalidProtocol);
+        }
+        assert_error(
+            normalize_protocols(vec!["grpc".to_string(), "GRPC".to_string()]),
+            AgentVaultError::InvalidProtocol,
+        );
+    }
+
+    #[test]
+    fn completed_then_disputed_jobs_set_the_success_rate() {
+        let stats = test_registry();
+        let mut worker = test_profile(DEFAULT_BASE_REPUTATION);
+        let mut history = ReputationHistory::new(worker.agent_id, 255);
+        assert_eq!(worker.success_rate_bps(), None);
+
+        let mut job = test_job(worker.agent_id);
+        complete_job(&mut job, &mut worker, &mut history, &stats, true, 0);
+        assert_eq!((worker.jobs_completed, worker.jobs_failed), (1, 0));
+        assert_eq!(worker.reputation, DEFAULT_BASE_REPUTATION + DEFAULT_ENDORSEMENT_BOOST);
+        assert_eq!(worker.success_rate_bps(), Some(BPS_DENOMINATOR));
+
+        // A dispute ruled against the worker, as `resolve_dispute` records it
+        worker.jobs_failed += 1;
+        assert_eq!(worker.success_rate_bps(), Some(5_000));
+
+        // Uncredited jobs still count as deliveries, without the boost
+        let mut job = test_job(worker.agent_id);
+        complete_job(&mut job, &mut worker, &mut history, &stats, false, 0);
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(worker.reputation, DEFAULT_BASE_REPUTATION + DEFAULT_ENDORSEMENT_BOOST);
+        assert_eq!(worker.success_rate_bps(), Some(6_666));
+    }
+
+    #[test]
+    fn blended_reputation_mixes_in_external_reputation() {
+        let mut stats = test_registry();
+        stats.external_weight_bps = 5_000;
+        let mut agent = test_profile(DEFAULT_BASE_REPUTATION);
+        agent.external_reputation = 90;
+
+        // Without a source the import is ignored
+        assert_eq!(blended_reputation(&agent, &stats), 50);
+
+        agent.external_source = "github".to_string();
+        assert_eq!(blended_reputation(&agent, &stats), 70);
+
+        stats.external_weight_bps = 0;
+        assert_eq!(blended_reputation(&agent, &stats), 50);
+        stats.external_weight_bps = BPS_DENOMINATOR;
+        assert_eq!(blended_reputation(&agent, &stats), 90);
+    }
+
+    #[test]
+    fn revoke_penalty_follows_the_mode() {
+        let mut stats = test_registry();
+        let endorsement = test_endorsement(2, 3);
+        let mut target = test_profile(60);
+        target.endorsements_received = 1;
+        // The endorser has grown from 50 to 100 since endorsing
+        let endorser = test_profile(100);
+
+        assert_eq!(revoke_penalty(&endorsement, Some(&endorser), &target, &stats), 2);
+
+        stats.revoke_penalty_mode = RevokePenaltyMode::CurrentReputation;
+        assert_eq!(revoke_penalty(&endorsement, Some(&endorser), &target, &stats), 3);
+        // Without the endorser's profile the stored weight stands in
+        assert_eq!(revoke_penalty(&endorsement, None, &target, &stats), 2);
+
+        let reciprocal = Endorsement {
+            reciprocal: true,
+            ..test_endorsement(1, 3)
+        };
+        assert_eq!(revoke_penalty(&reciprocal, Some(&endorser), &target, &stats), 1);
+    }
+
+    #[test]
+    fn current_reputation_penalty_never_takes_reputation_negative() {
+        let mut stats = test_registry();
+        stats.revoke_penalty_mode = RevokePenaltyMode::CurrentReputation;
+        stats.endorsement_boost = 50;
+        let endorsement = test_endorsement(1, 5);
+        let mut target = test_profile(3);
+        target.endorsements_received = 1;
+
+        let penalty = revoke_penalty(&endorsement, Some(&test_profile(100)), &target, &stats);
+        assert!(penalty > target.reputation);
+        apply_reputation_delta(&mut target, -(penalty as i16), &stats);
+        assert_eq!(target.reputation, 0);
+    }
+
+    #[test]
+    fn profile_completeness_needs_uri_bio_and_skills() {
+        let mut agent = test_profile(DEFAULT_BASE_REPUTATION);
+        agent.metadata_uri = "https://example.com/agent.json".to_string();
+        agent.bio = "Indexes Solana programs".to_string();
+        agent.skills = vec!["rust".to_string(), "solana".to_string()];
+        assert!(agent.is_complete());
+
+        let incomplete = [
+            AgentProfile {
+                metadata_uri: String::new(),
+                ..agent.clone()
+            },
+            AgentProfile {
+                bio: String::new(),
+                ..agent.clone()
+            },
+            AgentProfile {
+                skills: vec!["rust".to_string()],
+                ..agent.clone()
+            },
+        ];
+        assert!(incomplete.iter().all(|profile| !profile.is_complete()));
+    }
+
+    #[test]
+    fn verified_agents_hold_the_floor_and_unverified_ones_dont() {
+        let mut stats = test_registry();
+        stats.verified_floor = 40;
+        let mut verified = test_profile(60);
+        verified.verification_tier = 1;
+        let mut unverified = test_profile(60);
+        assert_eq!((stats.reputation_floor(&verified), stats.reputation_floor(&unverified)), (40, 0));
+
+        // Coordinated revocations
+        for _ in 0..3 {
+            apply_reputation_delta(&mut verified, -30, &stats);
+            apply_reputation_delta(&mut unverified, -30, &stats);
+        }
+        assert_eq!((verified.reputation, unverified.reputation), (40, 0));
+
+        // Decay stops at the higher of its own floor and the verified one
+        let mut verified = test_profile(60);
+        verified.verification_tier = 1;
+        let floor = std::cmp::max(DEFAULT_DECAY_FLOOR, stats.reputation_floor(&verified));
+        let penalty = decay_penalty(verified.reputation, floor, 1_000, BPS_DENOMINATOR);
+        apply_reputation_delta(&mut verified, -(penalty as i16), &stats);
+        assert_eq!(verified.reputation, 40);
+    }
+
+    #[test]
+    fn endorsement_credits_regenerate_hourly_up_to_the_limit() {
+        let stats = test_registry();
+        let mut agent = test_profile(DEFAULT_BASE_REPUTATION);
+        let start = 1_000_000;
+
+        // A profile starts with a full pool
+        spend_endorsement_credits(&mut agent, &stats, DEFAULT_MAX_ENDORSEMENT_CREDITS, start).unwrap();
+        assert_error(
+            spend_endorsement_credits(&mut agent, &stats, 1, start + SECONDS_PER_HOUR - 1),
+            AgentVaultError::NoEndorsementCredits,
+        );
+
+        // An hour and a half earns one credit, and the half hour carries
+        // over towards the next
+        spend_endorsement_credits(&mut agent, &stats, 1, start + 3 * SECONDS_PER_HOUR / 2).unwrap();
+        spend_endorsement_credits(&mut agent, &stats, 1, start + 2 * SECONDS_PER_HOUR).unwrap();
+        assert_eq!(
+            (agent.endorsement_credits, agent.credits_updated_at),
+            (0, start + 2 * SECONDS_PER_HOUR)
+        );
+
+        // A long idle stretch refills only up to the limit
+        let later = start + 100 * SECONDS_PER_HOUR;
+        spend_endorsement_credits(&mut agent, &stats, 1, later).unwrap();
+        assert_eq!(
+            (agent.endorsement_credits, agent.credits_updated_at),
+            (DEFAULT_MAX_ENDORSEMENT_CREDITS - 1, later)
+        );
+    }
+
+    #[test]
+    fn endorsement_credits_follow_the_registry_rate() {
+        let mut stats = test_registry();
+        stats.credits_per_hour = 4;
+        let mut agent = test_profile(DEFAULT_BASE_REPUTATION);
+        let start = 1_000_000;
+        spend_endorsement_credits(&mut agent, &stats, DEFAULT_MAX_ENDORSEMENT_CREDITS, start).unwrap();
+
+        // One credit every 15 minutes; the 5 minutes past it carry over
+        spend_endorsement_credits(&mut agent, &stats, 1, start + 20 * 60).unwrap();
+        assert_eq!(agent.credits_updated_at, start + 15 * 60);
+        spend_endorsement_credits(&mut agent, &stats, 1, start + 30 * 60).unwrap();
+        assert_error(
+            spend_endorsement_credits(&mut agent, &stats, 1, start + 44 * 60),
+            AgentVaultError::NoEndorsementCredits,
+        );
+    }
+
+    #[test]
+    fn validate_region_accepts_only_known_regions() {
+        assert!(REGIONS.iter().all(|region| validate_region(region).is_ok()));
+        // Clearing the region
+        assert!(validate_region("").is_ok());
+
+        for region in ["US-EAST", " us-east", "us-east-1", "mars"] {
+            assert_error(validate_region(region), AgentVaultError::InvalidRegion);
+        }
+    }
+
+    #[test]
+    fn rarity_weight_scales_inversely_with_skill_popularity() {
+        let mut frequency = SkillFrequency {
+            entries: vec![],
+            bump: 255,
+        };
+        let declare = |frequency: &mut SkillFrequency, skill: &str, agents: usize| {
+            (0..agents).for_each(|_| frequency.add(skill));
+        };
+        declare(&mut frequency, "rust", RARITY_NEUTRAL_AGENTS as usize);
+        declare(&mut frequency, "zig", 1);
+        declare(&mut frequency, "move", 8);
+        declare(&mut frequency, "python", 20);
+        declare(&mut frequency, "javascript", 100);
+
+        assert_eq!(rarity_weight(6, Some(&frequency), "rust"), 6);
+        assert_eq!(rarity_weight(6, Some(&frequency), "move"), 7);
+        assert_eq!(rarity_weight(6, Some(&frequency), "python"), 3);
+        // Clamped at both ends
+        assert_eq!(rarity_weight(6, Some(&frequency), "zig"), 12);
+        assert_eq!(rarity_weight(6, Some(&frequency), "javascript"), 3);
+        assert_eq!(rarity_weight(200, Some(&frequency), "zig"), u8::MAX);
+
+        // Untracked skills, or no frequency account at all, keep the weight
+        assert_eq!(rarity_weight(6, Some(&frequency), "cobol"), 6);
+        assert_eq!(rarity_weight(6, None, "zig"), 6);
+        frequency.remove("zig");
+        assert_eq!(frequency.agents("zig"), None);
+        assert_eq!(rarity_weight(6, Some(&frequency), "zig"), 6);
+    }
+
+    #[test]
+    fn decay_takes_in_proportion_to_the_height_above_the_floor() {
+        let decay = |reputation, intervals| {
+            decay_penalty(reputation, DEFAULT_DECAY_FLOOR, intervals, DEFAULT_DECAY_FRACTION_BPS)
+        };
+
+        // 90 and 45 above the floor over the same stretch
+        assert_eq!((decay(100, 1), decay(55, 1)), (9, 5));
+        assert_eq!((decay(100, 3), decay(55, 3)), (26, 13));
+
+        // A point per interval at least, and nothing at or below the floor
+        assert_eq!(decay(DEFAULT_DECAY_FLOOR + 1, 3), 1);
+        assert_eq!(decay(DEFAULT_DECAY_FLOOR, 3), 0);
+        assert_eq!(decay(DEFAULT_DECAY_FLOOR - 5, 3), 0);
+        assert_eq!(decay(100, 1_000), 100 - DEFAULT_DECAY_FLOOR);
+
+        // A larger fraction bites harder
+        assert_eq!(decay_penalty(100, DEFAULT_DECAY_FLOOR, 1, 2_500), 23);
+    }
+
+    #[test]
+    fn private_agents_hide_scores_and_changes_in_events() {
+        let mut agent = test_profile(70);
+        assert_eq!(agent.public_reputation(60, 70), (60, 70));
+        assert_eq!(agent.public_score(4), 4);
+
+        agent.private_reputation = true;
+        assert_eq!(agent.public_reputation(60, 70), (0, 0));
+        assert_eq!(agent.public_score(4), 0);
+    }
+
+    #[test]
+    fn profile_layout_ends_after_the_fields_of_its_version() {
+        let mut agent = test_profile(60);
+        agent.bio = "an agent".to_string();
+        agent.version = PROFILE_VERSION;
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+        assert_eq!(profile_layout(&data).unwrap(), (PROFILE_VERSION, data.len()));
+
+        // Under version 1 everything from `skill_capacity` on is left over
+        agent.version = 1;
+        data.clear();
+        agent.try_serialize(&mut data).unwrap();
+        let (version, len) = profile_layout(&data).unwrap();
+        assert_eq!(version, 1);
+
+        data[len..].fill(0);
+        let migrated = AgentProfile::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!((migrated.reputation, migrated.version), (60, 1));
+        assert_eq!(migrated.skill_capacity, 0);
+        assert!(migrated.bio.is_empty());
+    }
+
+    #[test]
+    fn blended_reputation_keeps_stored_weights_and_penalties() {
+        let stats = test_registry();
+        let mut agent = test_profile(DEFAULT_BASE_REPUTATION);
+
+        // Two endorsements of stored weights 3 and 5, then a slash of 10
+        for weight in [3, 5] {
+            agent.endorsements_received += 1;
+            agent.endorsement_points += apply_reputation_delta(&mut agent, weight, &stats) as u32;
+        }
+        adjust_reputation(&mut agent, -10, &stats);
+        assert_eq!(agent.reputation, 48);
+        assert_eq!(blended_reputation(&agent, &stats), 48);
+
+        // Decay and job credit carry over the same way
+        adjust_reputation(&mut agent, -6, &stats);
+        adjust_reputation(&mut agent, stats.endorsement_boost as i16, &stats);
+        assert_eq!(agent.reputation, 44);
+        assert_eq!(blended_reputation(&agent, &stats), 44);
+
+        // A revocation that takes more than the stored weight keeps the excess
+        take_endorsement_weight(&mut agent, 3, 5, &stats);
+        assert_eq!(agent.endorsement_points, 5);
+        assert_eq!(agent.reputation, 39);
+        assert_eq!(blended_reputation(&agent, &stats), 39);
+    }
 }
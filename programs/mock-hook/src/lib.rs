@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+declare_id!("MockHook11111111111111111111111111111111111");
+
+/// Stand-in for a registration hook (see agentvault's
+/// `invoke_registration_hook`), used by the tests. It counts the
+/// registrations it is called for, and fails them while `reject` is set.
+#[program]
+pub mod mock_hook {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        ctx.accounts.state.bump = ctx.bumps.state;
+        Ok(())
+    }
+
+    pub fn set_reject(ctx: Context<SetReject>, reject: bool) -> Result<()> {
+        ctx.accounts.state.reject = reject;
+        Ok(())
+    }
+
+    /// The hook ABI: the new profile, its owner as signer, then this
+    /// program's own accounts
+    pub fn on_agent_registered(ctx: Context<OnAgentRegistered>, profile: Pubkey) -> Result<()> {
+        require_keys_eq!(ctx.accounts.profile.key(), profile, MockHookError::ProfileMismatch);
+
+        let state = &mut ctx.accounts.state;
+        require!(!state.reject, MockHookError::Rejected);
+        state.registrations += 1;
+        state.last_profile = profile;
+        state.last_owner = ctx.accounts.owner.key();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + HookState::INIT_SPACE, seeds = [b"state"], bump)]
+    pub state: Account<'info, HookState>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReject<'info> {
+    #[account(mut, seeds = [b"state"], bump = state.bump)]
+    pub state: Account<'info, HookState>,
+}
+
+#[derive(Accounts)]
+pub struct OnAgentRegistered<'info> {
+    /// CHECK: the profile being registered, compared with the argument
+    pub profile: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"state"], bump = state.bump)]
+    pub state: Account<'info, HookState>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HookState {
+    pub registrations: u64,
+    pub last_profile: Pubkey,
+    pub last_owner: Pubkey,
+    pub reject: bool,
+    pub bump: u8,
+}
+
+#[error_code]
+pub enum MockHookError {
+    #[msg("The profile account doesn't match the argument")]
+    ProfileMismatch,
+    #[msg("The hook is set to reject registrations")]
+    Rejected,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+declare_id!("MockPersonhood11111111111111111111111111111");
+
+/// Stand-in for a proof-of-personhood verifier (see agentvault's
+/// `check_personhood`), used by the tests. It issues one credential per
+/// holder, which can be revoked, and accepts it only from that holder.
+#[program]
+pub mod mock_personhood {
+    use super::*;
+
+    pub fn issue(ctx: Context<Issue>, holder: Pubkey) -> Result<()> {
+        let credential = &mut ctx.accounts.credential;
+        credential.holder = holder;
+        credential.bump = ctx.bumps.credential;
+        Ok(())
+    }
+
+    pub fn revoke(ctx: Context<Revoke>) -> Result<()> {
+        ctx.accounts.credential.revoked = true;
+        Ok(())
+    }
+
+    /// The verifier ABI: the credential, then its holder as signer
+    pub fn verify_personhood(ctx: Context<VerifyPersonhood>, owner: Pubkey) -> Result<()> {
+        let credential = &ctx.accounts.credential;
+        require_keys_eq!(ctx.accounts.owner.key(), owner, MockPersonhoodError::NotHolder);
+        require_keys_eq!(credential.holder, owner, MockPersonhoodError::NotHolder);
+        require!(!credential.revoked, MockPersonhoodError::Revoked);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct Issue<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Credential::INIT_SPACE,
+        seeds = [b"credential", holder.as_ref()],
+        bump
+    )]
+    pub credential: Account<'info, Credential>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Revoke<'info> {
+    #[account(mut, seeds = [b"credential", credential.holder.as_ref()], bump = credential.bump)]
+    pub credential: Account<'info, Credential>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPersonhood<'info> {
+    #[account(seeds = [b"credential", credential.holder.as_ref()], bump = credential.bump)]
+    pub credential: Account<'info, Credential>,
+
+    pub owner: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Credential {
+    pub holder: Pubkey,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+#[error_code]
+pub enum MockPersonhoodError {
+    #[msg("The credential belongs to another wallet")]
+    NotHolder,
+    #[msg("The credential has been revoked")]
+    Revoked,
+}
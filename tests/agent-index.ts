@@ -0,0 +1,44 @@
+import { expect } from 'chai';
+import {
+  agentIndexPda,
+  deregister,
+  ensureRegistry,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('agent index', () => {
+  before(ensureRegistry);
+
+  it('assigns sequential indexes without gaps', async () => {
+    const first = (await program.account.registryStats.fetch(registryStatsPda)).nextAgentIndex;
+
+    const wallets = [await newWallet(), await newWallet(), await newWallet()];
+    for (const wallet of wallets) {
+      await registerAgent(wallet);
+    }
+
+    for (const [i, wallet] of wallets.entries()) {
+      const index = first.addn(i);
+      const entry = await program.account.agentIndex.fetch(agentIndexPda(index));
+      expect(entry.index.eq(index)).to.be.true;
+      expect(entry.agent.equals(wallet.publicKey)).to.be.true;
+      expect(entry.deregisteredAt.toNumber()).to.equal(0);
+    }
+    const next = (await program.account.registryStats.fetch(registryStatsPda)).nextAgentIndex;
+    expect(next.eq(first.addn(wallets.length))).to.be.true;
+  });
+
+  it('leaves a tombstone when an agent deregisters', async () => {
+    const owner = await newWallet();
+    const { index } = await program.account.agentProfile.fetch(await registerAgent(owner));
+
+    await deregister(owner.publicKey, owner);
+
+    const entry = await program.account.agentIndex.fetch(agentIndexPda(index!));
+    expect(entry.agent.equals(owner.publicKey)).to.be.true;
+    expect(entry.deregisteredAt.toNumber()).to.be.above(0);
+  });
+});
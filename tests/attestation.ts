@@ -0,0 +1,59 @@
+import { expect } from 'chai';
+import {
+  authority,
+  endorse,
+  ensureRegistry,
+  eventOf,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('endorsement attestations', () => {
+  before(ensureRegistry);
+
+  async function endorsement() {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+    return endorse(endorser, target.publicKey, 'rust', 4);
+  }
+
+  it('emits the stored endorsement as an attestation', async () => {
+    const address = await endorsement();
+
+    const signature = await program.methods
+      .attestEndorsement()
+      .accountsPartial({ endorsement: address, registryStats: registryStatsPda })
+      .rpc({ commitment: 'confirmed' });
+
+    const stored = await program.account.endorsement.fetch(address);
+    const event = await eventOf(signature, 'EndorsementAttestation');
+    expect(event.issuer.equals(program.programId)).to.be.true;
+    expect(event.endorsement.equals(address)).to.be.true;
+    expect(event.endorser.equals(stored.endorser)).to.be.true;
+    expect(event.target.equals(stored.target)).to.be.true;
+    expect(event.skill).to.equal(stored.skill);
+    expect(event.rating).to.equal(4);
+    expect(event.endorsedAt.eq(stored.timestamp)).to.be.true;
+    expect(event.expiresAt.eq(stored.expiresAt)).to.be.true;
+  });
+
+  it('refuses while the registry is frozen', async () => {
+    const address = await endorsement();
+    const freeze = { registryStats: registryStatsPda, authority: authority.publicKey };
+
+    await program.methods.emergencyFreeze(0).accountsPartial(freeze).rpc();
+    try {
+      await expectError(
+        program.methods.attestEndorsement().accountsPartial({ endorsement: address, registryStats: registryStatsPda }).rpc(),
+        'RegistryFrozen'
+      );
+    } finally {
+      await program.methods.unfreeze().accountsPartial(freeze).rpc();
+    }
+  });
+});
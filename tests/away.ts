@@ -0,0 +1,90 @@
+import { BN, Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import {
+  clockTime,
+  fails,
+  historyPda,
+  registerAgent,
+  registryStatsPda,
+  startBankrun,
+  tick,
+} from './helpers';
+
+const DAY = 24 * 60 * 60;
+// `DECAY_INTERVAL_DAYS` and `MAX_AWAY_DURATION`
+const DECAY_INTERVAL = 30 * DAY;
+const MAX_AWAY_DURATION = 90 * DAY;
+
+// Decay is measured in 30-day intervals, so these tests run in bankrun
+describe('away mode', () => {
+  const away = Keypair.generate();
+  const present = Keypair.generate();
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let awayProfile: PublicKey;
+  let presentProfile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([away, present]));
+    awayProfile = await registerAgent(away, {}, program);
+    presentProfile = await registerAgent(present, {}, program);
+  });
+
+  function setAway(awayUntil: number) {
+    return program.methods
+      .setAway(new BN(awayUntil))
+      .accountsPartial({
+        agentProfile: awayProfile,
+        registryStats: registryStatsPda,
+        owner: away.publicKey,
+        skillFrequency: null,
+      })
+      .signers([away])
+      .rpc();
+  }
+
+  async function decay(owner: Keypair, profile: PublicKey): Promise<void> {
+    await tick(context);
+    await program.methods
+      .decayReputation()
+      .accountsPartial({
+        agentProfile: profile,
+        reputationHistory: historyPda(owner.publicKey),
+        leaderboard: null,
+        registryStats: registryStatsPda,
+      })
+      .rpc();
+  }
+
+  const reputation = async (profile: PublicKey) => (await program.account.agentProfile.fetch(profile)).reputation;
+
+  it('caps how far ahead an agent can be away', async () => {
+    expect(await fails(setAway((await clockTime(context)) + MAX_AWAY_DURATION + DAY))).to.be.true;
+  });
+
+  it('suppresses decay during the window and resumes it after', async () => {
+    const awayUntil = (await clockTime(context)) + 2 * DECAY_INTERVAL;
+    await setAway(awayUntil);
+    const start = await reputation(awayProfile);
+
+    // One interval in: the present agent decays, the away one doesn't
+    await tick(context, DECAY_INTERVAL + DAY);
+    await decay(present, presentProfile);
+    expect(await reputation(presentProfile)).to.be.below(start);
+    expect(await fails(decay(away, awayProfile))).to.be.true;
+    expect(await reputation(awayProfile)).to.equal(start);
+
+    // Still inside the window
+    await tick(context, DECAY_INTERVAL - 2 * DAY);
+    expect(await fails(decay(away, awayProfile))).to.be.true;
+
+    // A full interval after the window ends
+    await tick(context, DECAY_INTERVAL + 2 * DAY);
+    expect(await clockTime(context)).to.be.at.least(awayUntil + DECAY_INTERVAL);
+    await decay(away, awayProfile);
+    expect(await reputation(awayProfile)).to.be.below(start);
+  });
+});
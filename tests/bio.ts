@@ -0,0 +1,29 @@
+import { expect } from 'chai';
+import { ensureRegistry, expectError, newWallet, program, registerAgent } from './helpers';
+
+// `MAX_BIO_LEN`, which like the name limit counts UTF-8 bytes
+const MAX_BIO_LEN = 160;
+
+describe('bio', () => {
+  before(ensureRegistry);
+
+  it('is optional at registration', async () => {
+    const profile = await registerAgent(await newWallet());
+
+    expect((await program.account.agentProfile.fetch(profile)).bio).to.equal('');
+  });
+
+  it('accepts a bio of exactly the byte limit', async () => {
+    // 80 two-byte characters
+    const bio = 'é'.repeat(MAX_BIO_LEN / 2);
+    const profile = await registerAgent(await newWallet(), { bio });
+
+    expect((await program.account.agentProfile.fetch(profile)).bio).to.equal(bio);
+  });
+
+  it('rejects a bio over the byte limit', async () => {
+    await expectError(registerAgent(await newWallet(), { bio: 'a'.repeat(MAX_BIO_LEN + 1) }), 'BioTooLong');
+    // Within the limit in characters, over it in bytes
+    await expectError(registerAgent(await newWallet(), { bio: '🦀'.repeat(MAX_BIO_LEN / 2) }), 'BioTooLong');
+  });
+});
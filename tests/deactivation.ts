@@ -0,0 +1,71 @@
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import { fails, registerAgent, registryStatsPda, startBankrun, tick } from './helpers';
+
+// `DEACTIVATION_GRACE_PERIOD`
+const GRACE_PERIOD = 7 * 24 * 60 * 60;
+
+// The grace period is a week, so these tests run in bankrun
+describe('deactivation grace period', () => {
+  const owner = Keypair.generate();
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let profile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([owner]));
+    profile = await registerAgent(owner, {}, program);
+  });
+
+  async function requestDeactivation(): Promise<void> {
+    await tick(context);
+    await program.methods
+      .requestDeactivation()
+      .accountsPartial({ agentProfile: profile, registryStats: registryStatsPda, owner: owner.publicKey, skillFrequency: null })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function reactivate(): Promise<void> {
+    await tick(context);
+    await program.methods
+      .reactivate()
+      .accountsPartial({ agentProfile: profile, registryStats: registryStatsPda, signer: owner.publicKey })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function finalize(): Promise<void> {
+    await tick(context);
+    await program.methods
+      .finalizeDeactivation()
+      .accountsPartial({ agentProfile: profile, registryStats: registryStatsPda })
+      .rpc();
+  }
+
+  const agent = () => program.account.agentProfile.fetch(profile);
+
+  it('suspends at once and can be reactivated within the grace period', async () => {
+    await requestDeactivation();
+    expect((await agent()).status).to.deep.equal({ suspended: {} });
+    expect(await fails(finalize())).to.be.true;
+
+    await tick(context, GRACE_PERIOD - 60);
+    await reactivate();
+    const reactivated = await agent();
+    expect(reactivated.status).to.deep.equal({ active: {} });
+    expect(reactivated.reactivateBefore.toNumber()).to.equal(0);
+  });
+
+  it('retires the agent once the grace period runs out', async () => {
+    await requestDeactivation();
+    await tick(context, GRACE_PERIOD);
+
+    expect(await fails(reactivate())).to.be.true;
+    await finalize();
+    expect((await agent()).status).to.deep.equal({ retired: {} });
+  });
+});
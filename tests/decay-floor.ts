@@ -0,0 +1,61 @@
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import { fails, historyPda, registerAgent, registryStatsPda, startBankrun, tick } from './helpers';
+
+// `DECAY_INTERVAL_DAYS`
+const DECAY_INTERVAL = 30 * 24 * 60 * 60;
+
+// Decay is measured in 30-day intervals, so these tests run in bankrun
+describe('decay floor', () => {
+  const owner = Keypair.generate();
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let profile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([owner]));
+    profile = await registerAgent(owner, {}, program);
+  });
+
+  function setDecayFloor(decayFloor: number) {
+    return program.methods
+      .setDecayFloor(decayFloor)
+      .accountsPartial({ registryStats: registryStatsPda, authority: context.payer.publicKey })
+      .rpc();
+  }
+
+  function decay() {
+    return program.methods
+      .decayReputation()
+      .accountsPartial({
+        agentProfile: profile,
+        reputationHistory: historyPda(owner.publicKey),
+        leaderboard: null,
+        registryStats: registryStatsPda,
+      })
+      .rpc();
+  }
+
+  const reputation = async () => (await program.account.agentProfile.fetch(profile)).reputation;
+
+  it('defaults to 10 and rejects a floor above max_reputation', async () => {
+    const stats = await program.account.registryStats.fetch(registryStatsPda);
+    expect(stats.decayFloor).to.equal(10);
+    expect(await fails(setDecayFloor(stats.maxReputation + 1))).to.be.true;
+  });
+
+  it('stops decay at the configured floor', async () => {
+    const start = await reputation();
+    await setDecayFloor(start);
+    await tick(context, 12 * DECAY_INTERVAL);
+    expect(await fails(decay())).to.be.true;
+
+    await setDecayFloor(start - 5);
+    await tick(context);
+    await decay();
+    expect(await reputation()).to.equal(start - 5);
+  });
+});
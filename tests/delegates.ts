@@ -0,0 +1,52 @@
+import { expect } from 'chai';
+import {
+  delegatePda,
+  deregister,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('delegates', () => {
+  before(ensureRegistry);
+
+  it('lets a delegate heartbeat but not deregister', async () => {
+    const owner = await newWallet();
+    const delegate = await newWallet();
+    const profile = await registerAgent(owner);
+
+    await program.methods
+      .setDelegate(delegate.publicKey)
+      .accountsPartial({
+        delegate: delegatePda(owner.publicKey),
+        agentProfile: profile,
+        registryStats: registryStatsPda,
+        owner: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+
+    await program.methods
+      .heartbeat()
+      .accountsPartial({
+        agentProfile: profile,
+        delegate: delegatePda(owner.publicKey),
+        registryStats: registryStatsPda,
+        signer: delegate.publicKey,
+        reputationHistory: null,
+        skillFrequency: null,
+      })
+      .signers([delegate])
+      .rpc();
+    expect((await program.account.agentProfile.fetch(profile)).lastHeartbeatAt.toNumber()).to.be.above(0);
+
+    await expectError(deregister(owner.publicKey, delegate), 'Unauthorized');
+    expect(await program.account.agentProfile.fetchNullable(profile)).to.not.be.null;
+
+    await deregister(owner.publicKey, owner);
+    expect(await program.account.agentProfile.fetchNullable(profile)).to.be.null;
+  });
+});
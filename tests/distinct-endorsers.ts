@@ -0,0 +1,26 @@
+import { expect } from 'chai';
+import { agentPda, endorse, ensureRegistry, newWallet, program, registerAgent } from './helpers';
+
+describe('distinct endorsers', () => {
+  before(ensureRegistry);
+
+  it('counts an endorser once across skills', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    const other = await newWallet();
+    await registerAgent(target, { skills: ['rust', 'go'] });
+    await registerAgent(endorser);
+    await registerAgent(other);
+
+    await endorse(endorser, target.publicKey, 'rust');
+    await endorse(endorser, target.publicKey, 'go');
+    let profile = await program.account.agentProfile.fetch(agentPda(target.publicKey));
+    expect(profile.endorsementsReceived).to.equal(2);
+    expect(profile.distinctEndorsers).to.equal(1);
+
+    await endorse(other, target.publicKey, 'rust');
+    profile = await program.account.agentProfile.fetch(agentPda(target.publicKey));
+    expect(profile.endorsementsReceived).to.equal(3);
+    expect(profile.distinctEndorsers).to.equal(2);
+  });
+});
@@ -0,0 +1,56 @@
+import { BN } from '@coral-xyz/anchor';
+import { LAMPORTS_PER_SOL } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  asAuthority,
+  endorse,
+  ensureRegistry,
+  newWallet,
+  program,
+  provider,
+  registerAgent,
+  registryStatsPda,
+  revoke,
+} from './helpers';
+
+describe('endorsement deposit', () => {
+  const deposit = new BN(LAMPORTS_PER_SOL / 100);
+  let previousDeposit: BN;
+  let cooldown: BN;
+
+  before(async () => {
+    await ensureRegistry();
+    const stats = await program.account.registryStats.fetch(registryStatsPda);
+    previousDeposit = stats.endorsementDeposit;
+    cooldown = stats.revokeCooldown;
+    await program.methods.setEndorsementDeposit(deposit).accountsPartial(asAuthority()).rpc();
+    await program.methods.setRevokeCooldown(new BN(0)).accountsPartial(asAuthority()).rpc();
+  });
+
+  after(async () => {
+    await program.methods.setEndorsementDeposit(previousDeposit).accountsPartial(asAuthority()).rpc();
+    await program.methods.setRevokeCooldown(cooldown).accountsPartial(asAuthority()).rpc();
+  });
+
+  it('returns the full deposit on an honest revoke', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+
+    const endorsement = await endorse(endorser, target.publicKey);
+    const info = await provider.connection.getAccountInfo(endorsement);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(info!.data.length);
+    expect(info!.lamports).to.equal(rent + deposit.toNumber());
+    expect((await program.account.endorsement.fetch(endorsement)).deposit.eq(deposit)).to.be.true;
+
+    // The provider wallet pays the fees, so the endorser's balance moves by
+    // exactly what the closed endorsement held
+    const before = await provider.connection.getBalance(endorser.publicKey);
+    await revoke(endorser, target.publicKey);
+    const after = await provider.connection.getBalance(endorser.publicKey);
+
+    expect(after - before).to.equal(info!.lamports);
+    expect(await provider.connection.getAccountInfo(endorsement)).to.be.null;
+  });
+});
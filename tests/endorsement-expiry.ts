@@ -0,0 +1,81 @@
+import { BN, Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import {
+  endorseAccounts,
+  fails,
+  registerAgent,
+  revokeAccounts,
+  startBankrun,
+  tick,
+} from './helpers';
+
+const DAY = 24 * 60 * 60;
+const HOUR = 60 * 60;
+// `MAX_ENDORSEMENT_VALIDITY`
+const MAX_ENDORSEMENT_VALIDITY = 2 * 365 * DAY;
+
+// Expiry is time based, so these tests run in bankrun
+describe('endorsement expiry', () => {
+  const target = Keypair.generate();
+  const endorser = Keypair.generate();
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let profile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([target, endorser]));
+    profile = await registerAgent(target, {}, program);
+    await registerAgent(endorser, {}, program);
+    // Past the default minimum endorser age
+    await tick(context, DAY + 1);
+  });
+
+  function endorse(validFor: number | null) {
+    return program.methods
+      .endorseSkill('rust', 3, validFor === null ? null : new BN(validFor))
+      .accountsPartial(endorseAccounts(endorser.publicKey, endorser.publicKey, target.publicKey, 'rust'))
+      .signers([endorser])
+      .rpc();
+  }
+
+  async function expire(): Promise<void> {
+    const { endorsement, targetAgent, reputationHistory, skillCount, skillReputation, pairCount, endorserIndex } =
+      revokeAccounts(endorser.publicKey, target.publicKey, 'rust');
+    await tick(context);
+    await program.methods
+      .expireEndorsement()
+      .accountsPartial({
+        endorsement,
+        targetAgent,
+        reputationHistory,
+        skillCount,
+        skillReputation,
+        pairCount,
+        endorserIndex,
+        endorser: endorser.publicKey,
+      })
+      .rpc();
+  }
+
+  it('rejects a validity that is not positive or too long', async () => {
+    expect(await fails(endorse(0))).to.be.true;
+    expect(await fails(endorse(MAX_ENDORSEMENT_VALIDITY + 1))).to.be.true;
+  });
+
+  it('expires a short-lived endorsement once its validity runs out', async () => {
+    const base = (await program.account.agentProfile.fetch(profile)).reputation;
+    await endorse(HOUR);
+    expect((await program.account.agentProfile.fetch(profile)).reputation).to.be.above(base);
+
+    expect(await fails(expire())).to.be.true;
+
+    await tick(context, HOUR + 1);
+    await expire();
+    const agent = await program.account.agentProfile.fetch(profile);
+    expect(agent.reputation).to.equal(base);
+    expect(agent.endorsementsReceived).to.equal(0);
+  });
+});
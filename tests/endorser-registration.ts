@@ -0,0 +1,48 @@
+import { expect } from 'chai';
+import {
+  endorse,
+  endorseAccounts,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+} from './helpers';
+
+describe('endorsements from unregistered wallets', () => {
+  before(ensureRegistry);
+
+  it('accepts an endorsement from a registered agent', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+
+    const endorsement = await endorse(endorser, target.publicKey);
+
+    const stored = await program.account.endorsement.fetch(endorsement);
+    expect(stored.endorser.toBase58()).to.equal(endorser.publicKey.toBase58());
+  });
+
+  it('rejects a wallet without a profile', async () => {
+    const target = await newWallet();
+    const stranger = await newWallet();
+    await registerAgent(target);
+
+    await expectError(endorse(stranger, target.publicKey), 'AccountNotInitialized');
+  });
+
+  it("rejects a wallet passing another agent's profile", async () => {
+    const target = await newWallet();
+    const registered = await newWallet();
+    const stranger = await newWallet();
+    await registerAgent(target);
+    await registerAgent(registered);
+
+    const accounts = endorseAccounts(stranger.publicKey, registered.publicKey, target.publicKey, 'rust');
+    await expectError(
+      program.methods.endorseSkill('rust', 3, null).accountsPartial(accounts).signers([stranger]).rpc(),
+      'EndorserNotRegistered'
+    );
+  });
+});
@@ -0,0 +1,50 @@
+import { expect } from 'chai';
+import {
+  agentPda,
+  asAuthority,
+  endorse,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('endorser skill requirement', () => {
+  let required: boolean;
+
+  before(async () => {
+    await ensureRegistry();
+    required = (await program.account.registryStats.fetch(registryStatsPda)).requireEndorserHasSkill;
+  });
+
+  afterEach(async () => {
+    await program.methods.setRequireEndorserHasSkill(required).accountsPartial(asAuthority()).rpc();
+  });
+
+  async function agents() {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target, { skills: ['rust', 'go'] });
+    await registerAgent(endorser, { skills: ['rust'] });
+    return { target, endorser };
+  }
+
+  it('lets anyone endorse any skill when off', async () => {
+    const { target, endorser } = await agents();
+    await program.methods.setRequireEndorserHasSkill(false).accountsPartial(asAuthority()).rpc();
+
+    await endorse(endorser, target.publicKey, 'go');
+
+    expect((await program.account.agentProfile.fetch(agentPda(target.publicKey))).endorsementsReceived).to.equal(1);
+  });
+
+  it('only lets endorsers vouch for skills they declare when on', async () => {
+    const { target, endorser } = await agents();
+    await program.methods.setRequireEndorserHasSkill(true).accountsPartial(asAuthority()).rpc();
+
+    await expectError(endorse(endorser, target.publicKey, 'go'), 'EndorserLacksSkill');
+    await endorse(endorser, target.publicKey, 'rust');
+  });
+});
@@ -0,0 +1,44 @@
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ensureRegistry, expectError, newWallet, program, registerAgent, registryStatsPda } from './helpers';
+
+describe('endpoint', () => {
+  before(ensureRegistry);
+
+  function setEndpoint(owner: Keypair, profile: PublicKey, endpoint: string, protocols: string[]) {
+    return program.methods
+      .setEndpoint(endpoint, protocols)
+      .accountsPartial({
+        agentProfile: profile,
+        delegate: null,
+        registryStats: registryStatsPda,
+        signer: owner.publicKey,
+        reputationHistory: null,
+        skillFrequency: null,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  it('sets and clears the endpoint', async () => {
+    const owner = await newWallet();
+    const profile = await registerAgent(owner);
+
+    await setEndpoint(owner, profile, 'https://agent.example.com/rpc', ['JSON-RPC', 'grpc']);
+    let agent = await program.account.agentProfile.fetch(profile);
+    expect(agent.endpoint).to.equal('https://agent.example.com/rpc');
+    expect(agent.protocols).to.deep.equal(['json-rpc', 'grpc']);
+
+    await setEndpoint(owner, profile, '', []);
+    agent = await program.account.agentProfile.fetch(profile);
+    expect(agent.endpoint).to.equal('');
+    expect(agent.protocols).to.deep.equal([]);
+  });
+
+  it('rejects an endpoint without a supported scheme', async () => {
+    const owner = await newWallet();
+    const profile = await registerAgent(owner);
+
+    await expectError(setEndpoint(owner, profile, 'http://agent.example.com', []), 'InvalidEndpoint');
+  });
+});
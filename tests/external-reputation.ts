@@ -0,0 +1,52 @@
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  asAuthority,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('external reputation', () => {
+  let previousOracle: PublicKey;
+  let oracle: Keypair;
+
+  before(async () => {
+    await ensureRegistry();
+    previousOracle = (await program.account.registryStats.fetch(registryStatsPda)).reputationOracle;
+    oracle = await newWallet();
+    await program.methods.setReputationOracle(oracle.publicKey).accountsPartial(asAuthority()).rpc();
+  });
+
+  after(async () => {
+    await program.methods.setReputationOracle(previousOracle).accountsPartial(asAuthority()).rpc();
+  });
+
+  function importReputation(profile: PublicKey, signer: Keypair, reputation: number, source: string) {
+    return program.methods
+      .importExternalReputation(reputation, source)
+      .accountsPartial({ agentProfile: profile, registryStats: registryStatsPda, oracle: signer.publicKey })
+      .signers([signer])
+      .rpc();
+  }
+
+  it('lets the oracle import a score', async () => {
+    const profile = await registerAgent(await newWallet());
+
+    await importReputation(profile, oracle, 80, 'github');
+
+    const agent = await program.account.agentProfile.fetch(profile);
+    expect(agent.externalReputation).to.equal(80);
+    expect(agent.externalSource).to.equal('github');
+  });
+
+  it('refuses imports from anyone else, the owner included', async () => {
+    const owner = await newWallet();
+    const profile = await registerAgent(owner);
+
+    await expectError(importReputation(profile, owner, 100, 'github'), 'Unauthorized');
+  });
+});
@@ -0,0 +1,382 @@
+import * as anchor from '@coral-xyz/anchor';
+import { BN, BorshCoder, EventParser, Program } from '@coral-xyz/anchor';
+import { AccountMeta, Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js';
+import { BankrunProvider } from 'anchor-bankrun';
+import { expect } from 'chai';
+import { Clock, ProgramTestContext, startAnchor } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+
+anchor.setProvider(anchor.AnchorProvider.env());
+
+export const provider = anchor.getProvider() as anchor.AnchorProvider;
+export const program = anchor.workspace.Agentvault as Program<Agentvault>;
+export const authority = (provider.wallet as anchor.Wallet).payer;
+
+// ============================================================================
+// PDAs
+// ============================================================================
+
+export function pda(...seeds: (Buffer | Uint8Array)[]): PublicKey {
+  return PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+}
+
+const seed = (text: string) => Buffer.from(text);
+
+export const registryStatsPda = pda(seed('registry_stats'));
+export const skillFrequencyPda = pda(seed('skill_frequency'));
+export const treasuryPda = pda(seed('treasury'));
+
+export const agentPda = (agentId: PublicKey) => pda(seed('agent'), agentId.toBuffer());
+export const ownerIndexPda = (wallet: PublicKey) => pda(seed('owner'), wallet.toBuffer());
+export const historyPda = (agentId: PublicKey) => pda(seed('reputation_history'), agentId.toBuffer());
+export const blacklistPda = (wallet: PublicKey) => pda(seed('blacklist'), wallet.toBuffer());
+export const deregisteredPda = (agentId: PublicKey) => pda(seed('deregistered'), agentId.toBuffer());
+export const moderatorPda = (wallet: PublicKey) => pda(seed('moderator'), wallet.toBuffer());
+export const delegatePda = (wallet: PublicKey) => pda(seed('delegate'), wallet.toBuffer());
+export const namePda = (name: string) => pda(seed('name'), Buffer.from(name.trim().toLowerCase()));
+export const agentIndexPda = (index: BN) => pda(seed('agent_idx'), index.toArrayLike(Buffer, 'le', 8));
+
+export const endorsementPda = (endorser: PublicKey, targetId: PublicKey, skill: string) =>
+  pda(seed('endorsement'), endorser.toBuffer(), targetId.toBuffer(), Buffer.from(skill));
+export const pairCountPda = (endorser: PublicKey, targetId: PublicKey) =>
+  pda(seed('pair_endorsements'), endorser.toBuffer(), targetId.toBuffer());
+export const endorserIndexPda = (endorser: PublicKey) => pda(seed('endorser_index'), endorser.toBuffer());
+export const skillCountPda = (targetProfile: PublicKey, skill: string) =>
+  pda(seed('skill_count'), targetProfile.toBuffer(), Buffer.from(skill));
+export const skillReputationPda = (targetProfile: PublicKey, skill: string) =>
+  pda(seed('skill_reputation'), targetProfile.toBuffer(), Buffer.from(skill));
+
+// ============================================================================
+// Setup
+// ============================================================================
+
+export async function fund(wallet: PublicKey, sol = 10): Promise<void> {
+  const signature = await provider.connection.requestAirdrop(wallet, sol * LAMPORTS_PER_SOL);
+  const latest = await provider.connection.getLatestBlockhash();
+  await provider.connection.confirmTransaction({ signature, ...latest }, 'confirmed');
+}
+
+export async function newWallet(sol = 10): Promise<Keypair> {
+  const wallet = Keypair.generate();
+  await fund(wallet.publicKey, sol);
+  return wallet;
+}
+
+// Every test file shares one validator, so the registry is initialized by
+// whichever file runs first. New endorsers are allowed straight away, since
+// the default minimum endorser age is a day.
+export async function ensureRegistry(): Promise<void> {
+  const existing = await program.account.registryStats.fetchNullable(registryStatsPda);
+  if (!existing) {
+    await program.methods
+      .initializeRegistry()
+      .accountsPartial({ registryStats: registryStatsPda, authority: authority.publicKey })
+      .rpc();
+  }
+
+  const stats = await program.account.registryStats.fetch(registryStatsPda);
+  if (!stats.minEndorserAge.isZero()) {
+    await program.methods
+      .setMinEndorserAge(new BN(0))
+      .accountsPartial({ registryStats: registryStatsPda, authority: authority.publicKey })
+      .rpc();
+  }
+}
+
+// `Leaderboard::SPACE`: discriminator, 1000 entries of 33 bytes and the length
+export const LEADERBOARD_SPACE = 8 + 1000 * 33 + 2;
+
+// The registry's leaderboard, created on first use. It is too large to
+// create through a CPI, so it is allocated in the same transaction.
+export async function ensureLeaderboard(): Promise<PublicKey> {
+  const stats = await program.account.registryStats.fetch(registryStatsPda);
+  if (!stats.leaderboard.equals(PublicKey.default)) {
+    return stats.leaderboard;
+  }
+
+  const leaderboard = Keypair.generate();
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(LEADERBOARD_SPACE);
+  await program.methods
+    .initializeLeaderboard()
+    .accountsPartial({
+      leaderboard: leaderboard.publicKey,
+      registryStats: registryStatsPda,
+      authority: authority.publicKey,
+    })
+    .preInstructions([
+      SystemProgram.createAccount({
+        fromPubkey: authority.publicKey,
+        newAccountPubkey: leaderboard.publicKey,
+        space: LEADERBOARD_SPACE,
+        lamports,
+        programId: program.programId,
+      }),
+    ])
+    .signers([leaderboard])
+    .rpc();
+  return leaderboard.publicKey;
+}
+
+export async function unitsConsumed(signature: string): Promise<number> {
+  const tx = await provider.connection.getTransaction(signature, {
+    commitment: 'confirmed',
+    maxSupportedTransactionVersion: 0,
+  });
+  return tx?.meta?.computeUnitsConsumed ?? 0;
+}
+
+// Data of the first `name` event emitted by a transaction sent with
+// `confirmed` commitment. Names are compared ignoring case, since the client
+// camel-cases what the program declares in Pascal case.
+export async function eventOf(signature: string, name: string): Promise<any> {
+  const tx = await provider.connection.getTransaction(signature, {
+    commitment: 'confirmed',
+    maxSupportedTransactionVersion: 0,
+  });
+  const parser = new EventParser(program.programId, new BorshCoder(program.idl));
+  for (const event of parser.parseLogs(tx?.meta?.logMessages ?? [])) {
+    if (event.name.toLowerCase() === name.toLowerCase()) {
+      return event.data;
+    }
+  }
+  expect.fail(`no ${name} event in ${signature}`);
+}
+
+let nameCounter = 0;
+
+export function uniqueName(prefix = 'agent'): string {
+  return `${prefix}-${Date.now().toString(36)}-${nameCounter++}`;
+}
+
+export interface RegisterOptions {
+  name?: string;
+  skills?: string[];
+  categories?: string[];
+  bio?: string | null;
+  hookProgram?: PublicKey | null;
+  region?: string | null;
+  personhoodProgram?: PublicKey | null;
+  personhoodCredential?: PublicKey | null;
+  remainingAccounts?: AccountMeta[];
+}
+
+// Register `owner` as an agent and return its profile address. The
+// `agent_id` of a fresh registration is the owner's wallet. `client` lets
+// tests outside the shared validator (see recovery.ts) reuse this.
+export async function registerAgent(
+  owner: Keypair,
+  options: RegisterOptions = {},
+  client: Program<Agentvault> = program
+): Promise<PublicKey> {
+  const stats = await client.account.registryStats.fetch(registryStatsPda);
+  const name = options.name ?? uniqueName();
+  const agentProfile = agentPda(owner.publicKey);
+
+  await client.methods
+    .registerAgent(
+      name,
+      'https://example.com/agent.json',
+      options.skills ?? ['rust'],
+      options.categories ?? [],
+      null,
+      options.bio ?? null,
+      new BN(0),
+      options.hookProgram ?? null,
+      options.region ?? null
+    )
+    .accountsPartial({
+      agentProfile,
+      ownerIndex: ownerIndexPda(owner.publicKey),
+      reputationHistory: historyPda(owner.publicKey),
+      blacklistEntry: blacklistPda(owner.publicKey),
+      guard: {
+        existingOwnerIndex: ownerIndexPda(owner.publicKey),
+        existingProfile: agentProfile,
+        existingDeregistration: deregisteredPda(owner.publicKey),
+        owner: owner.publicKey,
+      },
+      skillFrequency: skillFrequencyPda,
+      nameRegistry: namePda(name),
+      treasury: stats.registrationFee.isZero() ? null : stats.treasury,
+      registryStats: registryStatsPda,
+      agentIndex: agentIndexPda(stats.nextAgentIndex),
+      owner: owner.publicKey,
+      referrerProfile: null,
+      referrerWallet: null,
+      referral: null,
+      personhoodProgram: options.personhoodProgram ?? null,
+      personhoodCredential: options.personhoodCredential ?? null,
+    })
+    .remainingAccounts(options.remainingAccounts ?? [])
+    .signers([owner])
+    .rpc();
+
+  return agentProfile;
+}
+
+// Deregister the agent `agentId`, signing as `signer`, dropping it from
+// `leaderboard` if given. Agents in these tests register without a stake,
+// so there is none to return.
+export async function deregister(
+  agentId: PublicKey,
+  signer: Keypair,
+  leaderboard: PublicKey | null = null,
+): Promise<void> {
+  const profile = agentPda(agentId);
+  const agent = await program.account.agentProfile.fetch(profile);
+  await program.methods
+    .deregisterAgent()
+    .accountsPartial({
+      agentProfile: profile,
+      ownerIndex: ownerIndexPda(signer.publicKey),
+      reputationHistory: historyPda(agentId),
+      deregisteredAgent: deregisteredPda(agentId),
+      nameRegistry: namePda(agent.name),
+      agentIndex: agentIndexPda(agent.index ?? new BN(0)),
+      skillFrequency: skillFrequencyPda,
+      registryStats: registryStatsPda,
+      owner: signer.publicKey,
+      stakeMint: null,
+      stakeVault: null,
+      ownerTokenAccount: null,
+      tokenProgram: null,
+      leaderboard,
+    })
+    .signers([signer])
+    .rpc();
+}
+
+// Accounts for `endorse_skill` from `endorser` to the agent `targetId`
+export function endorseAccounts(endorser: PublicKey, endorserId: PublicKey, targetId: PublicKey, skill: string) {
+  const targetProfile = agentPda(targetId);
+  return {
+    endorsement: endorsementPda(endorser, targetId, skill),
+    endorserProfile: agentPda(endorserId),
+    targetAgent: targetProfile,
+    reverseEndorsement: null,
+    pairCount: pairCountPda(endorser, targetId),
+    endorserIndex: endorserIndexPda(endorser),
+    reputationHistory: historyPda(targetId),
+    skillCount: skillCountPda(targetProfile, skill),
+    skillReputation: skillReputationPda(targetProfile, skill),
+    leaderboard: null,
+    skillLeaderboard: null,
+    skillFrequency: skillFrequencyPda,
+    registryStats: registryStatsPda,
+    endorser,
+  };
+}
+
+export async function endorse(endorser: Keypair, targetId: PublicKey, skill = 'rust', rating = 3): Promise<PublicKey> {
+  const accounts = endorseAccounts(endorser.publicKey, endorser.publicKey, targetId, skill);
+  await program.methods
+    .endorseSkill(skill, rating, null)
+    .accountsPartial(accounts)
+    .signers([endorser])
+    .rpc();
+  return accounts.endorsement;
+}
+
+// Accounts for `revoke_endorsement` of `endorser`'s endorsement of `targetId`
+export function revokeAccounts(endorser: PublicKey, targetId: PublicKey, skill: string) {
+  const targetProfile = agentPda(targetId);
+  return {
+    endorsement: endorsementPda(endorser, targetId, skill),
+    targetAgent: targetProfile,
+    reputationHistory: historyPda(targetId),
+    skillCount: skillCountPda(targetProfile, skill),
+    skillReputation: skillReputationPda(targetProfile, skill),
+    pairCount: pairCountPda(endorser, targetId),
+    endorserIndex: endorserIndexPda(endorser),
+    leaderboard: null,
+    skillLeaderboard: null,
+    endorserProfile: null,
+    registryStats: registryStatsPda,
+    endorser,
+  };
+}
+
+export async function revoke(endorser: Keypair, targetId: PublicKey, skill = 'rust'): Promise<void> {
+  await program.methods
+    .revokeEndorsement(0)
+    .accountsPartial(revokeAccounts(endorser.publicKey, targetId, skill))
+    .signers([endorser])
+    .rpc();
+}
+
+// Run a registry setter as the authority
+export function asAuthority() {
+  return { registryStats: registryStatsPda, authority: authority.publicKey };
+}
+
+// ============================================================================
+// Bankrun
+// ============================================================================
+//
+// The shared validator's clock can't be moved, so tests of time-locked flows
+// run the program in bankrun instead, each on a fresh registry. Bankrun
+// reports failures differently from the validator, so these tests check that
+// a transaction fails and then assert on state.
+
+const IDL = require('../target/idl/agentvault.json');
+
+export interface Bankrun {
+  context: ProgramTestContext;
+  program: Program<Agentvault>;
+}
+
+// Start the program in bankrun with `wallets` funded and the registry
+// initialized
+export async function startBankrun(wallets: Keypair[]): Promise<Bankrun> {
+  const accounts = wallets.map((wallet) => ({
+    address: wallet.publicKey,
+    info: { lamports: 10 * LAMPORTS_PER_SOL, data: Buffer.alloc(0), owner: SystemProgram.programId, executable: false },
+  }));
+  const context = await startAnchor('.', [], accounts);
+  const client = new Program<Agentvault>(IDL, new BankrunProvider(context));
+
+  await client.methods
+    .initializeRegistry()
+    .accountsPartial({ registryStats: registryStatsPda, authority: context.payer.publicKey })
+    .rpc();
+  return { context, program: client };
+}
+
+// Move to the next slot, and `seconds` ahead. The new slot brings a new
+// blockhash, so a retried transaction isn't rejected as a duplicate.
+export async function tick(context: ProgramTestContext, seconds = 0): Promise<void> {
+  const clock = await context.banksClient.getClock();
+  const slot = clock.slot + BigInt(1);
+  const timestamp = clock.unixTimestamp + BigInt(seconds);
+  context.warpToSlot(slot);
+  context.setClock(new Clock(slot, clock.epochStartTimestamp, clock.epoch, clock.leaderScheduleEpoch, timestamp));
+}
+
+export async function clockTime(context: ProgramTestContext): Promise<number> {
+  return Number((await context.banksClient.getClock()).unixTimestamp);
+}
+
+// ============================================================================
+// Assertions
+// ============================================================================
+
+// Expect `promise` to fail with the program (or Anchor) error `code`
+export async function expectError(promise: Promise<unknown>, code: string): Promise<void> {
+  try {
+    await promise;
+  } catch (err) {
+    expect(err).to.have.nested.property('error.errorCode.code', code);
+    return;
+  }
+  expect.fail(`expected the transaction to fail with ${code}`);
+}
+
+// Whether `promise` fails, for bankrun transactions
+export async function fails(promise: Promise<unknown>): Promise<boolean> {
+  try {
+    await promise;
+    return false;
+  } catch {
+    return true;
+  }
+}
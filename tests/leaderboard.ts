@@ -0,0 +1,78 @@
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  agentPda,
+  deregister,
+  endorseAccounts,
+  ensureLeaderboard,
+  ensureRegistry,
+  newWallet,
+  program,
+  registerAgent,
+  unitsConsumed,
+} from './helpers';
+
+describe('leaderboard', () => {
+  let leaderboard: PublicKey;
+
+  before(async () => {
+    await ensureRegistry();
+    leaderboard = await ensureLeaderboard();
+  });
+
+  async function endorseWith(
+    board: PublicKey | null,
+  ): Promise<{ target: PublicKey; units: number; targetWallet: Keypair }> {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+
+    const signature = await program.methods
+      .endorseSkill('rust', 3, null)
+      .accountsPartial({
+        ...endorseAccounts(endorser.publicKey, endorser.publicKey, target.publicKey, 'rust'),
+        leaderboard: board,
+      })
+      .signers([endorser])
+      .rpc({ commitment: 'confirmed' });
+    return { target: target.publicKey, units: await unitsConsumed(signature), targetWallet: target };
+  }
+
+  async function ranked(agent: PublicKey): Promise<boolean> {
+    const board = await program.account.leaderboard.fetch(leaderboard);
+    return board.entries.slice(0, board.len).some((e) => e.agent.equals(agent));
+  }
+
+  it('ranks an endorsed agent at its new reputation', async () => {
+    const { target } = await endorseWith(leaderboard);
+
+    const board = await program.account.leaderboard.fetch(leaderboard);
+    const entries = board.entries.slice(0, board.len);
+    const entry = entries.find((e) => e.agent.equals(target));
+    const profile = await program.account.agentProfile.fetch(agentPda(target));
+
+    expect(entry?.reputation).to.equal(profile.reputation);
+    for (let i = 1; i < entries.length; i++) {
+      expect(entries[i - 1].reputation).to.be.at.least(entries[i].reputation);
+    }
+  });
+
+  it('drops a deregistered agent from the board', async () => {
+    const { target, targetWallet } = await endorseWith(leaderboard);
+    expect(await ranked(target)).to.be.true;
+
+    await deregister(target, targetWallet, leaderboard);
+
+    expect(await ranked(target)).to.be.false;
+  });
+
+  it('keeps the re-rank within a modest compute budget', async () => {
+    const without = await endorseWith(null);
+    const withBoard = await endorseWith(leaderboard);
+    const overhead = withBoard.units - without.units;
+
+    expect(overhead).to.be.below(100_000);
+    expect(withBoard.units).to.be.below(200_000);
+  });
+});
@@ -0,0 +1,82 @@
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  asAuthority,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+  uniqueName,
+} from './helpers';
+
+// Defaults for registries that predate configurable limits
+const DEFAULT_MAX_SKILLS = 10;
+const DEFAULT_MAX_NAME_LEN = 32;
+const DEFAULT_MAX_URI_LEN = 200;
+
+describe('registry limits', () => {
+  let limits: [number, number, number];
+
+  before(async () => {
+    await ensureRegistry();
+    const stats = await program.account.registryStats.fetch(registryStatsPda);
+    limits = [
+      stats.maxSkills || DEFAULT_MAX_SKILLS,
+      stats.maxNameLen || DEFAULT_MAX_NAME_LEN,
+      stats.maxUriLen || DEFAULT_MAX_URI_LEN,
+    ];
+  });
+
+  afterEach(async () => {
+    await program.methods.setLimits(...limits).accountsPartial(asAuthority()).rpc();
+  });
+
+  function setLimits(maxSkills: number, maxNameLen: number, maxUriLen: number) {
+    return program.methods.setLimits(maxSkills, maxNameLen, maxUriLen).accountsPartial(asAuthority()).rpc();
+  }
+
+  function updateSkills(owner: Keypair, profile: PublicKey, skills: string[] | null) {
+    return program.methods
+      .updateProfile(null, skills, 'still here', null, null, null, null, null)
+      .accountsPartial({
+        agentProfile: profile,
+        delegate: null,
+        registryStats: registryStatsPda,
+        signer: owner.publicKey,
+        reputationHistory: null,
+        skillFrequency: null,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  it('validates registrations against the configured limits', async () => {
+    const name = uniqueName('limit');
+    await setLimits(DEFAULT_MAX_SKILLS, name.length - 1, DEFAULT_MAX_URI_LEN);
+    await expectError(registerAgent(await newWallet(), { name }), 'NameTooLong');
+
+    await setLimits(1, DEFAULT_MAX_NAME_LEN, DEFAULT_MAX_URI_LEN);
+    await expectError(registerAgent(await newWallet(), { skills: ['rust', 'go'] }), 'TooManySkills');
+
+    await setLimits(DEFAULT_MAX_SKILLS, name.length, DEFAULT_MAX_URI_LEN);
+    await registerAgent(await newWallet(), { name });
+  });
+
+  it('only enforces lowered limits on writes', async () => {
+    const owner = await newWallet();
+    const profile = await registerAgent(owner, { skills: ['rust', 'go'] });
+
+    await setLimits(1, DEFAULT_MAX_NAME_LEN, DEFAULT_MAX_URI_LEN);
+    await updateSkills(owner, profile, null);
+    expect((await program.account.agentProfile.fetch(profile)).skills).to.deep.equal(['rust', 'go']);
+
+    await expectError(updateSkills(owner, profile, ['rust', 'go']), 'TooManySkills');
+  });
+
+  it('rejects limits outside the supported range', async () => {
+    await expectError(setLimits(0, DEFAULT_MAX_NAME_LEN, DEFAULT_MAX_URI_LEN), 'InvalidParameter');
+    await expectError(setLimits(DEFAULT_MAX_SKILLS, DEFAULT_MAX_NAME_LEN + 1, DEFAULT_MAX_URI_LEN), 'InvalidParameter');
+  });
+});
@@ -0,0 +1,127 @@
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import {
+  authority,
+  ensureRegistry,
+  expectError,
+  fails,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+  startBankrun,
+  tick,
+} from './helpers';
+
+describe('schema versions', () => {
+  before(ensureRegistry);
+
+  it('stamps new accounts with the current layout version', async () => {
+    const stats = await program.account.registryStats.fetch(registryStatsPda);
+    const profile = await program.account.agentProfile.fetch(await registerAgent(await newWallet()));
+
+    expect(stats.version).to.be.greaterThan(0);
+    expect(profile.version).to.be.greaterThan(0);
+  });
+
+  it('refuses to migrate a profile that is already current', async () => {
+    const profile = await registerAgent(await newWallet());
+
+    await expectError(
+      program.methods
+        .migrateProfile()
+        .accountsPartial({ agentProfile: profile, registryStats: registryStatsPda, payer: authority.publicKey })
+        .rpc(),
+      'ProfileAlreadyMigrated'
+    );
+  });
+});
+
+// Old layouts can't be produced by the current program, so these tests
+// rewrite accounts in bankrun
+describe('layout migration', () => {
+  const owner = Keypair.generate();
+  let context: ProgramTestContext;
+  let client: Program<Agentvault>;
+
+  before(async () => {
+    ({ context, program: client } = await startBankrun([owner]));
+  });
+
+  async function rewrite(address: PublicKey, edit: (data: Buffer) => Buffer): Promise<void> {
+    const account = await context.banksClient.getAccount(address);
+    context.setAccount(address, { ...account, data: edit(Buffer.from(account.data)) });
+  }
+
+  // Bytes of a profile's fields up to and including `version`, the layout
+  // as of version 1: the wallet and three variable-length fields, then
+  // 134 bytes of fixed-size ones
+  function v1Length(data: Buffer): number {
+    let offset = 8 + 32;
+    const skip = () => (offset += 4 + data.readUInt32LE(offset));
+    skip();
+    skip();
+    const skills = data.readUInt32LE(offset);
+    offset += 4;
+    for (let i = 0; i < skills; i++) skip();
+    return offset + 134;
+  }
+
+  it('migrates a version 1 profile, discarding stale bytes past its fields', async () => {
+    const profile = await registerAgent(owner, { bio: 'an agent' }, client);
+    const current = await client.account.agentProfile.fetch(profile);
+
+    // A v1 profile whose tail still holds bytes from a longer string
+    await rewrite(profile, (data) => {
+      const length = v1Length(data);
+      data[length - 1] = 1;
+      return data.fill(0xff, length);
+    });
+
+    await client.methods
+      .migrateProfile()
+      .accountsPartial({ agentProfile: profile, registryStats: registryStatsPda, payer: context.payer.publicKey })
+      .rpc();
+
+    const migrated = await client.account.agentProfile.fetch(profile);
+    expect(migrated.version).to.equal(current.version);
+    expect(migrated.name).to.equal(current.name);
+    expect(migrated.reputation).to.equal(current.reputation);
+    expect(migrated.skillCapacity).to.equal(current.skillCapacity);
+    expect(migrated.categories).to.be.empty;
+    expect(migrated.bio).to.equal('');
+    expect(migrated.hookProgram).to.be.null;
+  });
+
+  it('migrates a version 1 registry, restoring the defaults of newer settings', async () => {
+    const current = await client.account.registryStats.fetch(registryStatsPda);
+
+    // The registry's fields up to and including `version` take 194 bytes
+    await rewrite(registryStatsPda, (data) => {
+      data[193] = 1;
+      return data.subarray(0, 194);
+    });
+    await tick(context);
+
+    await client.methods.migrateRegistry().accountsPartial({ payer: context.payer.publicKey }).rpc();
+
+    const migrated = await client.account.registryStats.fetch(registryStatsPda);
+    expect(migrated.version).to.equal(current.version);
+    expect(migrated.authority.equals(current.authority)).to.be.true;
+    expect(migrated.totalAgents.toNumber()).to.equal(current.totalAgents.toNumber());
+    expect(migrated.minEndorserAge.toNumber()).to.equal(current.minEndorserAge.toNumber());
+    expect(migrated.maxSkills).to.equal(current.maxSkills);
+    expect(migrated.creditsPerHour).to.equal(current.creditsPerHour);
+    expect(migrated.decayFractionBps).to.equal(current.decayFractionBps);
+    expect(migrated.jobCreditCooldown.toNumber()).to.equal(current.jobCreditCooldown.toNumber());
+    expect(migrated.decayFloor).to.equal(current.decayFloor);
+    expect(migrated.leaderboard.equals(PublicKey.default)).to.be.true;
+
+    await tick(context);
+    const again = client.methods.migrateRegistry().accountsPartial({ payer: context.payer.publicKey }).rpc();
+    expect(await fails(again)).to.be.true;
+  });
+});
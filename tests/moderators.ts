@@ -0,0 +1,78 @@
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  authority,
+  ensureRegistry,
+  expectError,
+  moderatorPda,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('moderators', () => {
+  before(ensureRegistry);
+
+  function verify(profile: PublicKey, signer: Keypair, moderator: PublicKey | null) {
+    return program.methods
+      .setVerification(1)
+      .accountsPartial({
+        agentProfile: profile,
+        registryStats: registryStatsPda,
+        moderator,
+        signer: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+  }
+
+  function addModerator(wallet: PublicKey) {
+    return program.methods
+      .addModerator(wallet)
+      .accountsPartial({
+        moderator: moderatorPda(wallet),
+        registryStats: registryStatsPda,
+        authority: authority.publicKey,
+      })
+      .rpc();
+  }
+
+  it('lets a moderator verify agents but not run authority instructions', async () => {
+    const moderator = await newWallet();
+    const profile = await registerAgent(await newWallet());
+    await addModerator(moderator.publicKey);
+
+    await verify(profile, moderator, moderatorPda(moderator.publicKey));
+    expect((await program.account.agentProfile.fetch(profile)).verificationTier).to.equal(1);
+
+    await expectError(
+      program.methods
+        .setPause(true)
+        .accountsPartial({ registryStats: registryStatsPda, authority: moderator.publicKey })
+        .signers([moderator])
+        .rpc(),
+      'Unauthorized'
+    );
+  });
+
+  it('locks out a revoked moderator', async () => {
+    const moderator = await newWallet();
+    const profile = await registerAgent(await newWallet());
+    const record = moderatorPda(moderator.publicKey);
+    await addModerator(moderator.publicKey);
+
+    await program.methods
+      .removeModerator()
+      .accountsPartial({
+        moderator: record,
+        registryStats: registryStatsPda,
+        authority: authority.publicKey,
+      })
+      .rpc();
+    expect(await program.account.moderator.fetchNullable(record)).to.be.null;
+
+    await expectError(verify(profile, moderator, record), 'AccountNotInitialized');
+    await expectError(verify(profile, moderator, null), 'Unauthorized');
+  });
+});
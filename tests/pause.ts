@@ -0,0 +1,48 @@
+import { expect } from 'chai';
+import {
+  asAuthority,
+  endorse,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('registry pause', () => {
+  before(ensureRegistry);
+
+  afterEach(async () => {
+    await program.methods.setPause(false).accountsPartial(asAuthority()).rpc();
+  });
+
+  it('blocks registrations and endorsements while paused', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+
+    await program.methods.setPause(true).accountsPartial(asAuthority()).rpc();
+    expect((await program.account.registryStats.fetch(registryStatsPda)).paused).to.equal(true);
+
+    await expectError(registerAgent(await newWallet()), 'RegistryPaused');
+    await expectError(endorse(endorser, target.publicKey), 'RegistryPaused');
+
+    await program.methods.setPause(false).accountsPartial(asAuthority()).rpc();
+    await endorse(endorser, target.publicKey);
+  });
+
+  it('only lets the authority pause', async () => {
+    const stranger = await newWallet();
+
+    await expectError(
+      program.methods
+        .setPause(true)
+        .accountsPartial({ registryStats: registryStatsPda, authority: stranger.publicKey })
+        .signers([stranger])
+        .rpc(),
+      'Unauthorized'
+    );
+  });
+});
@@ -0,0 +1,90 @@
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { MockPersonhood } from '../target/types/mock_personhood';
+import {
+  agentPda,
+  asAuthority,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+} from './helpers';
+
+const verifier = anchor.workspace.MockPersonhood as Program<MockPersonhood>;
+const credentialPda = (holder: PublicKey) =>
+  PublicKey.findProgramAddressSync([Buffer.from('credential'), holder.toBuffer()], verifier.programId)[0];
+
+describe('proof of personhood', () => {
+  before(async () => {
+    await ensureRegistry();
+    await program.methods.setPersonhoodRequirement(true, verifier.programId).accountsPartial(asAuthority()).rpc();
+  });
+
+  after(async () => {
+    await program.methods.setPersonhoodRequirement(false, PublicKey.default).accountsPartial(asAuthority()).rpc();
+  });
+
+  // A fresh wallet holding a credential
+  async function verifiedWallet(): Promise<Keypair> {
+    const wallet = await newWallet();
+    await verifier.methods
+      .issue(wallet.publicKey)
+      .accountsPartial({ credential: credentialPda(wallet.publicKey) })
+      .rpc();
+    return wallet;
+  }
+
+  // Expect the registration to fail inside the verifier with `code`
+  async function expectRejected(owner: Keypair, credential: PublicKey, code: string): Promise<void> {
+    let failed = false;
+    try {
+      await registerAgent(owner, { personhoodProgram: verifier.programId, personhoodCredential: credential });
+    } catch (err) {
+      expect((err as { logs?: string[] }).logs?.join('\n')).to.include(`Error Code: ${code}`);
+      failed = true;
+    }
+    expect(failed).to.be.true;
+    expect(await program.account.agentProfile.fetchNullable(agentPda(owner.publicKey))).to.be.null;
+  }
+
+  it('registers a wallet the verifier accepts', async () => {
+    const owner = await verifiedWallet();
+
+    const profile = await registerAgent(owner, {
+      personhoodProgram: verifier.programId,
+      personhoodCredential: credentialPda(owner.publicKey),
+    });
+
+    expect((await program.account.agentProfile.fetch(profile)).wallet.equals(owner.publicKey)).to.be.true;
+  });
+
+  it('requires the verifier and a credential', async () => {
+    const owner = await verifiedWallet();
+    const credential = credentialPda(owner.publicKey);
+
+    await expectError(registerAgent(owner), 'PersonhoodRequired');
+    await expectError(registerAgent(owner, { personhoodProgram: verifier.programId }), 'PersonhoodRequired');
+    // Some other program standing in for the verifier
+    await expectError(
+      registerAgent(owner, { personhoodProgram: program.programId, personhoodCredential: credential }),
+      'PersonhoodRequired'
+    );
+  });
+
+  it("fails with the verifier's error for someone else's credential", async () => {
+    const holder = await verifiedWallet();
+
+    await expectRejected(await newWallet(), credentialPda(holder.publicKey), 'NotHolder');
+  });
+
+  it("fails with the verifier's error for a revoked credential", async () => {
+    const owner = await verifiedWallet();
+    const credential = credentialPda(owner.publicKey);
+    await verifier.methods.revoke().accountsPartial({ credential }).rpc();
+
+    await expectRejected(owner, credential, 'Revoked');
+  });
+});
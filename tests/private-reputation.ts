@@ -0,0 +1,70 @@
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  endorseAccounts,
+  ensureRegistry,
+  eventOf,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('private reputation', () => {
+  before(ensureRegistry);
+
+  async function setPrivate(owner: Keypair, profile: PublicKey, privateReputation: boolean): Promise<void> {
+    await program.methods
+      .updateProfile(null, null, null, null, null, null, privateReputation, null)
+      .accountsPartial({
+        agentProfile: profile,
+        delegate: null,
+        registryStats: registryStatsPda,
+        signer: owner.publicKey,
+        reputationHistory: null,
+        skillFrequency: null,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  // Endorse `target` from a fresh agent and return the transaction
+  async function endorseFromNewAgent(target: Keypair): Promise<string> {
+    const endorser = await newWallet();
+    await registerAgent(endorser);
+    return program.methods
+      .endorseSkill('rust', 3, null)
+      .accountsPartial(endorseAccounts(endorser.publicKey, endorser.publicKey, target.publicKey, 'rust'))
+      .signers([endorser])
+      .rpc({ commitment: 'confirmed' });
+  }
+
+  it('zeroes the scores in events but keeps them on-chain', async () => {
+    const target = await newWallet();
+    const profile = await registerAgent(target);
+    const before = (await program.account.agentProfile.fetch(profile)).reputation;
+    await setPrivate(target, profile, true);
+
+    const signature = await endorseFromNewAgent(target);
+    const event = await eventOf(signature, 'ReputationChanged');
+    expect(event.wallet.equals(target.publicKey)).to.be.true;
+    expect([event.oldReputation, event.newReputation]).to.deep.equal([0, 0]);
+    expect((await program.account.agentProfile.fetch(profile)).reputation).to.be.above(before);
+
+    // The boost itself would give the change away
+    const endorsed = await eventOf(signature, 'SkillEndorsed');
+    expect([endorsed.weight, endorsed.skillReputation]).to.deep.equal([0, 0]);
+  });
+
+  it('shows the scores again once the flag is cleared', async () => {
+    const target = await newWallet();
+    const profile = await registerAgent(target);
+    await setPrivate(target, profile, true);
+    await setPrivate(target, profile, false);
+    const before = (await program.account.agentProfile.fetch(profile)).reputation;
+
+    const event = await eventOf(await endorseFromNewAgent(target), 'ReputationChanged');
+    expect(event.oldReputation).to.equal(before);
+    expect(event.newReputation).to.equal((await program.account.agentProfile.fetch(profile)).reputation);
+  });
+});
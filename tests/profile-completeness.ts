@@ -0,0 +1,42 @@
+import {
+  asAuthority,
+  endorse,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('profile completeness', () => {
+  let required: boolean;
+
+  before(async () => {
+    await ensureRegistry();
+    required = (await program.account.registryStats.fetch(registryStatsPda)).requireCompleteProfile;
+    await program.methods.setRequireCompleteProfile(true).accountsPartial(asAuthority()).rpc();
+  });
+
+  after(async () => {
+    await program.methods.setRequireCompleteProfile(required).accountsPartial(asAuthority()).rpc();
+  });
+
+  it('rejects endorsements of a minimal profile', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+
+    await expectError(endorse(endorser, target.publicKey), 'ProfileIncomplete');
+  });
+
+  it('accepts endorsements of a complete profile', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target, { skills: ['rust', 'solana'], bio: 'Indexes Solana programs' });
+    await registerAgent(endorser);
+
+    await endorse(endorser, target.publicKey);
+  });
+});
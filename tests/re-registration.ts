@@ -0,0 +1,61 @@
+import { expect } from 'chai';
+import {
+  deregister,
+  deregisteredPda,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  ownerIndexPda,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('re-registration', () => {
+  before(ensureRegistry);
+
+  it('fails with AgentAlreadyRegistered for a wallet that has a profile', async () => {
+    const owner = await newWallet();
+    const profile = await registerAgent(owner);
+    const { name } = await program.account.agentProfile.fetch(profile);
+
+    // A fresh name, so the name registry can't be what rejects it
+    await expectError(registerAgent(owner), 'AgentAlreadyRegistered');
+
+    expect((await program.account.agentProfile.fetch(profile)).name).to.equal(name);
+  });
+
+  it('fails with ProfileAddressTransferred for a wallet that gave its profile away', async () => {
+    const owner = await newWallet();
+    const newOwner = await newWallet();
+    const profile = await registerAgent(owner);
+
+    await program.methods
+      .transferOwnership(newOwner.publicKey)
+      .accountsPartial({
+        agentProfile: profile,
+        ownerIndex: ownerIndexPda(owner.publicKey),
+        newOwnerIndex: ownerIndexPda(newOwner.publicKey),
+        registryStats: registryStatsPda,
+        owner: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+
+    await expectError(registerAgent(owner), 'ProfileAddressTransferred');
+
+    expect((await program.account.agentProfile.fetch(profile)).wallet.equals(newOwner.publicKey)).to.be.true;
+  });
+
+  it('fails with AgentIdDeregistered for an agent that deregistered', async () => {
+    const owner = await newWallet();
+    await registerAgent(owner);
+    await deregister(owner.publicKey, owner);
+
+    const record = await program.account.deregisteredAgent.fetch(deregisteredPda(owner.publicKey));
+    expect(record.agentId.equals(owner.publicKey)).to.be.true;
+    expect(record.deregisteredAt.toNumber()).to.be.above(0);
+
+    await expectError(registerAgent(owner), 'AgentIdDeregistered');
+  });
+});
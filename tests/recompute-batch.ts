@@ -0,0 +1,95 @@
+import { BN } from '@coral-xyz/anchor';
+import { expect } from 'chai';
+import {
+  asAuthority,
+  authority,
+  endorse,
+  ensureRegistry,
+  expectError,
+  historyPda,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('batch recompute', () => {
+  let params: [number, number, number];
+
+  before(async () => {
+    await ensureRegistry();
+    const stats = await program.account.registryStats.fetch(registryStatsPda);
+    params = [stats.baseReputation, stats.endorsementBoost, stats.maxReputation];
+  });
+
+  after(async () => {
+    await program.methods.setReputationParams(...params).accountsPartial(asAuthority()).rpc();
+  });
+
+  it('recomputes a batch of three profiles under the current parameters', async () => {
+    const endorsers = [await newWallet(), await newWallet()];
+    for (const endorser of endorsers) {
+      await registerAgent(endorser);
+    }
+    const wallets = [await newWallet(), await newWallet(), await newWallet()];
+    const profiles = [];
+    for (const [i, wallet] of wallets.entries()) {
+      profiles.push(await registerAgent(wallet));
+      for (const endorser of endorsers.slice(0, i)) {
+        await endorse(endorser, wallet.publicKey);
+      }
+    }
+
+    // A slash is kept through the recompute
+    await program.methods
+      .slashAgent(4, new BN(0), 0)
+      .accountsPartial({
+        agentProfile: profiles[2],
+        reputationHistory: historyPda(wallets[2].publicKey),
+        registryStats: registryStatsPda,
+        moderator: null,
+        signer: authority.publicKey,
+        stakeMint: null,
+        stakeVault: null,
+        stakeTreasury: null,
+        tokenProgram: null,
+        leaderboard: null,
+      })
+      .rpc();
+
+    const [base, boost, max] = params;
+    const newBase = base - 5;
+    await program.methods.setReputationParams(newBase, boost, max).accountsPartial(asAuthority()).rpc();
+
+    await program.methods
+      .recomputeBatch()
+      .accountsPartial({ registryStats: registryStatsPda, authority: authority.publicKey })
+      .remainingAccounts(
+        wallets.flatMap((wallet, i) => [
+          { pubkey: profiles[i], isSigner: false, isWritable: true },
+          { pubkey: historyPda(wallet.publicKey), isSigner: false, isWritable: true },
+        ])
+      )
+      .rpc();
+
+    for (const profile of profiles) {
+      // No reviews or external reputation, so the blend is the endorsement
+      // score: the new base plus the endorsements' stored weights
+      const agent = await program.account.agentProfile.fetch(profile);
+      const score = Math.floor((Math.min(max, newBase + agent.endorsementPoints) * 100) / max);
+      const expected = Math.floor((score * max) / 100) + agent.reputationAdjustment;
+      expect(agent.reputation).to.equal(expected);
+    }
+    expect((await program.account.agentProfile.fetch(profiles[2])).reputationAdjustment).to.equal(-4);
+  });
+
+  it('rejects an empty batch', async () => {
+    await expectError(
+      program.methods
+        .recomputeBatch()
+        .accountsPartial({ registryStats: registryStatsPda, authority: authority.publicKey })
+        .rpc(),
+      'InvalidBatchSize'
+    );
+  });
+});
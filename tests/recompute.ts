@@ -0,0 +1,56 @@
+import { BN } from '@coral-xyz/anchor';
+import { sendAndConfirmTransaction, Transaction } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  authority,
+  endorse,
+  ensureRegistry,
+  historyPda,
+  newWallet,
+  program,
+  provider,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('recompute reputation', () => {
+  before(ensureRegistry);
+
+  it('lets anyone recompute, keeping slashes in place', async () => {
+    const [owner, endorser, stranger] = [await newWallet(), await newWallet(), await newWallet()];
+    const profile = await registerAgent(owner);
+    await registerAgent(endorser);
+    await endorse(endorser, owner.publicKey);
+
+    await program.methods
+      .slashAgent(5, new BN(0), 0)
+      .accountsPartial({
+        agentProfile: profile,
+        reputationHistory: historyPda(owner.publicKey),
+        registryStats: registryStatsPda,
+        moderator: null,
+        signer: authority.publicKey,
+        stakeMint: null,
+        stakeVault: null,
+        stakeTreasury: null,
+        tokenProgram: null,
+        leaderboard: null,
+      })
+      .rpc();
+    const slashed = (await program.account.agentProfile.fetch(profile)).reputation;
+
+    // Sent and paid for by an unrelated wallet
+    const recompute = await program.methods
+      .recomputeReputation()
+      .accountsPartial({
+        agentProfile: profile,
+        reputationHistory: historyPda(owner.publicKey),
+        leaderboard: null,
+        registryStats: registryStatsPda,
+      })
+      .instruction();
+    await sendAndConfirmTransaction(provider.connection, new Transaction().add(recompute), [stranger]);
+
+    expect((await program.account.agentProfile.fetch(profile)).reputation).to.equal(slashed);
+  });
+});
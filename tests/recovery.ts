@@ -0,0 +1,104 @@
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import {
+  agentPda,
+  fails,
+  ownerIndexPda,
+  pda,
+  registerAgent,
+  registryStatsPda,
+  startBankrun,
+  tick,
+} from './helpers';
+
+// `RECOVERY_TIMELOCK`
+const RECOVERY_TIMELOCK = 7 * 24 * 60 * 60;
+
+const recoveryPda = (agentId: PublicKey) => pda(Buffer.from('recovery'), agentId.toBuffer());
+
+// The recovery timelock is a week, so these tests run in bankrun
+describe('social recovery', () => {
+  const owner = Keypair.generate();
+  const newOwner = Keypair.generate();
+  const guardians = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let profile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([owner, newOwner, ...guardians]));
+    profile = await registerAgent(owner, {}, program);
+
+    await program.methods
+      .setGuardians(
+        guardians.map((guardian) => guardian.publicKey),
+        2
+      )
+      .accountsPartial({
+        agentProfile: profile,
+        registryStats: registryStatsPda,
+        owner: owner.publicKey,
+        skillFrequency: null,
+      })
+      .signers([owner])
+      .rpc();
+  });
+
+  async function recover(): Promise<void> {
+    await tick(context);
+    await program.methods
+      .recoverAccount()
+      .accountsPartial({
+        agentProfile: profile,
+        recovery: recoveryPda(owner.publicKey),
+        ownerIndex: ownerIndexPda(owner.publicKey),
+        newOwnerIndex: ownerIndexPda(newOwner.publicKey),
+        oldOwner: owner.publicKey,
+        initiator: guardians[0].publicKey,
+        registryStats: registryStatsPda,
+        payer: context.payer.publicKey,
+      })
+      .rpc();
+  }
+
+  it('moves the profile to a new owner once guardians approve and the timelock passes', async () => {
+    await program.methods
+      .initiateRecovery(newOwner.publicKey)
+      .accountsPartial({
+        agentProfile: profile,
+        recovery: recoveryPda(owner.publicKey),
+        registryStats: registryStatsPda,
+        guardian: guardians[0].publicKey,
+      })
+      .signers([guardians[0]])
+      .rpc();
+
+    expect(await fails(recover()), 'timelock').to.be.true;
+
+    // One approval of the two required
+    await tick(context, RECOVERY_TIMELOCK);
+    expect(await fails(recover()), 'threshold').to.be.true;
+
+    await program.methods
+      .approveRecovery()
+      .accountsPartial({
+        agentProfile: profile,
+        recovery: recoveryPda(owner.publicKey),
+        registryStats: registryStatsPda,
+        guardian: guardians[1].publicKey,
+      })
+      .signers([guardians[1]])
+      .rpc();
+    await recover();
+
+    const agent = await program.account.agentProfile.fetch(agentPda(owner.publicKey));
+    expect(agent.wallet.equals(newOwner.publicKey)).to.be.true;
+    expect((await program.account.ownerIndex.fetch(ownerIndexPda(newOwner.publicKey))).profile.equals(profile)).to.be
+      .true;
+    expect(await program.account.ownerIndex.fetchNullable(ownerIndexPda(owner.publicKey))).to.be.null;
+    expect(await program.account.recovery.fetchNullable(recoveryPda(owner.publicKey))).to.be.null;
+  });
+});
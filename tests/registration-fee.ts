@@ -0,0 +1,42 @@
+import { BN } from '@coral-xyz/anchor';
+import { expect } from 'chai';
+import {
+  asAuthority,
+  ensureRegistry,
+  newWallet,
+  program,
+  provider,
+  registerAgent,
+  treasuryPda,
+} from './helpers';
+
+describe('registration fee', () => {
+  const fee = new BN(10_000_000);
+
+  before(ensureRegistry);
+
+  after(async () => {
+    await program.methods.setFeeConfig(new BN(0), treasuryPda).accountsPartial(asAuthority()).rpc();
+  });
+
+  it('pays the fee into the treasury', async () => {
+    await program.methods.setFeeConfig(fee, treasuryPda).accountsPartial(asAuthority()).rpc();
+    const owner = await newWallet();
+
+    const before = await provider.connection.getBalance(treasuryPda);
+    await registerAgent(owner);
+    const after = await provider.connection.getBalance(treasuryPda);
+
+    expect(after - before).to.equal(fee.toNumber());
+  });
+
+  it('charges nothing once the fee is cleared', async () => {
+    await program.methods.setFeeConfig(new BN(0), treasuryPda).accountsPartial(asAuthority()).rpc();
+    const owner = await newWallet();
+
+    const before = await provider.connection.getBalance(treasuryPda);
+    await registerAgent(owner);
+
+    expect(await provider.connection.getBalance(treasuryPda)).to.equal(before);
+  });
+});
@@ -0,0 +1,59 @@
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { MockHook } from '../target/types/mock_hook';
+import { agentPda, ensureRegistry, newWallet, program, registerAgent } from './helpers';
+
+const hook = anchor.workspace.MockHook as Program<MockHook>;
+const hookState = PublicKey.findProgramAddressSync([Buffer.from('state')], hook.programId)[0];
+
+describe('registration hook', () => {
+  before(async () => {
+    await ensureRegistry();
+    if (!(await hook.account.hookState.fetchNullable(hookState))) {
+      await hook.methods.initialize().accountsPartial({ state: hookState }).rpc();
+    }
+  });
+
+  const hookOptions = {
+    hookProgram: hook.programId,
+    remainingAccounts: [
+      { pubkey: hook.programId, isSigner: false, isWritable: false },
+      { pubkey: hookState, isSigner: false, isWritable: true },
+    ],
+  };
+
+  it('calls the hook with the new profile', async () => {
+    const owner = await newWallet();
+    const before = (await hook.account.hookState.fetch(hookState)).registrations;
+
+    const profile = await registerAgent(owner, hookOptions);
+
+    const state = await hook.account.hookState.fetch(hookState);
+    expect(state.registrations.toNumber()).to.equal(before.toNumber() + 1);
+    expect(state.lastProfile.equals(profile)).to.be.true;
+    expect(state.lastOwner.equals(owner.publicKey)).to.be.true;
+    expect((await program.account.agentProfile.fetch(profile)).hookProgram?.equals(hook.programId)).to.be.true;
+  });
+
+  it('rolls the registration back when the hook fails', async () => {
+    const owner = await newWallet();
+    await hook.methods.setReject(true).accountsPartial({ state: hookState }).rpc();
+
+    try {
+      let failed = false;
+      try {
+        await registerAgent(owner, hookOptions);
+      } catch (err) {
+        // The hook's own error, raised inside the CPI
+        expect((err as { logs?: string[] }).logs?.join('\n')).to.include('Error Code: Rejected');
+        failed = true;
+      }
+      expect(failed).to.be.true;
+      expect(await program.account.agentProfile.fetchNullable(agentPda(owner.publicKey))).to.be.null;
+    } finally {
+      await hook.methods.setReject(false).accountsPartial({ state: hookState }).rpc();
+    }
+  });
+});
@@ -0,0 +1,61 @@
+import { BN } from '@coral-xyz/anchor';
+import { expect } from 'chai';
+import {
+  asAuthority,
+  endorse,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+  revoke,
+} from './helpers';
+
+describe('revoke cooldown', () => {
+  let cooldown: BN;
+
+  before(async () => {
+    await ensureRegistry();
+    cooldown = (await program.account.registryStats.fetch(registryStatsPda)).revokeCooldown;
+  });
+
+  after(async () => {
+    await program.methods.setRevokeCooldown(cooldown).accountsPartial(asAuthority()).rpc();
+  });
+
+  it('refuses to revoke a fresh endorsement', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    await registerAgent(target);
+    await registerAgent(endorser);
+
+    await program.methods.setRevokeCooldown(new BN(3_600)).accountsPartial(asAuthority()).rpc();
+    await endorse(endorser, target.publicKey);
+
+    await expectError(revoke(endorser, target.publicKey), 'RevokeTooSoon');
+  });
+
+  it('allows revoking once the cooldown is lifted', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    const targetProfile = await registerAgent(target);
+    await registerAgent(endorser);
+    const base = (await program.account.agentProfile.fetch(targetProfile)).reputation;
+
+    await program.methods.setRevokeCooldown(new BN(0)).accountsPartial(asAuthority()).rpc();
+    await endorse(endorser, target.publicKey);
+    await revoke(endorser, target.publicKey);
+
+    const profile = await program.account.agentProfile.fetch(targetProfile);
+    expect(profile.reputation).to.equal(base);
+    expect(profile.endorsementsReceived).to.equal(0);
+  });
+
+  it('rejects a negative cooldown', async () => {
+    await expectError(
+      program.methods.setRevokeCooldown(new BN(-1)).accountsPartial(asAuthority()).rpc(),
+      'InvalidParameter'
+    );
+  });
+});
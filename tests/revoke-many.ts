@@ -0,0 +1,152 @@
+import { BN } from '@coral-xyz/anchor';
+import { AccountMeta, Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  agentPda,
+  asAuthority,
+  endorse,
+  endorseAccounts,
+  endorserIndexPda,
+  ensureRegistry,
+  expectError,
+  historyPda,
+  newWallet,
+  pairCountPda,
+  pda,
+  program,
+  registerAgent,
+  registryStatsPda,
+  skillCountPda,
+  skillReputationPda,
+  uniqueName,
+} from './helpers';
+
+describe('revoke_many', () => {
+  let cooldown: BN;
+
+  before(async () => {
+    await ensureRegistry();
+    cooldown = (await program.account.registryStats.fetch(registryStatsPda)).revokeCooldown;
+    await program.methods.setRevokeCooldown(new BN(0)).accountsPartial(asAuthority()).rpc();
+  });
+
+  after(async () => {
+    await program.methods.setRevokeCooldown(cooldown).accountsPartial(asAuthority()).rpc();
+  });
+
+  // The six accounts `revoke_many` takes for each endorsement, with the
+  // target's profile overridable to test the pairing check
+  function entry(
+    endorsement: PublicKey,
+    endorser: PublicKey,
+    targetId: PublicKey,
+    skill: string,
+    profile = agentPda(targetId)
+  ): AccountMeta[] {
+    const targetProfile = agentPda(targetId);
+    return [
+      endorsement,
+      profile,
+      historyPda(targetId),
+      skillCountPda(targetProfile, skill),
+      skillReputationPda(targetProfile, skill),
+      pairCountPda(endorser, targetId),
+    ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+  }
+
+  function revokeMany(endorser: Keypair, remainingAccounts: AccountMeta[]) {
+    return program.methods
+      .revokeMany()
+      .accountsPartial({
+        endorserIndex: endorserIndexPda(endorser.publicKey),
+        endorserProfile: null,
+        leaderboard: null,
+        registryStats: registryStatsPda,
+        endorser: endorser.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([endorser])
+      .rpc();
+  }
+
+  it('revokes three endorsements across two targets in one call', async () => {
+    const endorser = await newWallet();
+    const first = await newWallet();
+    const second = await newWallet();
+    await registerAgent(endorser);
+    const firstProfile = await registerAgent(first, { skills: ['rust', 'go'] });
+    const secondProfile = await registerAgent(second);
+    const base = (await program.account.agentProfile.fetch(firstProfile)).reputation;
+
+    const endorsements = [
+      await endorse(endorser, first.publicKey, 'rust'),
+      await endorse(endorser, first.publicKey, 'go'),
+      await endorse(endorser, second.publicKey, 'rust'),
+    ];
+    const totalBefore = (await program.account.registryStats.fetch(registryStatsPda)).totalEndorsements;
+
+    await revokeMany(endorser, [
+      ...entry(endorsements[0], endorser.publicKey, first.publicKey, 'rust'),
+      ...entry(endorsements[1], endorser.publicKey, first.publicKey, 'go'),
+      ...entry(endorsements[2], endorser.publicKey, second.publicKey, 'rust'),
+    ]);
+
+    for (const endorsement of endorsements) {
+      expect(await program.account.endorsement.fetchNullable(endorsement)).to.be.null;
+    }
+    for (const profile of [firstProfile, secondProfile]) {
+      const agent = await program.account.agentProfile.fetch(profile);
+      expect(agent.reputation).to.equal(base);
+      expect(agent.endorsementsReceived).to.equal(0);
+    }
+    const stats = await program.account.registryStats.fetch(registryStatsPda);
+    expect(totalBefore.sub(stats.totalEndorsements).toNumber()).to.equal(3);
+    expect((await program.account.endorserIndex.fetch(endorserIndexPda(endorser.publicKey))).endorsements).to.be.empty;
+  });
+
+  it('fails the whole call when a profile is not the endorsement target', async () => {
+    const endorser = await newWallet();
+    const first = await newWallet();
+    const second = await newWallet();
+    await registerAgent(endorser);
+    await registerAgent(first);
+    await registerAgent(second);
+    const firstEndorsement = await endorse(endorser, first.publicKey);
+    const secondEndorsement = await endorse(endorser, second.publicKey);
+
+    await expectError(
+      revokeMany(endorser, [
+        ...entry(firstEndorsement, endorser.publicKey, first.publicKey, 'rust'),
+        ...entry(secondEndorsement, endorser.publicKey, second.publicKey, 'rust', agentPda(first.publicKey)),
+      ]),
+      'EndorsementTargetMismatch'
+    );
+
+    expect(await program.account.endorsement.fetchNullable(firstEndorsement)).to.not.be.null;
+  });
+
+  it('drops the target from a skill leaderboard passed as a seventh account', async () => {
+    const skill = uniqueName('skill');
+    const board = pda(Buffer.from('skill_leaderboard'), Buffer.from(skill));
+    await program.methods.initializeSkillLeaderboard(skill).accountsPartial({ skillLeaderboard: board }).rpc();
+
+    const endorser = await newWallet();
+    const target = await newWallet();
+    await registerAgent(endorser);
+    await registerAgent(target, { skills: [skill] });
+    const accounts = endorseAccounts(endorser.publicKey, endorser.publicKey, target.publicKey, skill);
+    await program.methods
+      .endorseSkill(skill, 3, null)
+      .accountsPartial({ ...accounts, skillLeaderboard: board })
+      .signers([endorser])
+      .rpc();
+    expect((await program.account.skillLeaderboard.fetch(board)).len).to.equal(1);
+
+    await revokeMany(endorser, [
+      ...entry(accounts.endorsement, endorser.publicKey, target.publicKey, skill),
+      { pubkey: board, isSigner: false, isWritable: true },
+    ]);
+
+    expect((await program.account.skillLeaderboard.fetch(board)).len).to.equal(0);
+  });
+});
@@ -0,0 +1,93 @@
+import { AccountMeta, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  agentPda,
+  endorse,
+  endorsementPda,
+  endorserIndexPda,
+  ensureRegistry,
+  historyPda,
+  newWallet,
+  pairCountPda,
+  pda,
+  program,
+  registerAgent,
+  registryStatsPda,
+  skillCountPda,
+  skillFrequencyPda,
+  skillReputationPda,
+  uniqueName,
+} from './helpers';
+
+const writable = (pubkey: PublicKey): AccountMeta => ({ pubkey, isSigner: false, isWritable: true });
+
+describe('per-skill reputation', () => {
+  before(ensureRegistry);
+
+  it('credits only the endorsed skill', async () => {
+    const target = await newWallet();
+    const first = await newWallet();
+    const second = await newWallet();
+    const targetProfile = await registerAgent(target, { skills: ['rust', 'solana'] });
+    await registerAgent(first);
+    await registerAgent(second);
+
+    const endorsement = await endorse(first, target.publicKey, 'rust');
+    const { skillWeight } = await program.account.endorsement.fetch(endorsement);
+    expect(skillWeight).to.be.greaterThan(0);
+
+    const rust = await program.account.skillReputation.fetch(skillReputationPda(targetProfile, 'rust'));
+    expect(rust.skill).to.equal('rust');
+    expect(rust.score).to.equal(skillWeight);
+    expect(await program.account.skillReputation.fetchNullable(skillReputationPda(targetProfile, 'solana'))).to
+      .be.null;
+
+    await endorse(second, target.publicKey, 'rust');
+    const after = await program.account.skillReputation.fetch(skillReputationPda(targetProfile, 'rust'));
+    expect(after.score).to.be.greaterThan(rust.score);
+  });
+
+  it('ranks a batch endorsement on the skill leaderboards supplied', async () => {
+    const skill = uniqueName('skill');
+    const board = pda(Buffer.from('skill_leaderboard'), Buffer.from(skill));
+    await program.methods.initializeSkillLeaderboard(skill).accountsPartial({ skillLeaderboard: board }).rpc();
+
+    const target = await newWallet();
+    const endorser = await newWallet();
+    const targetProfile = await registerAgent(target, { skills: ['rust', skill] });
+    await registerAgent(endorser);
+
+    // `rust` has no board, so its fifth account is skipped
+    const remainingAccounts = ['rust', skill].flatMap((name) =>
+      [
+        endorsementPda(endorser.publicKey, target.publicKey, name),
+        skillCountPda(targetProfile, name),
+        skillReputationPda(targetProfile, name),
+        endorsementPda(target.publicKey, endorser.publicKey, name),
+        pda(Buffer.from('skill_leaderboard'), Buffer.from(name)),
+      ].map(writable)
+    );
+    await program.methods
+      .endorseSkills(['rust', skill], 3)
+      .accountsPartial({
+        endorserProfile: agentPda(endorser.publicKey),
+        targetAgent: targetProfile,
+        reputationHistory: historyPda(target.publicKey),
+        leaderboard: null,
+        pairCount: pairCountPda(endorser.publicKey, target.publicKey),
+        endorserIndex: endorserIndexPda(endorser.publicKey),
+        skillFrequency: skillFrequencyPda,
+        registryStats: registryStatsPda,
+        endorser: endorser.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([endorser])
+      .rpc();
+
+    const { score } = await program.account.skillReputation.fetch(skillReputationPda(targetProfile, skill));
+    const ranking = await program.account.skillLeaderboard.fetch(board);
+    expect(ranking.len).to.equal(1);
+    expect(ranking.entries[0].agent.equals(target.publicKey)).to.be.true;
+    expect(ranking.entries[0].reputation).to.equal(score);
+  });
+});
@@ -0,0 +1,63 @@
+import { BN } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  authority,
+  endorse,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  pda,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+const snapshotPda = (snapshotId: BN, agentId: PublicKey) =>
+  pda(Buffer.from('reputation_snapshot'), snapshotId.toArrayLike(Buffer, 'le', 8), agentId.toBuffer());
+
+describe('reputation snapshots', () => {
+  before(ensureRegistry);
+
+  function snapshot(snapshotId: BN, profile: PublicKey, agentId: PublicKey, signer: Keypair = authority) {
+    return program.methods
+      .snapshotReputation(snapshotId)
+      .accountsPartial({
+        agentProfile: profile,
+        reputationSnapshot: snapshotPda(snapshotId, agentId),
+        registryStats: registryStatsPda,
+        signer: signer.publicKey,
+        payer: signer.publicKey,
+      })
+      .signers(signer === authority ? [] : [signer])
+      .rpc();
+  }
+
+  it('records the reputation at the snapshot', async () => {
+    const target = await newWallet();
+    const endorser = await newWallet();
+    const profile = await registerAgent(target);
+    await registerAgent(endorser);
+
+    const snapshotId = new BN(Date.now());
+    await snapshot(snapshotId, profile, target.publicKey);
+    const recorded = await program.account.reputationSnapshot.fetch(snapshotPda(snapshotId, target.publicKey));
+    const before = (await program.account.agentProfile.fetch(profile)).reputation;
+    expect(recorded.reputation).to.equal(before);
+    expect(recorded.slot.toNumber()).to.be.above(0);
+
+    // Later endorsements move the profile, not the snapshot
+    await endorse(endorser, target.publicKey);
+    expect((await program.account.agentProfile.fetch(profile)).reputation).to.be.above(before);
+    const after = await program.account.reputationSnapshot.fetch(snapshotPda(snapshotId, target.publicKey));
+    expect(after.reputation).to.equal(before);
+  });
+
+  it('only lets the authority or snapshot authority create one', async () => {
+    const target = await newWallet();
+    const stranger = await newWallet();
+    const profile = await registerAgent(target);
+
+    await expectError(snapshot(new BN(Date.now()), profile, target.publicKey, stranger), 'Unauthorized');
+  });
+});
@@ -0,0 +1,59 @@
+import { Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import { clockTime, registerAgent, registryStatsPda, startBankrun, tick } from './helpers';
+
+const HOUR = 60 * 60;
+const DAY = 24 * HOUR;
+
+// Streaks count calendar days, so these tests run in bankrun
+describe('activity streaks', () => {
+  const owner = Keypair.generate();
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let profile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([owner]));
+    profile = await registerAgent(owner, {}, program);
+  });
+
+  async function heartbeat(seconds: number): Promise<[number, number]> {
+    await tick(context, seconds);
+    await program.methods
+      .heartbeat()
+      .accountsPartial({
+        agentProfile: profile,
+        delegate: null,
+        registryStats: registryStatsPda,
+        signer: owner.publicKey,
+        reputationHistory: null,
+        skillFrequency: null,
+      })
+      .signers([owner])
+      .rpc();
+    const agent = await program.account.agentProfile.fetch(profile);
+    return [agent.currentStreak, agent.longestStreak];
+  }
+
+  it('extends on consecutive days and starts over after a skipped one', async () => {
+    // 01:00 on a fresh day
+    const now = await clockTime(context);
+    expect(await heartbeat(DAY - (now % DAY) + HOUR)).to.deep.equal([1, 1]);
+
+    // 03:00 the same day
+    expect(await heartbeat(2 * HOUR)).to.deep.equal([1, 1]);
+
+    // 00:30 the next day: under a day later, but a new calendar day
+    expect(await heartbeat(DAY - 3 * HOUR + HOUR / 2)).to.deep.equal([2, 2]);
+
+    // 23:30 the day after: almost two days later, still consecutive
+    expect(await heartbeat(DAY + 23 * HOUR)).to.deep.equal([3, 3]);
+
+    // Skipping a whole day resets the streak but keeps the longest
+    expect(await heartbeat(2 * DAY)).to.deep.equal([1, 3]);
+    expect(await heartbeat(DAY)).to.deep.equal([2, 3]);
+  });
+});
@@ -0,0 +1,91 @@
+import { BN, Program } from '@coral-xyz/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { expect } from 'chai';
+import { createHash } from 'crypto';
+import { ProgramTestContext } from 'solana-bankrun';
+import { Agentvault } from '../target/types/agentvault';
+import { fails, historyPda, pda, registerAgent, registryStatsPda, startBankrun, tick } from './helpers';
+
+const DELAY = 60 * 60;
+
+// The delay has to pass, so these tests run in bankrun
+describe('timelock', () => {
+  const owner = Keypair.generate();
+  let context: ProgramTestContext;
+  let program: Program<Agentvault>;
+  let profile: PublicKey;
+
+  before(async () => {
+    ({ context, program } = await startBankrun([owner]));
+    profile = await registerAgent(owner, {}, program);
+    await program.methods
+      .setTimelockDelay(new BN(DELAY))
+      .accountsPartial({ registryStats: registryStatsPda, authority: context.payer.publicKey })
+      .rpc();
+  });
+
+  // Queue `action` and wait out the delay, returning its `QueuedAction`
+  async function queue(action: any): Promise<PublicKey> {
+    const encoded = program.coder.types.encode('TimelockedAction', action);
+    const hash = createHash('sha256').update(encoded).digest();
+    await program.methods
+      .queueAction([...hash])
+      .accountsPartial({ registryStats: registryStatsPda, authority: context.payer.publicKey })
+      .rpc();
+    await tick(context, DELAY);
+    return pda(Buffer.from('queued_action'), hash);
+  }
+
+  function slash(queuedAction: PublicKey | null) {
+    return program.methods
+      .slashAgent(5, new BN(0), 0)
+      .accountsPartial({
+        agentProfile: profile,
+        reputationHistory: historyPda(owner.publicKey),
+        registryStats: registryStatsPda,
+        moderator: null,
+        signer: context.payer.publicKey,
+        queuedAction,
+        stakeMint: null,
+        stakeVault: null,
+        stakeTreasury: null,
+        tokenProgram: null,
+        leaderboard: null,
+      })
+      .rpc();
+  }
+
+  it('refuses the direct setters while a delay is set', async () => {
+    const setFloor = program.methods
+      .setVerifiedFloor(10)
+      .accountsPartial({ registryStats: registryStatsPda, authority: context.payer.publicKey })
+      .rpc();
+    expect(await fails(setFloor)).to.be.true;
+
+    const queuedAction = await queue({ verifiedFloor: { verifiedFloor: 10 } });
+    await program.methods
+      .executeAction({ verifiedFloor: { verifiedFloor: 10 } })
+      .accountsPartial({ queuedAction, registryStats: registryStatsPda, authority: context.payer.publicKey })
+      .rpc();
+    expect((await program.account.registryStats.fetch(registryStatsPda)).verifiedFloor).to.equal(10);
+  });
+
+  it('only slashes once the same slash has been queued', async () => {
+    const before = (await program.account.agentProfile.fetch(profile)).reputation;
+    expect(await fails(slash(null))).to.be.true;
+
+    // A queued slash of a different size doesn't count
+    const other = await queue({
+      slash: { agentId: owner.publicKey, reputationPenalty: 9, stakePenalty: new BN(0), reason: 0 },
+    });
+    expect(await fails(slash(other))).to.be.true;
+
+    const queuedAction = await queue({
+      slash: { agentId: owner.publicKey, reputationPenalty: 5, stakePenalty: new BN(0), reason: 0 },
+    });
+    await slash(queuedAction);
+
+    expect((await program.account.agentProfile.fetch(profile)).reputation).to.equal(before - 5);
+    expect(await context.banksClient.getAccount(queuedAction)).to.be.null;
+  });
+});
@@ -0,0 +1,51 @@
+import { expect } from 'chai';
+import {
+  authority,
+  ensureRegistry,
+  expectError,
+  newWallet,
+  program,
+  registerAgent,
+  registryStatsPda,
+} from './helpers';
+
+describe('verification badges', () => {
+  before(ensureRegistry);
+
+  it('lets the authority set a verification tier', async () => {
+    const agent = await newWallet();
+    const profile = await registerAgent(agent);
+
+    await program.methods
+      .setVerification(2)
+      .accountsPartial({
+        agentProfile: profile,
+        registryStats: registryStatsPda,
+        moderator: null,
+        signer: authority.publicKey,
+      })
+      .rpc();
+
+    expect((await program.account.agentProfile.fetch(profile)).verificationTier).to.equal(2);
+  });
+
+  it('rejects anyone else', async () => {
+    const agent = await newWallet();
+    const stranger = await newWallet();
+    const profile = await registerAgent(agent);
+
+    await expectError(
+      program.methods
+        .setVerification(1)
+        .accountsPartial({
+          agentProfile: profile,
+          registryStats: registryStatsPda,
+          moderator: null,
+          signer: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      'Unauthorized'
+    );
+  });
+});